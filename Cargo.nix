@@ -129,6 +129,21 @@ rec {
           "rustc-dep-of-std" = [ "core" "compiler_builtins" ];
         };
       };
+      "adler2" = rec {
+        crateName = "adler2";
+        version = "2.0.1";
+        edition = "2021";
+        sha256 = "1ymy18s9hs7ya1pjc9864l30wk8p2qfqdi7mhhcc5nfakxbij09j";
+        authors = [
+          "Jonas Schievink <jonasschievink@gmail.com>"
+          "oyvindln <oyvindln@users.noreply.github.com>"
+        ];
+        features = {
+          "core" = [ "dep:core" ];
+          "default" = [ "std" ];
+          "rustc-dep-of-std" = [ "core" ];
+        };
+      };
       "aead" = rec {
         crateName = "aead";
         version = "0.5.2";
@@ -492,6 +507,31 @@ rec {
         };
         resolvedDefaultFeatures = [ "default" "std" ];
       };
+      "arrayref" = rec {
+        crateName = "arrayref";
+        version = "0.3.9";
+        edition = "2015";
+        sha256 = "1jzyp0nvp10dmahaq9a2rnxqdd5wxgbvp8xaibps3zai8c9fi8kn";
+        authors = [
+          "David Roundy <roundyd@physics.oregonstate.edu>"
+        ];
+
+      };
+      "arrayvec" = rec {
+        crateName = "arrayvec";
+        version = "0.7.8";
+        edition = "2018";
+        sha256 = "0mmd8lrijbvg1qp4c5zis5dq41a3mjv2rb6bxkyj9kwaw2k6gyyk";
+        authors = [
+          "bluss"
+        ];
+        features = {
+          "borsh" = [ "dep:borsh" ];
+          "default" = [ "std" ];
+          "serde" = [ "dep:serde" ];
+          "zeroize" = [ "dep:zeroize" ];
+        };
+      };
       "async-stream" = rec {
         crateName = "async-stream";
         version = "0.3.6";
@@ -728,7 +768,7 @@ rec {
           }
           {
             name = "miniz_oxide";
-            packageId = "miniz_oxide";
+            packageId = "miniz_oxide 0.7.4";
             usesDefaultFeatures = false;
             target = { target, features }: (!((target."windows" or false) && ("msvc" == target."env" or null) && (!("uwp" == target."vendor" or null))));
           }
@@ -990,6 +1030,7 @@ rec {
           "extern_crate_std" = [ "extern_crate_alloc" ];
           "latest_stable_rust" = [ "aarch64_simd" "align_offset" "alloc_uninit" "const_zeroed" "derive" "min_const_generics" "must_cast" "track_caller" "wasm_simd" "zeroable_atomics" "zeroable_maybe_uninit" ];
         };
+        resolvedDefaultFeatures = [ "aarch64_simd" "extern_crate_alloc" ];
       };
       "byteorder" = rec {
         crateName = "byteorder";
@@ -1004,6 +1045,17 @@ rec {
         };
         resolvedDefaultFeatures = [ "default" "std" ];
       };
+      "byteorder-lite" = rec {
+        crateName = "byteorder-lite";
+        version = "0.1.0";
+        edition = "2021";
+        sha256 = "15alafmz4b9az56z6x7glcbcb6a8bfgyd109qc3bvx07zx4fj7wg";
+        libName = "byteorder_lite";
+        features = {
+          "default" = [ "std" ];
+        };
+        resolvedDefaultFeatures = [ "default" "std" ];
+      };
       "bytes" = rec {
         crateName = "bytes";
         version = "1.9.0";
@@ -1430,6 +1482,16 @@ rec {
           }
         ];
 
+      };
+      "color_quant" = rec {
+        crateName = "color_quant";
+        version = "1.1.0";
+        edition = "2015";
+        sha256 = "12q1n427h2bbmmm1mnglr57jaz2dj9apk0plcxw7nwqiai7qjyrx";
+        authors = [
+          "nwin <nwin@users.noreply.github.com>"
+        ];
+
       };
       "colorchoice" = rec {
         crateName = "colorchoice";
@@ -1597,6 +1659,22 @@ rec {
         };
         resolvedDefaultFeatures = [ "default" "link" ];
       };
+      "core_maths" = rec {
+        crateName = "core_maths";
+        version = "0.1.1";
+        edition = "2015";
+        sha256 = "0c0dv11ixxpc9bsx5xasvl98mb1dlprzcm6qq6ls3nsygw0mwx3p";
+        authors = [
+          "Robert Bastian <me@robertbastian.dev"
+        ];
+        dependencies = [
+          {
+            name = "libm";
+            packageId = "libm";
+          }
+        ];
+
+      };
       "cpufeatures" = rec {
         crateName = "cpufeatures";
         version = "0.2.16";
@@ -1657,6 +1735,26 @@ rec {
         ];
 
       };
+      "crc32fast" = rec {
+        crateName = "crc32fast";
+        version = "1.5.2";
+        edition = "2021";
+        sha256 = "0y0f955n2hr5a8rd9nw9sr23nhjc42ddx3bjc47dnlmqssgpk9q1";
+        authors = [
+          "Sam Rijs <srijs@airpost.net>"
+          "Alex Crichton <alex@alexcrichton.com>"
+        ];
+        dependencies = [
+          {
+            name = "cfg-if";
+            packageId = "cfg-if";
+          }
+        ];
+        features = {
+          "default" = [ "std" ];
+        };
+        resolvedDefaultFeatures = [ "default" "std" ];
+      };
       "crossbeam-deque" = rec {
         crateName = "crossbeam-deque";
         version = "0.8.6";
@@ -2133,6 +2231,21 @@ rec {
           "typesize" = [ "dep:typesize" ];
         };
       };
+      "data-url" = rec {
+        crateName = "data-url";
+        version = "0.3.2";
+        edition = "2018";
+        sha256 = "0xl30jidc8s3kh2z3nvnn1nyzhbq5b2wpiqwzj9gjdrndk50n7my";
+        libName = "data_url";
+        authors = [
+          "Simon Sapin <simon.sapin@exyr.org>"
+        ];
+        features = {
+          "default" = [ "std" ];
+          "std" = [ "alloc" ];
+        };
+        resolvedDefaultFeatures = [ "alloc" "default" "std" ];
+      };
       "der" = rec {
         crateName = "der";
         version = "0.7.9";
@@ -3025,6 +3138,33 @@ rec {
         ];
 
       };
+      "euclid" = rec {
+        crateName = "euclid";
+        version = "0.22.14";
+        edition = "2021";
+        sha256 = "01ksjl4vb8ms89laswnjpld3z4n6c1s7qlqq0djx3imiwdjm787i";
+        authors = [
+          "The Servo Project Developers"
+        ];
+        dependencies = [
+          {
+            name = "num-traits";
+            packageId = "num-traits";
+            usesDefaultFeatures = false;
+          }
+        ];
+        features = {
+          "arbitrary" = [ "dep:arbitrary" ];
+          "bytemuck" = [ "dep:bytemuck" ];
+          "default" = [ "std" ];
+          "libm" = [ "num-traits/libm" ];
+          "malloc_size_of" = [ "dep:malloc_size_of" ];
+          "mint" = [ "dep:mint" ];
+          "serde" = [ "dep:serde" ];
+          "std" = [ "num-traits/std" ];
+        };
+        resolvedDefaultFeatures = [ "std" ];
+      };
       "event-listener" = rec {
         crateName = "event-listener";
         version = "5.3.1";
@@ -3104,6 +3244,22 @@ rec {
         };
         resolvedDefaultFeatures = [ "alloc" "default" "std" ];
       };
+      "fdeflate" = rec {
+        crateName = "fdeflate";
+        version = "0.3.7";
+        edition = "2021";
+        sha256 = "130ga18vyxbb5idbgi07njymdaavvk6j08yh1dfarm294ssm6s0y";
+        authors = [
+          "The image-rs Developers"
+        ];
+        dependencies = [
+          {
+            name = "simd-adler32";
+            packageId = "simd-adler32";
+          }
+        ];
+
+      };
       "ff" = rec {
         crateName = "ff";
         version = "0.13.1";
@@ -3244,6 +3400,72 @@ rec {
         ];
 
       };
+      "flate2" = rec {
+        crateName = "flate2";
+        version = "1.1.10";
+        edition = "2018";
+        sha256 = "1jvd2cl8j5hyf8imi62y1x7gwzz1hajirni0801yxhds1qp4wqvf";
+        authors = [
+          "Alex Crichton <alex@alexcrichton.com>"
+          "Josh Triplett <josh@joshtriplett.org>"
+        ];
+        dependencies = [
+          {
+            name = "crc32fast";
+            packageId = "crc32fast";
+            optional = true;
+            usesDefaultFeatures = false;
+          }
+          {
+            name = "miniz_oxide";
+            packageId = "miniz_oxide 0.9.1";
+            optional = true;
+            features = [ "simd" ];
+          }
+          {
+            name = "zlib-rs";
+            packageId = "zlib-rs";
+            optional = true;
+            usesDefaultFeatures = false;
+            features = [ "rust-allocator" ];
+          }
+        ];
+        features = {
+          "any_c_zlib" = [ "any_zlib" ];
+          "any_zlib" = [ "any_impl" ];
+          "cloudflare_zlib" = [ "zlib" ];
+          "default" = [ "rust_backend" "runtime_detection" ];
+          "document-features" = [ "dep:document-features" ];
+          "libz-ng-sys" = [ "dep:libz-ng-sys" ];
+          "libz-sys" = [ "dep:libz-sys" ];
+          "miniz-sys" = [ "rust_backend" ];
+          "miniz_oxide" = [ "any_impl" "dep:miniz_oxide" "dep:crc32fast" ];
+          "runtime_detection" = [ "zlib-rs?/std" "crc32fast?/std" ];
+          "rust_backend" = [ "miniz_oxide" "any_impl" ];
+          "zlib" = [ "any_c_zlib" "libz-sys" "dep:crc32fast" ];
+          "zlib-default" = [ "any_c_zlib" "libz-sys/default" "dep:crc32fast" ];
+          "zlib-ng" = [ "any_c_zlib" "libz-ng-sys" "dep:crc32fast" ];
+          "zlib-ng-compat" = [ "zlib" "libz-sys/zlib-ng" "dep:crc32fast" ];
+          "zlib-rs" = [ "any_zlib" "dep:zlib-rs" ];
+        };
+        resolvedDefaultFeatures = [ "any_impl" "default" "miniz_oxide" "runtime_detection" "rust_backend" ];
+      };
+      "float-cmp" = rec {
+        crateName = "float-cmp";
+        version = "0.9.0";
+        edition = "2018";
+        sha256 = "1i799ksbq7fj9rm9m82g1yqgm6xi3jnrmylddmqknmksajylpplq";
+        libName = "float_cmp";
+        authors = [
+          "Mike Dilger <mike@mikedilger.com>"
+        ];
+        features = {
+          "default" = [ "ratio" ];
+          "num-traits" = [ "dep:num-traits" ];
+          "ratio" = [ "num-traits" ];
+        };
+        resolvedDefaultFeatures = [ "std" ];
+      };
       "flume" = rec {
         crateName = "flume";
         version = "0.11.1";
@@ -3309,6 +3531,77 @@ rec {
         };
         resolvedDefaultFeatures = [ "default" "std" ];
       };
+      "fontconfig-parser" = rec {
+        crateName = "fontconfig-parser";
+        version = "0.5.8";
+        edition = "2018";
+        sha256 = "0ijnbzg31sl6v49g7q2l7sl76hjj8z0hvlsz77cdvm029vi77ixv";
+        libName = "fontconfig_parser";
+        dependencies = [
+          {
+            name = "roxmltree";
+            packageId = "roxmltree";
+          }
+        ];
+        features = {
+          "log" = [ "dep:log" ];
+          "serde" = [ "dep:serde" ];
+          "serialize" = [ "serde" ];
+        };
+      };
+      "fontdb" = rec {
+        crateName = "fontdb";
+        version = "0.23.0";
+        edition = "2018";
+        sha256 = "0199vry9x8zn9ix4x4rqvv53dy2ryhy68l53jwr580hj7ndphzj5";
+        authors = [
+          "Yevhenii Reizner <razrfalcon@gmail.com>"
+        ];
+        dependencies = [
+          {
+            name = "fontconfig-parser";
+            packageId = "fontconfig-parser";
+            optional = true;
+            usesDefaultFeatures = false;
+            target = { target, features }: ((target."unix" or false) && (!(("macos" == target."os" or null) || ("android" == target."os" or null))));
+          }
+          {
+            name = "log";
+            packageId = "log";
+          }
+          {
+            name = "memmap2";
+            packageId = "memmap2";
+            optional = true;
+          }
+          {
+            name = "slotmap";
+            packageId = "slotmap";
+            usesDefaultFeatures = false;
+          }
+          {
+            name = "tinyvec";
+            packageId = "tinyvec";
+            features = [ "alloc" ];
+          }
+          {
+            name = "ttf-parser";
+            packageId = "ttf-parser";
+            usesDefaultFeatures = false;
+            features = [ "opentype-layout" "apple-layout" "variable-fonts" "glyph-names" "no-std-float" ];
+          }
+        ];
+        features = {
+          "default" = [ "std" "fs" "memmap" "fontconfig" ];
+          "fontconfig" = [ "fontconfig-parser" "fs" ];
+          "fontconfig-parser" = [ "dep:fontconfig-parser" ];
+          "fs" = [ "std" ];
+          "memmap" = [ "fs" "memmap2" ];
+          "memmap2" = [ "dep:memmap2" ];
+          "std" = [ "ttf-parser/std" ];
+        };
+        resolvedDefaultFeatures = [ "fontconfig" "fontconfig-parser" "fs" "memmap" "memmap2" "std" ];
+      };
       "foreign-types" = rec {
         crateName = "foreign-types";
         version = "0.3.2";
@@ -3976,6 +4269,31 @@ rec {
           "zeroize" = [ "dep:zeroize" ];
         };
       };
+      "gif" = rec {
+        crateName = "gif";
+        version = "0.13.3";
+        edition = "2021";
+        sha256 = "06z6gll24q7psbz9fb86jbcbmgwnxkym8jsp0fbq5qikbqilgq2a";
+        authors = [
+          "The image-rs Developers"
+        ];
+        dependencies = [
+          {
+            name = "color_quant";
+            packageId = "color_quant";
+            optional = true;
+          }
+          {
+            name = "weezl";
+            packageId = "weezl";
+          }
+        ];
+        features = {
+          "color_quant" = [ "dep:color_quant" ];
+          "default" = [ "raii_no_panic" "std" "color_quant" ];
+        };
+        resolvedDefaultFeatures = [ "color_quant" "default" "raii_no_panic" "std" ];
+      };
       "gimli" = rec {
         crateName = "gimli";
         version = "0.28.1";
@@ -5796,6 +6114,35 @@ rec {
         features = {
         };
       };
+      "image-webp" = rec {
+        crateName = "image-webp";
+        version = "0.2.4";
+        edition = "2021";
+        sha256 = "1hz814csyi9283vinzlkix6qpnd6hs3fkw7xl6z2zgm4w7rrypjj";
+        libName = "image_webp";
+        dependencies = [
+          {
+            name = "byteorder-lite";
+            packageId = "byteorder-lite";
+          }
+          {
+            name = "quick-error";
+            packageId = "quick-error";
+          }
+        ];
+        features = {
+        };
+      };
+      "imagesize" = rec {
+        crateName = "imagesize";
+        version = "0.13.0";
+        edition = "2021";
+        sha256 = "11f26ac9zvbr7sjnsv2z9jd3ryaz40pg8xch4ij1q1rg5zbjgkgd";
+        authors = [
+          "Maid Dog <maiddogsrl@gmail.com>"
+        ];
+
+      };
       "indenter" = rec {
         crateName = "indenter";
         version = "0.3.3";
@@ -6123,6 +6470,42 @@ rec {
         ];
 
       };
+      "kurbo" = rec {
+        crateName = "kurbo";
+        version = "0.11.3";
+        edition = "2021";
+        sha256 = "0qiwq4l83fy9v5d1piywvh44yg9ha3rl04d2kdcqlvvm8jp2c866";
+        authors = [
+          "Raph Levien <raph.levien@gmail.com>"
+        ];
+        dependencies = [
+          {
+            name = "arrayvec";
+            packageId = "arrayvec";
+            usesDefaultFeatures = false;
+          }
+          {
+            name = "euclid";
+            packageId = "euclid";
+            optional = true;
+            usesDefaultFeatures = false;
+          }
+          {
+            name = "smallvec";
+            packageId = "smallvec";
+          }
+        ];
+        features = {
+          "default" = [ "std" ];
+          "euclid" = [ "dep:euclid" ];
+          "libm" = [ "dep:libm" "euclid?/libm" ];
+          "mint" = [ "dep:mint" ];
+          "schemars" = [ "schemars/smallvec" "dep:schemars" ];
+          "serde" = [ "smallvec/serde" "dep:serde" ];
+          "std" = [ "euclid?/std" ];
+        };
+        resolvedDefaultFeatures = [ "default" "std" ];
+      };
       "lazy_static" = rec {
         crateName = "lazy_static";
         version = "1.5.0";
@@ -6569,6 +6952,27 @@ rec {
         };
         resolvedDefaultFeatures = [ "alloc" "default" "std" ];
       };
+      "memmap2" = rec {
+        crateName = "memmap2";
+        version = "0.9.11";
+        edition = "2021";
+        sha256 = "1h4qnzgarnn488ljjpg9ns5y4bw0sq0xv0fj0iqywagjnz8rw8fi";
+        authors = [
+          "Dan Burkert <dan@danburkert.com>"
+          "Yevhenii Reizner <razrfalcon@gmail.com>"
+          "The Contributors"
+        ];
+        dependencies = [
+          {
+            name = "libc";
+            packageId = "libc";
+            target = { target, features }: (target."unix" or false);
+          }
+        ];
+        features = {
+          "stable_deref_trait" = [ "dep:stable_deref_trait" ];
+        };
+      };
       "mime" = rec {
         crateName = "mime";
         version = "0.3.17";
@@ -6593,7 +6997,7 @@ rec {
         };
         resolvedDefaultFeatures = [ "std" ];
       };
-      "miniz_oxide" = rec {
+      "miniz_oxide 0.7.4" = rec {
         crateName = "miniz_oxide";
         version = "0.7.4";
         edition = "2018";
@@ -6619,6 +7023,75 @@ rec {
           "simd-adler32" = [ "dep:simd-adler32" ];
         };
       };
+      "miniz_oxide 0.8.9" = rec {
+        crateName = "miniz_oxide";
+        version = "0.8.9";
+        edition = "2021";
+        sha256 = "05k3pdg8bjjzayq3rf0qhpirq9k37pxnasfn4arbs17phqn6m9qz";
+        authors = [
+          "Frommi <daniil.liferenko@gmail.com>"
+          "oyvindln <oyvindln@users.noreply.github.com>"
+          "Rich Geldreich richgel99@gmail.com"
+        ];
+        dependencies = [
+          {
+            name = "adler2";
+            packageId = "adler2";
+            usesDefaultFeatures = false;
+          }
+          {
+            name = "simd-adler32";
+            packageId = "simd-adler32";
+            optional = true;
+            usesDefaultFeatures = false;
+          }
+        ];
+        features = {
+          "alloc" = [ "dep:alloc" ];
+          "core" = [ "dep:core" ];
+          "default" = [ "with-alloc" ];
+          "rustc-dep-of-std" = [ "core" "alloc" "adler2/rustc-dep-of-std" ];
+          "serde" = [ "dep:serde" ];
+          "simd" = [ "simd-adler32" ];
+          "simd-adler32" = [ "dep:simd-adler32" ];
+        };
+        resolvedDefaultFeatures = [ "default" "simd" "simd-adler32" "with-alloc" ];
+      };
+      "miniz_oxide 0.9.1" = rec {
+        crateName = "miniz_oxide";
+        version = "0.9.1";
+        edition = "2021";
+        sha256 = "0k2bgjzk2sbsynpsv4wizwxbqp6vs7g08y5anbkrh3l6a15bqgxn";
+        authors = [
+          "Frommi <daniil.liferenko@gmail.com>"
+          "oyvindln <oyvindln@users.noreply.github.com>"
+          "Rich Geldreich richgel99@gmail.com"
+        ];
+        dependencies = [
+          {
+            name = "adler2";
+            packageId = "adler2";
+            usesDefaultFeatures = false;
+          }
+          {
+            name = "simd-adler32";
+            packageId = "simd-adler32";
+            optional = true;
+            usesDefaultFeatures = false;
+          }
+        ];
+        features = {
+          "alloc" = [ "dep:alloc" ];
+          "core" = [ "dep:core" ];
+          "default" = [ "with-alloc" ];
+          "rustc-dep-of-std" = [ "core" "alloc" "adler2/rustc-dep-of-std" ];
+          "serde" = [ "dep:serde" ];
+          "simd" = [ "simd-adler32" ];
+          "simd-adler32" = [ "dep:simd-adler32" ];
+          "std" = [ "serde?/std" ];
+        };
+        resolvedDefaultFeatures = [ "default" "simd" "simd-adler32" "with-alloc" ];
+      };
       "mio" = rec {
         crateName = "mio";
         version = "1.0.3";
@@ -8300,7 +8773,7 @@ rec {
         dependencies = [
           {
             name = "siphasher";
-            packageId = "siphasher";
+            packageId = "siphasher 0.3.11";
           }
         ];
         features = {
@@ -8310,6 +8783,19 @@ rec {
         };
         resolvedDefaultFeatures = [ "default" "std" ];
       };
+      "pico-args" = rec {
+        crateName = "pico-args";
+        version = "0.5.0";
+        edition = "2018";
+        sha256 = "05d30pvxd6zlnkg2i3ilr5a70v3f3z2in18m67z25vinmykngqav";
+        libName = "pico_args";
+        authors = [
+          "Yevhenii Reizner <razrfalcon@gmail.com>"
+        ];
+        features = {
+        };
+        resolvedDefaultFeatures = [ "default" "eq-separator" ];
+      };
       "pin-project-lite" = rec {
         crateName = "pin-project-lite";
         version = "0.2.15";
@@ -8408,6 +8894,41 @@ rec {
         ];
 
       };
+      "png" = rec {
+        crateName = "png";
+        version = "0.17.16";
+        edition = "2018";
+        sha256 = "09kmkms9fmkbkarw0lnf0scqvjwwg3r7riddag0i3q39r0pil5c2";
+        authors = [
+          "The image-rs Developers"
+        ];
+        dependencies = [
+          {
+            name = "bitflags";
+            packageId = "bitflags 1.3.2";
+          }
+          {
+            name = "crc32fast";
+            packageId = "crc32fast";
+          }
+          {
+            name = "fdeflate";
+            packageId = "fdeflate";
+          }
+          {
+            name = "flate2";
+            packageId = "flate2";
+          }
+          {
+            name = "miniz_oxide";
+            packageId = "miniz_oxide 0.8.9";
+            features = [ "simd" ];
+          }
+        ];
+        features = {
+          "unstable" = [ "crc32fast/nightly" ];
+        };
+      };
       "polyval" = rec {
         crateName = "polyval";
         version = "0.6.2";
@@ -8585,6 +9106,18 @@ rec {
         };
         resolvedDefaultFeatures = [ "colors" "default" "yansi" ];
       };
+      "quick-error" = rec {
+        crateName = "quick-error";
+        version = "2.0.1";
+        edition = "2018";
+        sha256 = "18z6r2rcjvvf8cn92xjhm2qc3jpd1ljvcbf12zv0k9p565gmb4x9";
+        libName = "quick_error";
+        authors = [
+          "Paul Colomiets <paul@colomiets.name>"
+          "Colin Kiegel <kiegel@gmx.de>"
+        ];
+
+      };
       "quick-xml" = rec {
         crateName = "quick-xml";
         version = "0.37.1";
@@ -9659,6 +10192,66 @@ rec {
         };
         resolvedDefaultFeatures = [ "__rustls" "__rustls-ring" "__tls" "charset" "default" "default-tls" "h2" "http2" "macos-system-configuration" "rustls-tls" "rustls-tls-webpki-roots" "rustls-tls-webpki-roots-no-provider" ];
       };
+      "resvg" = rec {
+        crateName = "resvg";
+        version = "0.45.1";
+        edition = "2021";
+        crateBin = [];
+        sha256 = "0hyz89wyasn0wdnpw7qy5iyl4xv3yx36hk3crb4h6pm5q2c8g4m8";
+        dependencies = [
+          {
+            name = "gif";
+            packageId = "gif";
+            optional = true;
+          }
+          {
+            name = "image-webp";
+            packageId = "image-webp";
+            optional = true;
+          }
+          {
+            name = "log";
+            packageId = "log";
+          }
+          {
+            name = "pico-args";
+            packageId = "pico-args";
+            features = [ "eq-separator" ];
+          }
+          {
+            name = "rgb";
+            packageId = "rgb";
+          }
+          {
+            name = "svgtypes";
+            packageId = "svgtypes";
+          }
+          {
+            name = "tiny-skia";
+            packageId = "tiny-skia";
+          }
+          {
+            name = "usvg";
+            packageId = "usvg";
+            usesDefaultFeatures = false;
+          }
+          {
+            name = "zune-jpeg";
+            packageId = "zune-jpeg";
+            optional = true;
+          }
+        ];
+        features = {
+          "default" = [ "text" "system-fonts" "memmap-fonts" "raster-images" ];
+          "gif" = [ "dep:gif" ];
+          "image-webp" = [ "dep:image-webp" ];
+          "memmap-fonts" = [ "usvg/memmap-fonts" ];
+          "raster-images" = [ "gif" "image-webp" "dep:zune-jpeg" ];
+          "system-fonts" = [ "usvg/system-fonts" ];
+          "text" = [ "usvg/text" ];
+        };
+        resolvedDefaultFeatures = [ "default" "gif" "image-webp" "memmap-fonts" "raster-images" "system-fonts" "text" ];
+      };
       "rfc6979" = rec {
         crateName = "rfc6979";
         version = "0.4.0";
@@ -9682,6 +10275,31 @@ rec {
         ];
 
       };
+      "rgb" = rec {
+        crateName = "rgb";
+        version = "0.8.53";
+        edition = "2021";
+        sha256 = "1i0c55whln68zs6f5qqrkbg1mzai0p3qk1mwkwzdgr9i3dw4pcs7";
+        authors = [
+          "Kornel Lesiński <kornel@geekhood.net>"
+          "James Forster <james.forsterer@gmail.com>"
+        ];
+        dependencies = [
+          {
+            name = "bytemuck";
+            packageId = "bytemuck";
+            optional = true;
+          }
+        ];
+        features = {
+          "as-bytes" = [ "bytemuck" ];
+          "bytemuck" = [ "dep:bytemuck" ];
+          "default" = [ "as-bytes" "argb" "grb" ];
+          "defmt-03" = [ "dep:defmt" ];
+          "serde" = [ "dep:serde" ];
+        };
+        resolvedDefaultFeatures = [ "argb" "as-bytes" "bytemuck" "default" "grb" ];
+      };
       "ring" = rec {
         crateName = "ring";
         version = "0.17.8";
@@ -10113,6 +10731,19 @@ rec {
         };
         resolvedDefaultFeatures = [ "default" "http2" "private-cookies" "serde" "serde_" ];
       };
+      "roxmltree" = rec {
+        crateName = "roxmltree";
+        version = "0.20.0";
+        edition = "2021";
+        sha256 = "15vw91ps91wkmmgy62khf9zb63bdinvm80957dascbsw7dwvc83c";
+        authors = [
+          "Yevhenii Reizner <razrfalcon@gmail.com>"
+        ];
+        features = {
+          "default" = [ "std" "positions" ];
+        };
+        resolvedDefaultFeatures = [ "default" "positions" "std" ];
+      };
       "rsa" = rec {
         crateName = "rsa";
         version = "0.9.7";
@@ -10510,6 +11141,71 @@ rec {
         ];
 
       };
+      "rustybuzz" = rec {
+        crateName = "rustybuzz";
+        version = "0.20.1";
+        edition = "2021";
+        sha256 = "00hp1gwykjfli258zs7lqg8p2zdh94dv2mw8zx7f73m0z2b7qg7x";
+        authors = [
+          "Caleb Maclennan <caleb@alerque.com>"
+          "Laurenz Stampfl <laurenz.stampfl@gmail.com>"
+          "Yevhenii Reizner <razrfalcon@gmail.com>"
+          "خالد حسني (Khaled Hosny) <khaled@aliftype.com>"
+        ];
+        dependencies = [
+          {
+            name = "bitflags";
+            packageId = "bitflags 2.9.1";
+          }
+          {
+            name = "bytemuck";
+            packageId = "bytemuck";
+            features = [ "extern_crate_alloc" ];
+          }
+          {
+            name = "core_maths";
+            packageId = "core_maths";
+          }
+          {
+            name = "log";
+            packageId = "log";
+          }
+          {
+            name = "smallvec";
+            packageId = "smallvec";
+          }
+          {
+            name = "ttf-parser";
+            packageId = "ttf-parser";
+            usesDefaultFeatures = false;
+            features = [ "opentype-layout" "apple-layout" "variable-fonts" "glyph-names" "no-std-float" ];
+          }
+          {
+            name = "unicode-bidi-mirroring";
+            packageId = "unicode-bidi-mirroring";
+          }
+          {
+            name = "unicode-ccc";
+            packageId = "unicode-ccc";
+          }
+          {
+            name = "unicode-properties";
+            packageId = "unicode-properties";
+            usesDefaultFeatures = false;
+            features = [ "general-category" ];
+          }
+          {
+            name = "unicode-script";
+            packageId = "unicode-script";
+          }
+        ];
+        features = {
+          "default" = [ "std" ];
+          "std" = [ "ttf-parser/std" ];
+          "wasm-shaper" = [ "std" "dep:wasmi" ];
+        };
+        resolvedDefaultFeatures = [ "default" "std" ];
+      };
       "ryu" = rec {
         crateName = "ryu";
         version = "1.0.18";
@@ -11535,14 +12231,46 @@ rec {
           }
         ];
         features = {
-          "derive" = [ "dep:derive" ];
-          "digest" = [ "dep:digest" ];
-          "rand_core" = [ "dep:rand_core" ];
-          "std" = [ "alloc" "rand_core?/std" ];
+          "derive" = [ "dep:derive" ];
+          "digest" = [ "dep:digest" ];
+          "rand_core" = [ "dep:rand_core" ];
+          "std" = [ "alloc" "rand_core?/std" ];
+        };
+        resolvedDefaultFeatures = [ "alloc" "digest" "rand_core" "std" ];
+      };
+      "simd-adler32" = rec {
+        crateName = "simd-adler32";
+        version = "0.3.10";
+        edition = "2018";
+        sha256 = "1sny4y2qa5mwyxx5x59ln2p02vsdh92004njlslnx98imjc9489s";
+        libName = "simd_adler32";
+        authors = [
+          "Marvin Countryman <me@maar.vin>"
+        ];
+        features = {
+          "default" = [ "std" "const-generics" ];
+        };
+        resolvedDefaultFeatures = [ "const-generics" "default" "std" ];
+      };
+      "simplecss" = rec {
+        crateName = "simplecss";
+        version = "0.2.2";
+        edition = "2021";
+        sha256 = "0v0kid7b2602kcka2x2xs9wwfjf8lnvpgpl8x287qg4wra1ni73s";
+        dependencies = [
+          {
+            name = "log";
+            packageId = "log";
+            usesDefaultFeatures = false;
+          }
+        ];
+        features = {
+          "default" = [ "std" ];
+          "std" = [ "log/std" ];
         };
-        resolvedDefaultFeatures = [ "alloc" "digest" "rand_core" "std" ];
+        resolvedDefaultFeatures = [ "default" "std" ];
       };
-      "siphasher" = rec {
+      "siphasher 0.3.11" = rec {
         crateName = "siphasher";
         version = "0.3.11";
         edition = "2018";
@@ -11559,6 +12287,23 @@ rec {
         };
         resolvedDefaultFeatures = [ "default" "std" ];
       };
+      "siphasher 1.0.4" = rec {
+        crateName = "siphasher";
+        version = "1.0.4";
+        edition = "2018";
+        sha256 = "0mn28y43123jdpskdn6r9wibmn066f7h30zkkqn88bd6hj8zxx1k";
+        authors = [
+          "Frank Denis <github@pureftpd.org>"
+        ];
+        features = {
+          "default" = [ "std" ];
+          "serde" = [ "dep:serde" ];
+          "serde_json" = [ "dep:serde_json" ];
+          "serde_no_std" = [ "serde/alloc" ];
+          "serde_std" = [ "std" "serde/std" ];
+        };
+        resolvedDefaultFeatures = [ "default" "std" ];
+      };
       "slab" = rec {
         crateName = "slab";
         version = "0.4.9";
@@ -11579,6 +12324,25 @@ rec {
         };
         resolvedDefaultFeatures = [ "default" "std" ];
       };
+      "slotmap" = rec {
+        crateName = "slotmap";
+        version = "1.1.1";
+        edition = "2018";
+        sha256 = "0f20xf53zaysx9ydzkwwqm6hsjyb8lj2j6amhg57iln3jcy8rmdx";
+        authors = [
+          "Orson Peters <orsonpeters@gmail.com>"
+        ];
+        buildDependencies = [
+          {
+            name = "version_check";
+            packageId = "version_check";
+          }
+        ];
+        features = {
+          "default" = [ "std" ];
+          "serde" = [ "dep:serde" ];
+        };
+      };
       "slug" = rec {
         crateName = "slug";
         version = "0.1.6";
@@ -11602,9 +12366,9 @@ rec {
       };
       "smallvec" = rec {
         crateName = "smallvec";
-        version = "1.13.2";
+        version = "1.16.3";
         edition = "2018";
-        sha256 = "0rsw5samawl3wsw6glrsb127rx6sh89a8wyikicw6dkdcjd1lpiw";
+        sha256 = "06z0akmcq2zc9qg22dxda298p50gg3di5ggl3y2nllag8ypwhgav";
         authors = [
           "The Servo Project Developers"
         ];
@@ -11618,9 +12382,13 @@ rec {
         ];
         features = {
           "arbitrary" = [ "dep:arbitrary" ];
+          "bincode" = [ "dep:bincode" ];
           "const_new" = [ "const_generics" ];
           "drain_keep_rest" = [ "drain_filter" ];
+          "impl_bincode" = [ "bincode" "unty" ];
+          "malloc_size_of" = [ "dep:malloc_size_of" ];
           "serde" = [ "dep:serde" ];
+          "unty" = [ "dep:unty" ];
         };
         resolvedDefaultFeatures = [ "const_generics" "const_new" "serde" ];
       };
@@ -12777,6 +13545,31 @@ rec {
         features = {
         };
       };
+      "strict-num" = rec {
+        crateName = "strict-num";
+        version = "0.1.1";
+        edition = "2018";
+        sha256 = "0cb7l1vhb8zj90mzm8avlk815k40sql9515s865rqdrdfavvldv6";
+        libName = "strict_num";
+        authors = [
+          "Yevhenii Reizner <razrfalcon@gmail.com>"
+        ];
+        dependencies = [
+          {
+            name = "float-cmp";
+            packageId = "float-cmp";
+            optional = true;
+            usesDefaultFeatures = false;
+            features = [ "std" ];
+          }
+        ];
+        features = {
+          "approx-eq" = [ "float-cmp" ];
+          "default" = [ "approx-eq" ];
+          "float-cmp" = [ "dep:float-cmp" ];
+        };
+        resolvedDefaultFeatures = [ "approx-eq" "default" "float-cmp" ];
+      };
       "string_cache" = rec {
         crateName = "string_cache";
         version = "0.8.9";
@@ -12957,6 +13750,23 @@ rec {
         };
         resolvedDefaultFeatures = [ "default" "i128" "std" ];
       };
+      "svgtypes" = rec {
+        crateName = "svgtypes";
+        version = "0.15.3";
+        edition = "2021";
+        sha256 = "1z4a0b76ww6rf2c8zdapqh2a7r7kmmy7m957q5h5ics4zwgm9iv8";
+        dependencies = [
+          {
+            name = "kurbo";
+            packageId = "kurbo";
+          }
+          {
+            name = "siphasher";
+            packageId = "siphasher 1.0.4";
+          }
+        ];
+
+      };
       "syn" = rec {
         crateName = "syn";
         version = "2.0.90";
@@ -13475,6 +14285,89 @@ rec {
         };
         resolvedDefaultFeatures = [ "formatting" "parsing" "serde" ];
       };
+      "tiny-skia" = rec {
+        crateName = "tiny-skia";
+        version = "0.11.4";
+        edition = "2018";
+        sha256 = "1aq9gd4qh4418g8v08qzakqqggx8hl66qcianl3k5bjdsja37lc3";
+        libName = "tiny_skia";
+        authors = [
+          "Yevhenii Reizner <razrfalcon@gmail.com>"
+        ];
+        dependencies = [
+          {
+            name = "arrayref";
+            packageId = "arrayref";
+          }
+          {
+            name = "arrayvec";
+            packageId = "arrayvec";
+            usesDefaultFeatures = false;
+          }
+          {
+            name = "bytemuck";
+            packageId = "bytemuck";
+            features = [ "aarch64_simd" ];
+          }
+          {
+            name = "cfg-if";
+            packageId = "cfg-if";
+          }
+          {
+            name = "log";
+            packageId = "log";
+          }
+          {
+            name = "png";
+            packageId = "png";
+            optional = true;
+          }
+          {
+            name = "tiny-skia-path";
+            packageId = "tiny-skia-path";
+            usesDefaultFeatures = false;
+          }
+        ];
+        features = {
+          "default" = [ "std" "simd" "png-format" ];
+          "no-std-float" = [ "tiny-skia-path/no-std-float" ];
+          "png" = [ "dep:png" ];
+          "png-format" = [ "std" "png" ];
+          "std" = [ "tiny-skia-path/std" ];
+        };
+        resolvedDefaultFeatures = [ "default" "png" "png-format" "simd" "std" ];
+      };
+      "tiny-skia-path" = rec {
+        crateName = "tiny-skia-path";
+        version = "0.11.4";
+        edition = "2018";
+        sha256 = "14ywbdfakvacl6rxxmzbnycplaxpc6i2linh2yqk0sp8qb07z7lw";
+        libName = "tiny_skia_path";
+        authors = [
+          "Yevhenii Reizner <razrfalcon@gmail.com>"
+        ];
+        dependencies = [
+          {
+            name = "arrayref";
+            packageId = "arrayref";
+          }
+          {
+            name = "bytemuck";
+            packageId = "bytemuck";
+          }
+          {
+            name = "strict-num";
+            packageId = "strict-num";
+            usesDefaultFeatures = false;
+          }
+        ];
+        features = {
+          "default" = [ "std" ];
+          "libm" = [ "dep:libm" ];
+          "no-std-float" = [ "libm" ];
+        };
+        resolvedDefaultFeatures = [ "default" "std" ];
+      };
       "tinystr" = rec {
         crateName = "tinystr";
         version = "0.7.6";
@@ -14380,6 +15273,33 @@ rec {
         ];
 
       };
+      "ttf-parser" = rec {
+        crateName = "ttf-parser";
+        version = "0.25.1";
+        edition = "2018";
+        sha256 = "0cbgqglcwwjg3hirwq6xlza54w04mb5x02kf7zx4hrw50xmr1pyj";
+        libName = "ttf_parser";
+        authors = [
+          "Caleb Maclennan <caleb@alerque.com>"
+          "Laurenz Stampfl <laurenz.stampfl@gmail.com>"
+          "Yevhenii Reizner <razrfalcon@gmail.com>"
+          "خالد حسني (Khaled Hosny) <khaled@aliftype.com>"
+        ];
+        dependencies = [
+          {
+            name = "core_maths";
+            packageId = "core_maths";
+            optional = true;
+          }
+        ];
+        features = {
+          "core_maths" = [ "dep:core_maths" ];
+          "default" = [ "std" "opentype-layout" "apple-layout" "variable-fonts" "glyph-names" ];
+          "gvar-alloc" = [ "std" ];
+          "no-std-float" = [ "core_maths" ];
+        };
+        resolvedDefaultFeatures = [ "apple-layout" "core_maths" "glyph-names" "no-std-float" "opentype-layout" "std" "variable-fonts" ];
+      };
       "typenum" = rec {
         crateName = "typenum";
         version = "1.17.0";
@@ -14584,6 +15504,28 @@ rec {
         };
         resolvedDefaultFeatures = [ "default" "hardcoded-data" "std" ];
       };
+      "unicode-bidi-mirroring" = rec {
+        crateName = "unicode-bidi-mirroring";
+        version = "0.4.0";
+        edition = "2018";
+        sha256 = "1zirs1z3ahlwy7swg7apnm3pc6vix1g15q0kn6fx8rmvc266xyjx";
+        libName = "unicode_bidi_mirroring";
+        authors = [
+          "Yevhenii Reizner <razrfalcon@gmail.com>"
+        ];
+
+      };
+      "unicode-ccc" = rec {
+        crateName = "unicode-ccc";
+        version = "0.4.0";
+        edition = "2018";
+        sha256 = "0gjhxwx27ywm3rcbb0m5q20w8zxi51440b3ps6swi6ywpj4d8qff";
+        libName = "unicode_ccc";
+        authors = [
+          "Yevhenii Reizner <razrfalcon@gmail.com>"
+        ];
+
+      };
       "unicode-ident" = rec {
         crateName = "unicode-ident";
         version = "1.0.14";
@@ -14632,6 +15574,32 @@ rec {
         };
         resolvedDefaultFeatures = [ "default" "emoji" "general-category" ];
       };
+      "unicode-script" = rec {
+        crateName = "unicode-script";
+        version = "0.5.8";
+        edition = "2018";
+        sha256 = "1vmifpgd0map3frmvhszhl96k82crcry083prv05wii7p45x8fiq";
+        libName = "unicode_script";
+        authors = [
+          "Manish Goregaokar <manishsmail@gmail.com>"
+        ];
+        features = {
+          "core" = [ "dep:core" ];
+          "rustc-dep-of-std" = [ "std" "core" ];
+          "std" = [ "dep:std" ];
+        };
+      };
+      "unicode-vo" = rec {
+        crateName = "unicode-vo";
+        version = "0.1.0";
+        edition = "2015";
+        sha256 = "151sha088v9jyfvbg5164xh4dk72g53b82xm4zzbf5dlagzqdlxi";
+        libName = "unicode_vo";
+        authors = [
+          "Evgeniy Reizner <razrfalcon@gmail.com>"
+        ];
+
+      };
       "unicode-xid" = rec {
         crateName = "unicode-xid";
         version = "0.2.6";
@@ -14739,6 +15707,112 @@ rec {
         };
         resolvedDefaultFeatures = [ "default" "serde" "std" ];
       };
+      "usvg" = rec {
+        crateName = "usvg";
+        version = "0.45.1";
+        edition = "2021";
+        crateBin = [];
+        sha256 = "1vs7gfhyxjgkgibgwfjniwrszfw0iivj1aq06hq8nfxfzc39pgl0";
+        dependencies = [
+          {
+            name = "base64";
+            packageId = "base64 0.22.1";
+          }
+          {
+            name = "data-url";
+            packageId = "data-url";
+          }
+          {
+            name = "flate2";
+            packageId = "flate2";
+            usesDefaultFeatures = false;
+            features = [ "rust_backend" ];
+          }
+          {
+            name = "fontdb";
+            packageId = "fontdb";
+            optional = true;
+            usesDefaultFeatures = false;
+          }
+          {
+            name = "imagesize";
+            packageId = "imagesize";
+          }
+          {
+            name = "kurbo";
+            packageId = "kurbo";
+          }
+          {
+            name = "log";
+            packageId = "log";
+          }
+          {
+            name = "pico-args";
+            packageId = "pico-args";
+            features = [ "eq-separator" ];
+          }
+          {
+            name = "roxmltree";
+            packageId = "roxmltree";
+          }
+          {
+            name = "rustybuzz";
+            packageId = "rustybuzz";
+            optional = true;
+          }
+          {
+            name = "simplecss";
+            packageId = "simplecss";
+          }
+          {
+            name = "siphasher";
+            packageId = "siphasher 1.0.4";
+          }
+          {
+            name = "strict-num";
+            packageId = "strict-num";
+          }
+          {
+            name = "svgtypes";
+            packageId = "svgtypes";
+          }
+          {
+            name = "tiny-skia-path";
+            packageId = "tiny-skia-path";
+          }
+          {
+            name = "unicode-bidi";
+            packageId = "unicode-bidi";
+            optional = true;
+          }
+          {
+            name = "unicode-script";
+            packageId = "unicode-script";
+            optional = true;
+          }
+          {
+            name = "unicode-vo";
+            packageId = "unicode-vo";
+            optional = true;
+          }
+          {
+            name = "xmlwriter";
+            packageId = "xmlwriter";
+          }
+        ];
+        features = {
+          "default" = [ "text" "system-fonts" "memmap-fonts" ];
+          "fontdb" = [ "dep:fontdb" ];
+          "memmap-fonts" = [ "fontdb/memmap" ];
+          "rustybuzz" = [ "dep:rustybuzz" ];
+          "system-fonts" = [ "fontdb/fs" "fontdb/fontconfig" ];
+          "text" = [ "fontdb" "rustybuzz" "unicode-bidi" "unicode-script" "unicode-vo" ];
+          "unicode-bidi" = [ "dep:unicode-bidi" ];
+          "unicode-script" = [ "dep:unicode-script" ];
+          "unicode-vo" = [ "dep:unicode-vo" ];
+        };
+        resolvedDefaultFeatures = [ "fontdb" "memmap-fonts" "rustybuzz" "system-fonts" "text" "unicode-bidi" "unicode-script" "unicode-vo" ];
+      };
       "utf-8" = rec {
         crateName = "utf-8";
         version = "0.7.6";
@@ -15647,6 +16721,23 @@ rec {
         ];
 
       };
+      "weezl" = rec {
+        crateName = "weezl";
+        version = "0.1.12";
+        edition = "2018";
+        crateBin = [];
+        sha256 = "122a1dhha6cib5az4ihcqlh60ns2bi6rskdv875p94lbvj6wk2m2";
+        authors = [
+          "The image-rs Developers"
+        ];
+        features = {
+          "async" = [ "futures" "std" ];
+          "default" = [ "std" ];
+          "futures" = [ "dep:futures" ];
+          "std" = [ "alloc" ];
+        };
+        resolvedDefaultFeatures = [ "alloc" "default" "std" ];
+      };
       "whoami" = rec {
         crateName = "whoami";
         version = "1.5.2";
@@ -17788,6 +18879,10 @@ rec {
             name = "reqwest";
             packageId = "reqwest";
           }
+          {
+            name = "resvg";
+            packageId = "resvg";
+          }
           {
             name = "rocket";
             packageId = "rocket";
@@ -17889,6 +18984,16 @@ rec {
           "either" = [ "dep:either" ];
         };
       };
+      "xmlwriter" = rec {
+        crateName = "xmlwriter";
+        version = "0.1.0";
+        edition = "2018";
+        sha256 = "1fg0ldmkgiis6hnxpi1c9gy7v23y0lpi824bp8yp12fi3r82lypc";
+        authors = [
+          "Evgeniy Reizner <razrfalcon@gmail.com>"
+        ];
+
+      };
       "yansi" = rec {
         crateName = "yansi";
         version = "1.0.1";
@@ -18195,6 +19300,57 @@ rec {
         ];
 
       };
+      "zlib-rs" = rec {
+        crateName = "zlib-rs";
+        version = "0.6.8";
+        edition = "2021";
+        sha256 = "04j158293bx73kv5pj1i89ai411q7fxc9zwk3wkpqgb9gj7fas5j";
+        libName = "zlib_rs";
+        features = {
+          "__internal-fuzz" = [ "arbitrary" ];
+          "__internal-test" = [ "quickcheck" ];
+          "arbitrary" = [ "dep:arbitrary" ];
+          "avx512" = [ "vpclmulqdq" ];
+          "default" = [ "std" "c-allocator" ];
+          "quickcheck" = [ "dep:quickcheck" ];
+          "std" = [ "rust-allocator" ];
+        };
+        resolvedDefaultFeatures = [ "rust-allocator" "std" ];
+      };
+      "zune-core" = rec {
+        crateName = "zune-core";
+        version = "0.4.12";
+        edition = "2021";
+        sha256 = "0jj1ra86klzlcj9aha9als9d1dzs7pqv3azs1j3n96822wn3lhiz";
+        libName = "zune_core";
+        features = {
+          "log" = [ "dep:log" ];
+          "serde" = [ "dep:serde" ];
+        };
+        resolvedDefaultFeatures = [ "std" ];
+      };
+      "zune-jpeg" = rec {
+        crateName = "zune-jpeg";
+        version = "0.4.21";
+        edition = "2021";
+        sha256 = "04r7g6y9jp7d4c9bq23rz3gwzlr1dsl7vdk4yly35bc4jf52rki9";
+        libName = "zune_jpeg";
+        authors = [
+          "caleb <etemesicaleb@gmail.com>"
+        ];
+        dependencies = [
+          {
+            name = "zune-core";
+            packageId = "zune-core";
+          }
+        ];
+        features = {
+          "default" = [ "x86" "neon" "std" ];
+          "log" = [ "zune-core/log" ];
+          "std" = [ "zune-core/std" ];
+        };
+        resolvedDefaultFeatures = [ "default" "neon" "std" "x86" ];
+      };
     };
 
     #
@@ -0,0 +1,17 @@
+-- Queue of webmentions we owe to other sites. Rows outlive the post that
+-- created them so that removed mentions and deleted posts still get notified.
+CREATE TABLE outgoing_mentions (
+    from_path TEXT NOT NULL,
+    to_url TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_attempt TIMESTAMPTZ,
+    endpoint TEXT,
+    response_code INTEGER,
+    last_error TEXT,
+    PRIMARY KEY (from_path, to_url)
+);
+
+CREATE INDEX outgoing_mentions_due ON outgoing_mentions (next_attempt)
+WHERE status = 'pending';
@@ -0,0 +1,18 @@
+-- Webmentions we've been sent but haven't verified yet, along with the
+-- outcome of verifying them so senders can check on their request.
+CREATE TABLE mention_requests (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    source TEXT NOT NULL,
+    target TEXT NOT NULL,
+    to_path TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'queued',
+    detail TEXT,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    received TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    next_attempt TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    processed TIMESTAMPTZ,
+    UNIQUE (source, to_path)
+);
+
+CREATE INDEX mention_requests_due ON mention_requests (next_attempt)
+WHERE status = 'queued';
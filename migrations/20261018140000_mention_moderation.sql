@@ -0,0 +1,11 @@
+-- Mentions now wait for approval, except the ones we already had
+ALTER TABLE incoming_mentions
+ADD status TEXT NOT NULL DEFAULT 'pending';
+UPDATE incoming_mentions SET status = 'approved';
+
+-- Domains banned at runtime, on top of the ones in the config file
+CREATE TABLE webmention_bans (
+    pattern TEXT PRIMARY KEY,
+    reason TEXT,
+    created TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
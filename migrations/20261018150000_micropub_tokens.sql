@@ -0,0 +1,9 @@
+-- Bearer tokens for the micropub endpoint, issued from the command line.
+-- Only a hash of each token is kept.
+CREATE TABLE micropub_tokens (
+    token_hash TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    scope TEXT[] NOT NULL,
+    created TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_used TIMESTAMPTZ
+);
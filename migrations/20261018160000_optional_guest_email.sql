@@ -0,0 +1,2 @@
+-- IndieAuth profiles don't have to include an email address
+ALTER TABLE guests ALTER COLUMN email DROP NOT NULL;
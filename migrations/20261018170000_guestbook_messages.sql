@@ -0,0 +1,7 @@
+-- Optional messages left when signing a guestbook. Bare signatures from
+-- before messages existed count as approved.
+ALTER TABLE guestbook
+    ADD COLUMN message TEXT,
+    ADD COLUMN message_html TEXT,
+    ADD COLUMN message_status TEXT NOT NULL DEFAULT 'approved',
+    ADD COLUMN edited TIMESTAMPTZ;
@@ -0,0 +1,17 @@
+-- Bans on signing guestbooks, either for one identity or for everyone
+-- whose email is at a domain
+CREATE TABLE guest_bans (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    provider TEXT,
+    sub TEXT,
+    email_domain TEXT,
+    reason TEXT,
+    created TIMESTAMPTZ NOT NULL DEFAULT now(),
+    expires TIMESTAMPTZ,
+    CHECK (
+        (provider IS NOT NULL AND sub IS NOT NULL AND email_domain IS NULL)
+        OR (provider IS NULL AND sub IS NULL AND email_domain IS NOT NULL)
+    )
+);
+CREATE INDEX guest_bans_identity ON guest_bans (provider, sub);
+CREATE INDEX guest_bans_email_domain ON guest_bans (email_domain);
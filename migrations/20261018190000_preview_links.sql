@@ -0,0 +1,17 @@
+-- Drafts are now kept in the database, so keep them out of tag listings
+CREATE OR REPLACE VIEW visible_posts AS
+SELECT *
+FROM posts
+WHERE meta->>'hidden' = 'false' AND meta->>'ready' = 'true';
+
+-- Expiring links that let someone read a draft before it's published.
+-- Only a hash of each token is kept.
+CREATE TABLE preview_links (
+    id SERIAL PRIMARY KEY,
+    token_hash TEXT NOT NULL UNIQUE,
+    path TEXT NOT NULL,
+    note TEXT,
+    created TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires TIMESTAMPTZ NOT NULL
+);
+CREATE INDEX preview_links_path ON preview_links (path);
@@ -0,0 +1,11 @@
+-- Emoji reactions to posts, one row per guest per emoji
+CREATE TABLE reactions (
+    post TEXT NOT NULL,
+    provider TEXT NOT NULL,
+    guest TEXT NOT NULL,
+    emoji TEXT NOT NULL,
+    timestamp TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (post, provider, guest, emoji),
+    FOREIGN KEY (provider, guest) REFERENCES guests(provider, sub) ON DELETE CASCADE,
+    FOREIGN KEY (post) REFERENCES posts(path) ON DELETE CASCADE
+);
@@ -0,0 +1,19 @@
+-- Threaded comments from logged-in guests. Deleted comments that still
+-- have replies are kept as empty placeholders so the thread holds together.
+CREATE TABLE comments (
+    id BIGSERIAL PRIMARY KEY,
+    post TEXT NOT NULL,
+    parent BIGINT REFERENCES comments(id) ON DELETE CASCADE,
+    provider TEXT NOT NULL,
+    guest TEXT NOT NULL,
+    body TEXT NOT NULL,
+    body_html TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending',
+    created TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    edited TIMESTAMPTZ,
+    deleted BOOLEAN NOT NULL DEFAULT false,
+    FOREIGN KEY (provider, guest) REFERENCES guests(provider, sub) ON DELETE CASCADE,
+    FOREIGN KEY (post) REFERENCES posts(path) ON DELETE CASCADE
+);
+CREATE INDEX comments_post ON comments (post, created);
+CREATE INDEX comments_status ON comments (status);
@@ -0,0 +1,13 @@
+-- Read tracking for anonymous visitors. Their cookie only carries the ID.
+CREATE TABLE visitors (
+    id TEXT PRIMARY KEY,
+    created TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_seen TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+CREATE INDEX visitors_last_seen ON visitors (last_seen);
+CREATE TABLE visitor_views (
+    visitor TEXT NOT NULL REFERENCES visitors(id) ON DELETE CASCADE,
+    path TEXT NOT NULL,
+    viewed DATE NOT NULL,
+    PRIMARY KEY (visitor, path)
+);
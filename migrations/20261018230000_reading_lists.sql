@@ -0,0 +1,10 @@
+-- Posts visitors have saved to read later
+CREATE TABLE visitor_bookmarks (
+    visitor TEXT NOT NULL REFERENCES visitors(id) ON DELETE CASCADE,
+    path TEXT NOT NULL,
+    added TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (visitor, path)
+);
+
+-- The secret part of a visitor's private unread feed URL
+ALTER TABLE visitors ADD COLUMN feed_token TEXT UNIQUE;
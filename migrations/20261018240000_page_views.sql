@@ -0,0 +1,34 @@
+-- Daily view counts. `kind` says what was viewed: a post, a search or a feed
+CREATE TABLE page_views (
+    kind TEXT NOT NULL,
+    path TEXT NOT NULL,
+    day DATE NOT NULL,
+    views INTEGER NOT NULL DEFAULT 0,
+    visitors INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (kind, path, day)
+);
+
+CREATE INDEX page_views_day ON page_views (day);
+
+-- Where views came from, by domain only
+CREATE TABLE page_referrers (
+    kind TEXT NOT NULL,
+    path TEXT NOT NULL,
+    day DATE NOT NULL,
+    domain TEXT NOT NULL,
+    views INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (kind, path, day, domain)
+);
+
+-- One salt per day. Once a day's salt is deleted, its visitor hashes can't
+-- be linked to anyone, or to the hashes of any other day
+CREATE TABLE analytics_salts (
+    day DATE PRIMARY KEY,
+    salt TEXT NOT NULL
+);
+
+-- Hashes of who has viewed what today, to count each visitor once
+CREATE TABLE page_view_hashes (
+    day DATE NOT NULL REFERENCES analytics_salts(day) ON DELETE CASCADE,
+    hash TEXT PRIMARY KEY
+);
@@ -0,0 +1,6 @@
+-- Published posts that have since been deleted, so they can answer 410 Gone
+-- rather than 404
+CREATE TABLE removed_posts (
+    path TEXT PRIMARY KEY,
+    removed TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
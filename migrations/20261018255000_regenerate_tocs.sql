@@ -0,0 +1,3 @@
+-- Posts read before tables of contents were generated stored an empty one,
+-- which now means they opted out, so read them all again
+UPDATE posts SET updated = 'epoch';
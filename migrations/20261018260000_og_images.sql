@@ -0,0 +1,6 @@
+-- Generated social preview images, kept until their post changes
+CREATE TABLE og_images (
+    path TEXT PRIMARY KEY REFERENCES posts(path) ON DELETE CASCADE,
+    rendered_for TIMESTAMPTZ NOT NULL,
+    png BYTEA NOT NULL
+);
@@ -0,0 +1,3 @@
+-- Posts now record their language when they're read, so read them all again
+UPDATE posts SET updated = 'epoch';
+CREATE INDEX posts_translation_of ON posts ((meta->>'translation_of'));
@@ -0,0 +1,8 @@
+-- Where each published post sits in its series, worked out when any part of
+-- the series changes
+CREATE TABLE series_parts (
+    path TEXT PRIMARY KEY REFERENCES posts(path) ON DELETE CASCADE,
+    series TEXT NOT NULL,
+    position INTEGER NOT NULL
+);
+CREATE INDEX series_parts_series ON series_parts (series, position);
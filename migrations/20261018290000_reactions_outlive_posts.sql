@@ -0,0 +1,3 @@
+-- Reactions belong to a path rather than to a posts row, like guestbook
+-- signatures, so they survive a post being taken down and put back
+ALTER TABLE reactions DROP CONSTRAINT reactions_post_fkey;
@@ -0,0 +1,3 @@
+-- Comments belong to a path rather than to a posts row, like guestbook
+-- signatures, so a thread survives its post being taken down and put back
+ALTER TABLE comments DROP CONSTRAINT comments_post_fkey;
@@ -0,0 +1,3 @@
+-- Stored tables of contents had their labels escaped, which they no longer
+-- are, so read every post again
+UPDATE posts SET updated = 'epoch';
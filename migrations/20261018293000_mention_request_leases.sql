@@ -0,0 +1,6 @@
+-- When a worker's lease on a request runs out, so a mention sent again in
+-- the meantime doesn't reset a request that's being verified
+ALTER TABLE mention_requests ADD COLUMN leased_until TIMESTAMPTZ;
+-- Set when a mention is sent again while it's being verified, so it's
+-- queued again once the worker's done with it
+ALTER TABLE mention_requests ADD COLUMN resend BOOLEAN NOT NULL DEFAULT false;
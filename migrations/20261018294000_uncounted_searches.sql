@@ -0,0 +1,23 @@
+-- Searches and search feeds were counted under their query strings, which
+-- hold whatever visitors typed, so fold them into one count a day
+WITH searches AS (
+    DELETE FROM page_views
+    WHERE kind = 'search' OR (kind = 'feed' AND path LIKE 'feed?%')
+    RETURNING *
+)
+INSERT INTO page_views (kind, path, day, views, visitors)
+SELECT kind, CASE WHEN kind = 'search' THEN 'search' ELSE 'feed/search' END, day,
+    SUM(views)::INTEGER, SUM(visitors)::INTEGER
+FROM searches
+GROUP BY 1, 2, 3;
+
+WITH searches AS (
+    DELETE FROM page_referrers
+    WHERE kind = 'search' OR (kind = 'feed' AND path LIKE 'feed?%')
+    RETURNING *
+)
+INSERT INTO page_referrers (kind, path, day, domain, views)
+SELECT kind, CASE WHEN kind = 'search' THEN 'search' ELSE 'feed/search' END, day, domain,
+    SUM(views)::INTEGER
+FROM searches
+GROUP BY 1, 2, 3, 4;
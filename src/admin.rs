@@ -0,0 +1,180 @@
+use std::{
+	path::Path,
+	sync::{Arc, LazyLock, Mutex},
+};
+
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use rocket::{
+	State,
+	request::FlashMessage,
+	response::{Flash, Redirect, content::RawHtml},
+};
+use serde::Serialize;
+use sqlx::{Pool, Postgres};
+use tera::Tera;
+use tokio::sync::RwLock;
+
+use crate::{Config, cookies::Preferences, db, errors::AppError, oauth, preview, templates};
+
+/// What the last full update of the content root did.
+#[derive(Serialize, Default, Clone)]
+pub struct IngestStatus {
+	pub running: bool,
+	pub started: Option<DateTime<Utc>>,
+	pub finished: Option<DateTime<Utc>>,
+	pub error: Option<String>,
+	/// The most recent post to make it into the database
+	pub last_ingested: Option<(String, DateTime<Utc>)>,
+}
+
+/// A source file we couldn't turn into a post.
+#[derive(Serialize, Clone)]
+pub struct ContentError {
+	pub file: String,
+	pub message: String,
+	pub at: DateTime<Utc>,
+}
+
+static STATUS: LazyLock<Mutex<IngestStatus>> = LazyLock::new(Mutex::default);
+static CONTENT_ERRORS: LazyLock<DashMap<String, ContentError>> = LazyLock::new(DashMap::new);
+
+pub fn update_started() {
+	let mut status = STATUS.lock().unwrap();
+	status.running = true;
+	status.started = Some(Utc::now());
+}
+
+pub fn update_finished(error: Option<String>) {
+	let mut status = STATUS.lock().unwrap();
+	status.running = false;
+	status.finished = Some(Utc::now());
+	status.error = error;
+}
+
+pub fn ingested(path: &str) {
+	CONTENT_ERRORS.remove(path);
+	STATUS.lock().unwrap().last_ingested = Some((path.to_string(), Utc::now()));
+}
+
+pub fn content_error(path: &str, file: &Path, message: String) {
+	CONTENT_ERRORS.insert(
+		path.to_string(),
+		ContentError {
+			file: file.display().to_string(),
+			message,
+			at: Utc::now(),
+		},
+	);
+}
+
+/// A post's source is gone, so whatever we knew about it no longer matters.
+pub fn forget(path: &str) {
+	CONTENT_ERRORS.remove(path);
+}
+
+fn spawn_update(db: &Pool<Postgres>, cfg: &Arc<Config>) {
+	tokio::spawn({
+		let db = db.clone();
+		let cfg = cfg.clone();
+		async move {
+			if let Err(e) = db::update_all(cfg, &db).await {
+				eprintln!("Error in requested database update: {e}");
+			}
+		}
+	});
+}
+
+#[get("/")]
+pub async fn dashboard(
+	admin: oauth::Admin,
+	flash: Option<FlashMessage<'_>>,
+	db: &State<Pool<Postgres>>,
+	tera: &State<Arc<RwLock<Tera>>>,
+	config: &State<Arc<Config>>,
+	prefs: Preferences,
+) -> Result<RawHtml<String>, AppError> {
+	let status = STATUS.lock().unwrap().clone();
+	let mut content_errors: Vec<_> = CONTENT_ERRORS
+		.iter()
+		.map(|e| (e.key().clone(), e.value().clone()))
+		.collect();
+	content_errors.sort_by(|a, b| a.0.cmp(&b.0));
+	let unlisted = db::unlisted_posts(db).await?;
+	let preview_links = db::preview_links(db).await?;
+	let post_count = db::post_count(db).await?;
+	let webmentions = db::webmention_stats(db).await?;
+	let signatures = db::recent_signatures(db, 10).await?;
+	let pending_messages = db::pending_message_count(db).await?;
+	let pending_comments = db::pending_comment_count(db).await?;
+	let content = crate::templates::render(
+		tera,
+		config,
+		"admin.html.tera",
+		&crate::context!({
+			"prefs": prefs,
+			"admin": admin.identity,
+			"admin_provider": admin.provider,
+			"flash": flash.map(|f| (f.kind().to_string(), f.message().to_string())),
+			"status": status,
+			"post_count": post_count,
+			"content_errors": content_errors,
+			"unlisted": unlisted,
+			"preview_links": preview_links,
+			"default_preview_days": preview::DEFAULT_DAYS,
+			"webmentions": webmentions,
+			"signatures": signatures,
+			"pending_messages": pending_messages,
+			"pending_comments": pending_comments,
+		}),
+	)
+	.await?;
+	Ok(RawHtml(content))
+}
+
+#[post("/update")]
+pub fn update(
+	_admin: oauth::Admin,
+	db: &State<Pool<Postgres>>,
+	cfg: &State<Arc<Config>>,
+) -> Flash<Redirect> {
+	if STATUS.lock().unwrap().running {
+		return Flash::error(Redirect::to("/admin"), "An update is already running");
+	}
+	spawn_update(db, cfg);
+	Flash::success(Redirect::to("/admin"), "Started a full update")
+}
+
+#[post("/reload-templates")]
+pub async fn reload_templates(
+	_admin: oauth::Admin,
+	tera: &State<Arc<RwLock<Tera>>>,
+	cfg: &State<Arc<Config>>,
+	db: &State<Pool<Postgres>>,
+) -> Flash<Redirect> {
+	match templates::reload(tera, cfg, db).await {
+		Ok(()) => Flash::success(Redirect::to("/admin"), "Reloaded templates"),
+		Err(e) => Flash::error(
+			Redirect::to("/admin"),
+			format!("Couldn't reload templates, so the old ones are still in use: {e}"),
+		),
+	}
+}
+
+/// Forget cached login provider metadata and read every post again from
+/// scratch.
+#[post("/purge-caches")]
+pub async fn purge_caches(
+	_admin: oauth::Admin,
+	db: &State<Pool<Postgres>>,
+	cfg: &State<Arc<Config>>,
+) -> Result<Flash<Redirect>, AppError> {
+	let providers = oauth::forget_providers();
+	db::mark_all_stale(db).await?;
+	CONTENT_ERRORS.clear();
+	spawn_update(db, cfg);
+	Ok(Flash::success(
+		Redirect::to("/admin"),
+		format!("Forgot {providers} login providers and started re-reading every post"),
+	))
+}
@@ -0,0 +1,127 @@
+use std::{sync::Arc, time::Duration};
+
+use chrono::{Days, Utc};
+use rocket::{
+	Request, State,
+	outcome::Outcome,
+	request::{self, FromRequest},
+	response::content::RawHtml,
+};
+use sqlx::{Pool, Postgres};
+use tera::Tera;
+use tokio::sync::RwLock;
+use url::Url;
+
+use crate::{Config, cookies::Preferences, db, errors::AppError, oauth::Admin};
+
+/// How many rows each table on the stats page shows.
+const STATS_LEN: i64 = 50;
+
+/// What was viewed.
+#[derive(Clone, Copy, strum::Display)]
+#[strum(serialize_all = "lowercase")]
+pub enum ViewKind {
+	Post,
+	Search,
+	Feed,
+}
+
+/// Who's looking, in as little detail as will tell visitors apart for a
+/// day. Nothing here is stored as is.
+pub struct Visit {
+	key: String,
+	referrer: Option<String>,
+}
+
+#[async_trait]
+impl<'r> FromRequest<'r> for Visit {
+	type Error = std::convert::Infallible;
+
+	async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+		let config = request.rocket().state::<Arc<Config>>().unwrap();
+		let ip = request
+			.client_ip()
+			.map(|ip| ip.to_string())
+			.unwrap_or_default();
+		let user_agent = request.headers().get_one("User-Agent").unwrap_or_default();
+		// Only the domain of a referrer is kept, and links within the site
+		// aren't worth counting
+		let referrer = request
+			.headers()
+			.get_one("Referer")
+			.and_then(|r| Url::parse(r).ok())
+			.and_then(|r| r.host_str().map(str::to_lowercase))
+			.filter(|host| Some(host.as_str()) != config.origin.host_str());
+		Outcome::Success(Visit {
+			key: format!("{ip}\n{user_agent}"),
+			referrer,
+		})
+	}
+}
+
+/// Count a view in the background, so it never holds up a response.
+pub fn record(db: &Pool<Postgres>, config: &Config, visit: Visit, kind: ViewKind, path: &str) {
+	if !config.enable_analytics {
+		return;
+	}
+	let db = db.clone();
+	let path = path.to_string();
+	tokio::spawn(async move {
+		// Telling visitors apart per path keeps the hash from linking the
+		// pages one visitor read
+		let key = format!("{}\n{kind}\n{path}", visit.key);
+		let today = Utc::now().date_naive();
+		let kind = kind.to_string();
+		if let Err(e) =
+			db::record_page_view(&db, &kind, &path, today, &key, visit.referrer.as_deref()).await
+		{
+			eprintln!("Couldn't count a view of {path}: {e}");
+		}
+	});
+}
+
+/// Forget yesterday's salt once the day is over, so its visitor hashes
+/// can't be matched up with anything again.
+pub fn spawn_salt_pruner(db: Pool<Postgres>) {
+	tokio::spawn(async move {
+		let mut hourly = tokio::time::interval(Duration::from_hours(1));
+		loop {
+			hourly.tick().await;
+			if let Err(e) = db::prune_analytics_salts(&db, Utc::now().date_naive()).await {
+				eprintln!("Error pruning analytics salts: {e}");
+			}
+		}
+	});
+}
+
+#[get("/?<days>")]
+pub async fn stats(
+	admin: Admin,
+	days: Option<u16>,
+	db: &State<Pool<Postgres>>,
+	tera: &State<Arc<RwLock<Tera>>>,
+	config: &State<Arc<Config>>,
+	prefs: Preferences,
+) -> Result<RawHtml<String>, AppError> {
+	let days = days.unwrap_or(30).clamp(1, 3660);
+	let since = Utc::now().date_naive() - Days::new(u64::from(days) - 1);
+	let daily = db::daily_views(db, &ViewKind::Post.to_string(), since).await?;
+	let paths = db::top_paths(db, since, STATS_LEN).await?;
+	let referrers = db::top_referrers(db, since, STATS_LEN).await?;
+	let content = crate::templates::render(
+		tera,
+		config,
+		"admin-stats.html.tera",
+		&crate::context!({
+			"prefs": prefs,
+			"admin": admin.identity,
+			"admin_provider": admin.provider,
+			"days": days,
+			"daily": daily,
+			"paths": paths,
+			"referrers": referrers,
+		}),
+	)
+	.await?;
+	Ok(RawHtml(content))
+}
@@ -0,0 +1,72 @@
+use std::path::Path;
+
+use clap::{Parser, Subcommand};
+use openidconnect::CsrfToken;
+use sqlx::{Pool, Postgres};
+
+use crate::{Config, db, preview};
+
+#[derive(Parser)]
+#[command(version, about = "The wolog")]
+pub struct Args {
+	#[command(subcommand)]
+	pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+	/// Issue a bearer token for the micropub endpoint
+	IssueToken {
+		/// Who or what the token is for
+		name: String,
+		/// Comma-separated list of what the token is allowed to do
+		#[arg(long, value_delimiter = ',', default_value = "create,update,delete")]
+		scope: Vec<String>,
+	},
+	/// Revoke every micropub token with the given name
+	RevokeToken { name: String },
+	/// Make a link that lets someone read a draft before it's published
+	Preview {
+		/// The post, or its source file relative to the content root
+		path: String,
+		/// How many days the link works for
+		#[arg(long, default_value_t = preview::DEFAULT_DAYS)]
+		days: u16,
+		/// Who the link is for, to tell links apart later
+		#[arg(long)]
+		note: Option<String>,
+	},
+	/// Revoke every preview link for a post
+	RevokePreviews { path: String },
+}
+
+pub async fn run(command: Command, cfg: &Config, db: &Pool<Postgres>) -> color_eyre::Result<()> {
+	match command {
+		Command::IssueToken { name, scope } => {
+			let token = CsrfToken::new_random_len(32).secret().clone();
+			db::add_micropub_token(db, &token, &name, &scope).await?;
+			println!("{token}");
+			eprintln!(
+				"Issued token {name:?} with scope {}. It won't be shown again.",
+				scope.join(",")
+			);
+		}
+		Command::RevokeToken { name } => {
+			let revoked = db::revoke_micropub_tokens(db, &name).await?;
+			eprintln!("Revoked {revoked} token(s) named {name:?}");
+		}
+		Command::Preview { path, days, note } => {
+			let Some(url) = preview::issue(cfg, db, &path, days, note.as_deref()).await? else {
+				color_eyre::eyre::bail!("There's no post at {path}. Has it been ingested yet?");
+			};
+			println!("{url}");
+			eprintln!("The link works for {days} days.");
+		}
+		Command::RevokePreviews { path } => {
+			let path = db::trim_path(cfg, Path::new(&path));
+			let revoked = db::revoke_preview_links(db, &path).await?;
+			eprintln!("Revoked {revoked} preview link(s) for {path}");
+		}
+	}
+	Ok(())
+}
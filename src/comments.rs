@@ -0,0 +1,432 @@
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
+
+use chrono::{DateTime, Local};
+use rocket::{
+	Request, State,
+	form::Form,
+	http::{ContentType, Status},
+	response::{Redirect, Responder, content::RawHtml},
+};
+use rocket_governor::RocketGovernor;
+use serde::Serialize;
+use sqlx::{Pool, Postgres};
+use tera::Tera;
+use thiserror::Error;
+use tokio::sync::RwLock;
+
+use crate::{
+	Config,
+	analytics::{self, ViewKind, Visit},
+	cookies::Preferences,
+	db,
+	errors::{self, AppError},
+	guestbook::{self, GuestbookError, GuestbookRateLimit, MessageStatus},
+	oauth::{Admin, Identities},
+	pandoc,
+	templates::RenderError,
+};
+
+/// The longest comment, in characters.
+const MAX_COMMENT_LEN: usize = 5000;
+
+/// How many comments go in a post's comment feed.
+const FEED_LEN: i64 = 50;
+
+#[derive(Error, Debug)]
+pub enum CommentError {
+	#[error("Database error")]
+	DbError(#[from] sqlx::Error),
+	#[error(transparent)]
+	Guestbook(#[from] GuestbookError),
+	#[error("Not found")]
+	NotFound,
+	#[error("Not yours")]
+	NotYours,
+	#[error("Comment too long")]
+	TooLong,
+	#[error("Empty comment")]
+	Empty,
+	#[error("Couldn't render comment")]
+	BadComment,
+	#[error(transparent)]
+	Render(#[from] RenderError),
+	#[error(transparent)]
+	App(#[from] AppError),
+}
+
+impl<'r, 'o: 'r> Responder<'r, 'o> for CommentError {
+	fn respond_to(self, request: &'r Request<'_>) -> rocket::response::Result<'o> {
+		match self {
+			CommentError::DbError(e) => {
+				dbg!(e);
+				errors::caught(request, Status::InternalServerError, "Database error")
+			}
+			CommentError::Guestbook(e) => e.respond_to(request),
+			CommentError::Render(e) => e.respond_to(request),
+			CommentError::App(e) => e.respond_to(request),
+			CommentError::NotFound => errors::caught(request, Status::NotFound, "Not found"),
+			CommentError::NotYours => errors::caught(
+				request,
+				Status::Forbidden,
+				"You can only change your own comments",
+			),
+			CommentError::TooLong => errors::caught(
+				request,
+				Status::PayloadTooLarge,
+				format!("Comments can be at most {MAX_COMMENT_LEN} characters long"),
+			),
+			CommentError::Empty => {
+				errors::caught(request, Status::BadRequest, "Comments can't be empty")
+			}
+			CommentError::BadComment => errors::caught(
+				request,
+				Status::BadRequest,
+				"Couldn't understand that comment",
+			),
+		}
+	}
+}
+
+/// A comment as one visitor sees it, with the replies they can see.
+#[derive(Serialize)]
+struct Thread {
+	id: i64,
+	name: String,
+	/// Empty when the comment itself can't be shown but its replies can
+	body_html: Option<String>,
+	status: String,
+	created: DateTime<Local>,
+	edited: Option<DateTime<Local>>,
+	/// The identity this visitor left it with, if it's theirs
+	mine: Option<String>,
+	body: Option<String>,
+	replies: Vec<Thread>,
+}
+
+/// Something said about a post, either a comment thread or a webmention.
+#[derive(Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum Discussion {
+	Comment(Thread),
+	Mention(db::Mention),
+}
+
+impl Discussion {
+	fn at(&self) -> DateTime<Local> {
+		match self {
+			Discussion::Comment(thread) => thread.created,
+			Discussion::Mention(mention) => mention
+				.first_mentioned
+				.unwrap_or(mention.last_mentioned)
+				.into(),
+		}
+	}
+}
+
+/// Arrange comments into threads. Approved comments are shown to everyone
+/// and a guest's own comments are shown to them whatever their status.
+/// Anything else only appears, emptied, when it has replies to show.
+fn threads(comments: Vec<db::Comment>, identities: &Identities) -> Vec<Thread> {
+	let mut children: HashMap<Option<i64>, Vec<db::Comment>> = HashMap::new();
+	for comment in comments {
+		children.entry(comment.parent).or_default().push(comment);
+	}
+	replies_to(None, &mut children, identities)
+}
+
+fn replies_to(
+	parent: Option<i64>,
+	children: &mut HashMap<Option<i64>, Vec<db::Comment>>,
+	identities: &Identities,
+) -> Vec<Thread> {
+	children
+		.remove(&parent)
+		.unwrap_or_default()
+		.into_iter()
+		.filter_map(|comment| {
+			let replies = replies_to(Some(comment.id), children, identities);
+			let mine = identities
+				.get(&comment.provider)
+				.filter(|identity| identity.sub == comment.guest)
+				.map(|_| comment.provider.clone());
+			let visible = !comment.deleted
+				&& (comment.status == MessageStatus::Approved.to_string() || mine.is_some());
+			if !visible && replies.is_empty() {
+				return None;
+			}
+			Some(Thread {
+				id: comment.id,
+				name: if visible { comment.name } else { String::new() },
+				body_html: visible.then_some(comment.body_html),
+				body: (visible && mine.is_some()).then_some(comment.body),
+				status: comment.status,
+				created: comment.created,
+				edited: comment.edited,
+				mine: mine.filter(|_| visible),
+				replies,
+			})
+		})
+		.collect()
+}
+
+/// Turn what a guest typed into a comment, checking it's allowed.
+async fn render(body: &str) -> Result<(&str, String), CommentError> {
+	let body = body.trim();
+	if body.is_empty() {
+		return Err(CommentError::Empty);
+	}
+	if body.chars().count() > MAX_COMMENT_LEN {
+		return Err(CommentError::TooLong);
+	}
+	let html = pandoc::render_untrusted(body)
+		.await
+		.ok_or(CommentError::BadComment)?;
+	Ok((body, html))
+}
+
+/// Where new and edited comments start out.
+async fn initial_status(
+	config: &Config,
+	db: &Pool<Postgres>,
+	provider: &str,
+	sub: &str,
+) -> Result<MessageStatus, sqlx::Error> {
+	let trusted =
+		config.guestbook_auto_approve && db::is_returning_guest(db, provider, sub).await?;
+	Ok(if trusted {
+		MessageStatus::Approved
+	} else {
+		MessageStatus::Pending
+	})
+}
+
+async fn discussable(
+	config: &Config,
+	db: &Pool<Postgres>,
+	path: &str,
+) -> Result<db::ArticleMeta, CommentError> {
+	// Drafts can't be discussed until they're published
+	db::read_post_meta(db, path)
+		.await
+		.filter(|m| m.ready || config.develop)
+		.ok_or(CommentError::NotFound)
+}
+
+#[get("/<path..>")]
+pub async fn display(
+	path: PathBuf,
+	db: &State<Pool<Postgres>>,
+	tera: &State<Arc<RwLock<Tera>>>,
+	identities: Identities,
+	config: &State<Arc<Config>>,
+	prefs: Preferences,
+) -> Result<RawHtml<String>, CommentError> {
+	let path = &db::trim_path(config, &path);
+	let meta = discussable(config, db, path).await?;
+	let comments = db::comments(db, path).await?;
+	let mut discussion: Vec<_> = threads(comments, &identities)
+		.into_iter()
+		.map(Discussion::Comment)
+		.chain(
+			db::mentioners(db, path)
+				.await?
+				.into_iter()
+				.map(Discussion::Mention),
+		)
+		.collect();
+	discussion.sort_by_key(Discussion::at);
+	let content = crate::templates::render(
+		tera,
+		config,
+		"comments.html.tera",
+		&crate::context!({
+			"prefs": prefs,
+			"path": path,
+			"meta": meta,
+			"discussion": discussion,
+			"identities": identities,
+			"providers": config.oauth_providers.keys().collect::<Vec<_>>(),
+			"indieauth": config.enable_indieauth,
+			"max_comment_len": MAX_COMMENT_LEN,
+		}),
+	)
+	.await?;
+	Ok(RawHtml(content))
+}
+
+#[derive(FromForm)]
+pub struct CommentForm {
+	pub identity: String,
+	pub body: String,
+	pub parent: Option<i64>,
+}
+
+#[post("/<path..>", data = "<form>")]
+pub async fn post(
+	form: Form<CommentForm>,
+	path: PathBuf,
+	db: &State<Pool<Postgres>>,
+	identities: Identities,
+	config: &State<Arc<Config>>,
+	_rl: RocketGovernor<'_, GuestbookRateLimit>,
+) -> Result<Redirect, CommentError> {
+	let path = &db::trim_path(config, &path);
+	discussable(config, db, path).await?;
+	let Some(identity) = identities.get(&form.identity) else {
+		return Err(GuestbookError::NoIdentity.into());
+	};
+	guestbook::check_bans(config, db, &form.identity, identity).await?;
+	if let Some(parent) = form.parent
+		&& !db::comment(db, parent)
+			.await?
+			.is_some_and(|p| p.post == *path && !p.deleted)
+	{
+		return Err(CommentError::NotFound);
+	}
+	let (body, body_html) = render(&form.body).await?;
+	let status = initial_status(config, db, &form.identity, &identity.sub).await?;
+	let id = db::add_comment(
+		db,
+		db::NewComment {
+			post: path,
+			parent: form.parent,
+			provider: &form.identity,
+			guest: &identity.sub,
+			body,
+			body_html: &body_html,
+			status: &status.to_string(),
+		},
+	)
+	.await?;
+	Ok(Redirect::to(format!("/comments/{path}#comment-{id}")))
+}
+
+/// Look up a comment, making sure the visitor wrote it.
+async fn own_comment(
+	db: &Pool<Postgres>,
+	id: i64,
+	identities: &Identities,
+) -> Result<db::Comment, CommentError> {
+	let comment = db::comment(db, id)
+		.await?
+		.filter(|c| !c.deleted)
+		.ok_or(CommentError::NotFound)?;
+	if identities
+		.get(&comment.provider)
+		.is_none_or(|identity| identity.sub != comment.guest)
+	{
+		return Err(CommentError::NotYours);
+	}
+	Ok(comment)
+}
+
+#[derive(FromForm)]
+pub struct EditForm {
+	pub body: String,
+}
+
+#[post("/<id>/edit", data = "<form>")]
+pub async fn edit(
+	id: i64,
+	form: Form<EditForm>,
+	db: &State<Pool<Postgres>>,
+	identities: Identities,
+	config: &State<Arc<Config>>,
+	_rl: RocketGovernor<'_, GuestbookRateLimit>,
+) -> Result<Redirect, CommentError> {
+	let comment = own_comment(db, id, &identities).await?;
+	let identity = &identities[&comment.provider];
+	guestbook::check_bans(config, db, &comment.provider, identity).await?;
+	let (body, body_html) = render(&form.body).await?;
+	let status = initial_status(config, db, &comment.provider, &comment.guest).await?;
+	db::edit_comment(db, id, body, &body_html, &status.to_string()).await?;
+	Ok(Redirect::to(format!(
+		"/comments/{}#comment-{id}",
+		comment.post
+	)))
+}
+
+#[post("/<id>/delete")]
+pub async fn delete(
+	id: i64,
+	db: &State<Pool<Postgres>>,
+	identities: Identities,
+) -> Result<Redirect, CommentError> {
+	let comment = own_comment(db, id, &identities).await?;
+	db::delete_comment(db, id).await?;
+	Ok(Redirect::to(format!("/comments/{}", comment.post)))
+}
+
+#[get("/<path..>")]
+pub async fn feed(
+	path: PathBuf,
+	db: &State<Pool<Postgres>>,
+	tera: &State<Arc<RwLock<Tera>>>,
+	config: &State<Arc<Config>>,
+	visit: Visit,
+) -> Result<(ContentType, String), CommentError> {
+	let path = &db::trim_path(config, &path);
+	let meta = discussable(config, db, path).await?;
+	analytics::record(
+		db,
+		config,
+		visit,
+		ViewKind::Feed,
+		&format!("feed/comments/{path}"),
+	);
+	let comments = db::approved_comments(db, path, FEED_LEN).await?;
+	let content = tera
+		.read()
+		.await
+		.render(
+			"comments.atom.tera",
+			&crate::context!({
+				"path": path,
+				"meta": meta,
+				"comments": comments,
+				"config": (*config).clone(),
+			}),
+		)
+		.map_err(|e| AppError::Internal(format!("Couldn't render a feed: {e}")))?;
+	Ok((ContentType::new("application", "atom+xml"), content))
+}
+
+#[get("/")]
+pub async fn moderate(
+	admin: Admin,
+	db: &State<Pool<Postgres>>,
+	tera: &State<Arc<RwLock<Tera>>>,
+	config: &State<Arc<Config>>,
+	prefs: Preferences,
+) -> Result<RawHtml<String>, CommentError> {
+	let comments = db::recent_comments(db, 100).await?;
+	let content = crate::templates::render(
+		tera,
+		config,
+		"admin-comments.html.tera",
+		&crate::context!({
+			"prefs": prefs,
+			"admin": admin.identity,
+			"admin_provider": admin.provider,
+			"comments": comments,
+		}),
+	)
+	.await?;
+	Ok(RawHtml(content))
+}
+
+#[derive(FromForm)]
+pub struct CommentModerationForm {
+	pub id: i64,
+	pub status: MessageStatus,
+}
+
+#[post("/status", data = "<form>")]
+pub async fn set_status(
+	_admin: Admin,
+	form: Form<CommentModerationForm>,
+	db: &State<Pool<Postgres>>,
+) -> Result<Redirect, CommentError> {
+	db::set_comment_status(db, form.id, &form.status.to_string()).await?;
+	Ok(Redirect::to("/admin/comments"))
+}
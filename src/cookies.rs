@@ -1,40 +1,206 @@
-use chrono::NaiveDate;
+use chrono::{NaiveDate, Utc};
+use openidconnect::CsrfToken;
 use rocket::{
+	Request,
 	http::{Cookie, SameSite},
 	outcome::Outcome,
 	request::{self, FromRequest},
-	Request,
 };
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use sqlx::{Pool, Postgres};
+use std::{collections::HashMap, sync::Arc, time::Duration};
 
-#[derive(Serialize, Deserialize, Default, Clone)]
+use crate::{Config, db};
+
+/// What a visitor has read. The cookie only holds a random ID, and the
+/// rest lives in the database.
+#[derive(Serialize, Default, Clone)]
 pub struct ClientPersist {
+	#[serde(skip)]
+	pub id: String,
 	pub viewed: HashMap<String, NaiveDate>,
 }
 
+/// The whole read history, as the cookie used to carry it.
+#[derive(Deserialize)]
+struct LegacyClientPersist {
+	viewed: HashMap<String, NaiveDate>,
+}
+
+/// How many random bytes go into a visitor ID.
+const VISITOR_ID_BYTES: u32 = 16;
+
+/// Whether a cookie holds an ID we could have handed out: the unpadded URL-safe
+/// base64 of [`VISITOR_ID_BYTES`] random bytes.
+fn is_visitor_id(value: &str) -> bool {
+	value.len() == (VISITOR_ID_BYTES as usize * 4).div_ceil(3)
+		&& value
+			.bytes()
+			.all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+}
+
+impl ClientPersist {
+	fn new() -> Self {
+		ClientPersist {
+			id: CsrfToken::new_random_len(VISITOR_ID_BYTES).secret().clone(),
+			viewed: HashMap::new(),
+		}
+	}
+
+	/// Note that the visitor has just read the post at `path`.
+	pub async fn view(&mut self, db: &Pool<Postgres>, path: &str) {
+		let today = Utc::now().date_naive();
+		self.viewed.insert(path.to_string(), today);
+		let view = HashMap::from([(path.to_string(), today)]);
+		if let Err(e) = db::record_views(db, &self.id, &view).await {
+			eprintln!("Couldn't record a view of {path}: {e}");
+		}
+	}
+}
+
 #[async_trait]
 impl<'r> FromRequest<'r> for ClientPersist {
 	type Error = std::convert::Infallible;
 
+	async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+		let db = request.rocket().state::<Pool<Postgres>>().unwrap();
+		let Some(cookie) = request.cookies().get("client_persist") else {
+			return Outcome::Success(ClientPersist::new());
+		};
+		let value = cookie.value();
+		// Cookies from before read state moved to the database hold all of
+		// it as JSON, so carry it over and swap the cookie for an ID
+		if value.starts_with('{') {
+			let mut persist = ClientPersist::new();
+			if let Ok(legacy) = serde_json::from_str::<LegacyClientPersist>(value) {
+				persist.viewed = legacy.viewed;
+				if let Err(e) = db::record_views(db, &persist.id, &persist.viewed).await {
+					eprintln!("Couldn't carry over a visitor's read state: {e}");
+				}
+			}
+			request.cookies().add(persist.clone());
+			return Outcome::Success(persist);
+		}
+		// Anything else we didn't hand out, so start afresh
+		if !is_visitor_id(value) {
+			let persist = ClientPersist::new();
+			request.cookies().add(persist.clone());
+			return Outcome::Success(persist);
+		}
+		let viewed = db::visitor_views(db, value).await.unwrap_or_default();
+		Outcome::Success(ClientPersist {
+			id: value.to_string(),
+			viewed,
+		})
+	}
+}
+
+impl From<ClientPersist> for Cookie<'static> {
+	fn from(val: ClientPersist) -> Self {
+		let mut cookie = Cookie::new("client_persist", val.id);
+		cookie.set_path("/");
+		cookie.set_http_only(true);
+		cookie.set_same_site(SameSite::Lax);
+		cookie.make_permanent();
+		cookie
+	}
+}
+
+/// Forget visitors who haven't been back in a while, once a day.
+pub fn spawn_visitor_pruner(db: Pool<Postgres>, cfg: Arc<Config>) {
+	tokio::spawn(async move {
+		let mut daily = tokio::time::interval(Duration::from_hours(24));
+		loop {
+			daily.tick().await;
+			match db::prune_visitors(&db, cfg.visitor_expiry_days).await {
+				Ok(0) => {}
+				Ok(forgotten) => eprintln!("Forgot {forgotten} visitors who haven't been back"),
+				Err(e) => eprintln!("Error pruning visitors: {e}"),
+			}
+		}
+	});
+}
+
+/// Catppuccin flavor to draw the site in. `Auto` follows the browser's
+/// light or dark preference.
+#[derive(Serialize, Deserialize, FromFormField, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Theme {
+	#[default]
+	Auto,
+	Latte,
+	Frappe,
+	Macchiato,
+	Mocha,
+}
+
+/// How English translations of toki pona text are shown.
+#[derive(Serialize, Deserialize, FromFormField, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Translation {
+	#[default]
+	Hidden,
+	Inline,
+	Hover,
+}
+
+#[derive(Serialize, Deserialize, FromFormField, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FontSize {
+	Small,
+	#[default]
+	Normal,
+	Large,
+	Larger,
+}
+
+/// How a visitor likes pages to look. Every page gets these, so they apply
+/// from the first paint without any scripts.
+#[derive(Serialize, Deserialize, FromForm, Clone)]
+#[serde(default)]
+pub struct Preferences {
+	pub theme: Theme,
+	pub sitelen_pona: bool,
+	pub translation: Translation,
+	pub font_size: FontSize,
+	pub reduced_motion: bool,
+}
+
+impl Default for Preferences {
+	fn default() -> Self {
+		Preferences {
+			theme: Theme::Auto,
+			sitelen_pona: true,
+			translation: Translation::Hidden,
+			font_size: FontSize::Normal,
+			reduced_motion: false,
+		}
+	}
+}
+
+#[async_trait]
+impl<'r> FromRequest<'r> for Preferences {
+	type Error = std::convert::Infallible;
+
 	async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
 		Outcome::Success(
 			request
 				.cookies()
-				.get("client_persist")
+				.get("preferences")
 				.and_then(|c| serde_json::from_str(c.value()).ok())
 				.unwrap_or_default(),
 		)
 	}
 }
 
-impl From<ClientPersist> for Cookie<'static> {
-	fn from(val: ClientPersist) -> Self {
+impl From<Preferences> for Cookie<'static> {
+	fn from(val: Preferences) -> Self {
 		let mut cookie = Cookie::new(
-			"client_persist",
+			"preferences",
 			serde_json::to_string(&val).unwrap_or_default(),
 		);
 		cookie.set_path("/");
+		cookie.set_same_site(SameSite::Lax);
 		cookie.make_permanent();
 		cookie
 	}
@@ -72,3 +238,20 @@ impl From<SecurePersist> for Cookie<'static> {
 		cookie
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn only_ids_we_issue_are_accepted() {
+		for _ in 0..100 {
+			assert!(is_visitor_id(&ClientPersist::new().id));
+		}
+		assert!(!is_visitor_id(""));
+		assert!(!is_visitor_id("short"));
+		assert!(!is_visitor_id(&"a".repeat(4096)));
+		assert!(!is_visitor_id("abcdefghijklmnopqrs'--"));
+		assert!(!is_visitor_id("abcdefghijklmnopqrst+/"));
+	}
+}
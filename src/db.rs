@@ -1,27 +1,28 @@
-use crate::{Config, oauth::Identity, pandoc};
-use chrono::{DateTime, Days, Duration, Local, NaiveDate, Utc};
-use color_eyre::eyre;
-use dom_query::Document;
+use crate::{
+	Config, admin,
+	oauth::Identity,
+	pandoc::{self, Reader},
+	webmention,
+};
+use chrono::{DateTime, Days, Local, NaiveDate, Utc};
 use pandoc_ast::{Block, Inline, MetaValue, Pandoc};
-use reqwest::Client;
-use rocket::tokio::{self};
+use rocket::{
+	http::RawStr,
+	tokio::{self},
+};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use sqlx::{Pool, Postgres, query, query_as, types::Json};
 use std::{
 	collections::{BTreeSet, HashMap, HashSet},
-	ffi::OsStr,
 	fmt::Display,
-	ops::{Bound, RangeBounds},
-	path::{Path, PathBuf},
-	str::FromStr,
+	ops::Bound,
+	path::{Component, Path, PathBuf},
 	sync::Arc,
-	time::SystemTime,
 };
 use strum::EnumString;
 use tracing::instrument;
 use url::Url;
-use walkdir::WalkDir;
 
 const DEFAULT_TITLE: &dyn Fn() -> String = &|| "Untitled Page".to_string();
 const DEFAULT_TEMPLATE: &dyn Fn() -> String = &|| "article.html.tera".to_string();
@@ -34,6 +35,7 @@ pub struct Link {
 
 #[serde_with::serde_as]
 #[derive(Serialize, Deserialize, Clone, Debug, Default)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct ArticleMeta {
 	#[serde(default = "DEFAULT_TITLE")]
 	pub title: String,
@@ -45,8 +47,10 @@ pub struct ArticleMeta {
 	pub tags: Vec<String>,
 	#[serde(default = "DEFAULT_TEMPLATE")]
 	pub template: String,
+	/// Written in frontmatter, or generated from the headings when the post
+	/// is read. Empty for posts with `toc: false`.
 	#[serde(default)]
-	pub toc: Vec<Toc>,
+	pub toc: Option<Vec<Toc>>,
 	#[serde(default)]
 	pub exclude_from_rss: bool,
 	#[serde(default)]
@@ -59,10 +63,32 @@ pub struct ArticleMeta {
 	pub always_rerender: bool,
 	#[serde(flatten)]
 	pub extra: Value,
+	/// The image shown when the post is shared, instead of a generated one
+	#[serde(default)]
+	pub cover: Option<String>,
+	/// What the post is written in, as a BCP 47 tag. Filled in with the
+	/// site's language when the post is read.
+	#[serde(default)]
+	pub lang: Option<String>,
+	/// The path of the post this one translates. Every translation of a
+	/// post points at the same original.
+	#[serde(default)]
+	pub translation_of: Option<String>,
+	/// Pandoc writer options for just this post
+	#[serde(default)]
+	pub writer: serde_json::Map<String, Value>,
 	#[serde(default)]
 	pub mentioners: Vec<String>,
 	#[serde(default)]
 	pub mentions: Vec<String>,
+	/// The series this post is part of. Its prev, next and up links are
+	/// worked out from the series unless they're given here.
+	#[serde(default)]
+	pub series: Option<String>,
+	/// Where the post goes in its series, if not by date
+	#[serde(default)]
+	#[serde_as(as = "Option<serde_with::PickFirst<(_, serde_with::DisplayFromStr)>>")]
+	pub series_order: Option<i64>,
 	#[serde(default)]
 	#[serde_as(deserialize_as = "serde_with::DefaultOnError")]
 	pub next: Option<Link>,
@@ -82,8 +108,7 @@ impl TryFrom<&Pandoc> for ArticleMeta {
 			match i {
 				pandoc_ast::Inline::Str(s) => s.as_str(),
 				pandoc_ast::Inline::Space => " ",
-				pandoc_ast::Inline::SoftBreak => "\n",
-				pandoc_ast::Inline::LineBreak => "\n",
+				pandoc_ast::Inline::SoftBreak | pandoc_ast::Inline::LineBreak => "\n",
 				_ => "",
 			}
 		}
@@ -134,17 +159,89 @@ impl TryFrom<&Pandoc> for ArticleMeta {
 				}
 			}
 		}
-		let meta = pandoc_ast
+		let mut meta = pandoc_ast
 			.meta
 			.iter()
-			.map(|(key, value)| (key.to_string(), pandoc_meta_to_value(value.clone())))
+			.map(|(key, value)| (key.clone(), pandoc_meta_to_value(value.clone())))
 			.collect();
+		normalize_meta(&mut meta);
 		let meta = serde_json::Value::Object(meta);
 		let meta: ArticleMeta = serde_json::from_value(meta)?;
 		Ok(meta)
 	}
 }
 
+/// Bring metadata from formats that can only write plain text, or that have
+/// their own names for things, into the shape markdown frontmatter has.
+fn normalize_meta(meta: &mut serde_json::Map<String, Value>) {
+	// Org-mode's `#+filetags: :a:b:` and the `keywords` most formats have
+	if !meta.contains_key("tags") {
+		let tags = meta.remove("filetags").or_else(|| meta.remove("keywords"));
+		if let Some(tags) = tags {
+			meta.insert("tags".to_string(), tags);
+		}
+	}
+	if let Some(Value::String(tags)) = meta.get("tags") {
+		let tags = tags
+			.split(|c: char| c == ',' || c == ':' || c.is_whitespace())
+			.filter(|t| !t.is_empty())
+			.map(|t| Value::String(t.to_string()))
+			.collect();
+		meta.insert("tags".to_string(), Value::Array(tags));
+	}
+	for key in [
+		"exclude_from_rss",
+		"hidden",
+		"ready",
+		"always_rerender",
+		"toc",
+	] {
+		let Some(Value::String(value)) = meta.get(key) else {
+			continue;
+		};
+		let value = match value.trim().to_ascii_lowercase().as_str() {
+			"true" | "t" | "yes" | "y" | "1" => true,
+			"false" | "nil" | "no" | "n" | "0" | "" => false,
+			_ => continue,
+		};
+		meta.insert(key.to_string(), Value::Bool(value));
+	}
+	// A table of contents is generated unless one is written out, so
+	// `toc: true` is the same as not saying anything
+	match meta.get("toc") {
+		Some(Value::Bool(false)) => {
+			meta.insert("toc".to_string(), Value::Array(vec![]));
+		}
+		Some(Value::Bool(true)) => {
+			meta.remove("toc");
+		}
+		_ => {}
+	}
+	if !meta.contains_key("created")
+		&& let Some(date) = meta.get("date").cloned()
+	{
+		meta.insert("created".to_string(), date);
+	}
+	if !meta.contains_key("updated")
+		&& let Some(created) = meta.get("created").cloned()
+	{
+		meta.insert("updated".to_string(), created);
+	}
+	// Org timestamps look like `<2025-01-02 Thu>`
+	for key in ["created", "updated"] {
+		let Some(Value::String(date)) = meta.get(key) else {
+			continue;
+		};
+		let date = date.trim().trim_start_matches(['<', '[']);
+		if let Some(date) = date
+			.get(..10)
+			.filter(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").is_ok())
+		{
+			meta.insert(key.to_string(), Value::String(date.to_string()));
+		}
+	}
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub enum Toc {
 	Text(String),
@@ -155,24 +252,31 @@ pub enum Toc {
 	},
 }
 
+/// Labels and anchors are kept as plain text and escaped here, so the JSON
+/// and template forms of a table of contents get them unescaped.
 impl Display for Toc {
 	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 		match self {
-			Toc::Text(text) => write!(f, "<li>{text}</li>"),
+			Toc::Text(text) => write!(f, "<li>{}</li>", tera::escape_html(text)),
 			Toc::Heading {
 				label,
 				anchor,
 				subheadings,
-			} if subheadings.is_empty() => {
-				write!(f, "<li><a href=\"#{anchor}\">{label}</a></li>")
-			}
+			} if subheadings.is_empty() => write!(
+				f,
+				"<li><a href=\"#{}\">{}</a></li>",
+				tera::escape_html(anchor),
+				tera::escape_html(label)
+			),
 			Toc::Heading {
 				label,
 				anchor,
 				subheadings,
 			} => write!(
 				f,
-				"<li><a href=\"#{anchor}\">{label}</a><ul>{}</ul></li>",
+				"<li><a href=\"#{}\">{}</a><ul>{}</ul></li>",
+				tera::escape_html(anchor),
+				tera::escape_html(label),
 				subheadings
 					.iter()
 					.map(std::string::ToString::to_string)
@@ -182,6 +286,43 @@ impl Display for Toc {
 	}
 }
 
+/// A table of contents entry in a shape templates can walk, rather than
+/// the `<li>`s `Toc` displays as.
+#[derive(Serialize)]
+pub struct TocEntry<'a> {
+	/// Plain text, like the anchor, so templates need to escape both
+	label: &'a str,
+	anchor: Option<&'a str>,
+	/// 1 for the top level
+	depth: usize,
+	children: Vec<TocEntry<'a>>,
+}
+
+impl Toc {
+	pub fn entries(toc: &[Toc], depth: usize) -> Vec<TocEntry<'_>> {
+		toc.iter()
+			.map(|entry| match entry {
+				Toc::Text(label) => TocEntry {
+					label,
+					anchor: None,
+					depth,
+					children: vec![],
+				},
+				Toc::Heading {
+					label,
+					anchor,
+					subheadings,
+				} => TocEntry {
+					label,
+					anchor: Some(anchor),
+					depth,
+					children: Toc::entries(subheadings, depth + 1),
+				},
+			})
+			.collect()
+	}
+}
+
 #[derive(
 	Serialize,
 	Deserialize,
@@ -203,16 +344,87 @@ pub enum PostType {
 	Reply,
 }
 
-pub fn trim_path(path: &Path) -> String {
+/// The reader for a source file, going by its extension.
+pub fn reader<'a>(cfg: &'a Config, path: &Path) -> Option<&'a Reader> {
+	let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+	cfg.readers.get(&ext)
+}
+
+/// The post path for a file or URL path, which never includes the source
+/// format's extension.
+pub fn trim_path(cfg: &Config, path: &Path) -> String {
+	let path = if reader(cfg, path).is_some() {
+		path.with_extension("")
+	} else {
+		path.to_path_buf()
+	};
 	path.to_string_lossy()
 		.trim_start_matches(['.', '/'])
-		.trim_end_matches(".md")
 		.to_string()
 }
 
+/// Work out whatever a post's frontmatter leaves to the site.
+fn fill_meta(cfg: &Config, path: &str, ast: &Pandoc, meta: &mut ArticleMeta) {
+	if meta.toc.is_none() {
+		meta.toc = Some(pandoc::table_of_contents(
+			ast,
+			cfg.toc_min_depth..=cfg.toc_max_depth,
+		));
+	}
+	meta.lang.get_or_insert_with(|| cfg.default_lang.clone());
+	meta.series = meta
+		.series
+		.take()
+		.map(|series| series.trim().to_string())
+		.filter(|series| !series.is_empty());
+	// Translations can name their original by link or by path
+	meta.translation_of = meta
+		.translation_of
+		.as_deref()
+		.map(|original| {
+			let original = original
+				.strip_prefix(cfg.origin.as_str())
+				.unwrap_or(original)
+				.trim_start_matches('/');
+			trim_path(
+				cfg,
+				Path::new(original.strip_prefix("post/").unwrap_or(original)),
+			)
+		})
+		.filter(|original| !original.is_empty() && original != path);
+}
+
+/// Every source file that provides a post. There should only be one, but
+/// nothing stops `post.md` and `post.org` from sitting side by side.
+pub fn source_files(cfg: &Config, path: &str) -> Vec<PathBuf> {
+	let mut extensions: Vec<_> = cfg.readers.keys().collect();
+	extensions.sort();
+	extensions
+		.into_iter()
+		.map(|ext| cfg.content_root.join(format!("{path}.{ext}")))
+		.filter(|file| file.is_file())
+		.collect()
+}
+
+/// The post a URL on our site refers to, if any.
+pub fn post_path(cfg: &Config, url: &Url) -> Option<String> {
+	if url.host() != cfg.origin.host() {
+		return None;
+	}
+	let path = url.path().trim_end_matches('/').strip_prefix("/post/")?;
+	let path = RawStr::new(path).percent_decode().ok()?;
+	let path = trim_path(cfg, Path::new(path.as_ref()));
+	let safe = Path::new(&path)
+		.components()
+		.all(|c| matches!(c, Component::Normal(_)));
+	(safe && !path.is_empty()).then_some(path)
+}
+
 pub async fn update_all(cfg: Arc<Config>, db: &Pool<Postgres>) -> color_eyre::Result<()> {
-	update_posts(db, &cfg).await?;
-	Ok(())
+	admin::update_started();
+	let result = update_posts(db, &cfg).await;
+	admin::update_finished(result.as_ref().err().map(ToString::to_string));
+	result
 }
 
 #[instrument(skip(cfg, db))]
@@ -221,55 +433,93 @@ pub async fn update_one(
 	db: &Pool<Postgres>,
 	fs_path: &str,
 ) -> Result<(), sqlx::Error> {
-	if !std::path::Path::new(fs_path)
-		.extension()
-		.is_some_and(|ext| ext.eq_ignore_ascii_case("md"))
-	{
+	ingest(cfg, db, Path::new(fs_path), false).await
+}
+
+/// Bring the database in line with one file in the content root. Unless
+/// `force` is set, this does nothing for files older than their post.
+async fn ingest(
+	cfg: &Config,
+	db: &Pool<Postgres>,
+	fs_path: &Path,
+	force: bool,
+) -> Result<(), sqlx::Error> {
+	let Some(reader) = reader(cfg, fs_path) else {
+		if fs_path.extension().is_some_and(|ext| ext == "yaml") {
+			Box::pin(ingest_metadata_file(cfg, db, fs_path)).await?;
+		}
 		return Ok(());
-	}
-	let path = trim_path(Path::new(fs_path));
-	let fs_path = cfg.content_root.join(fs_path.trim_start_matches('/'));
+	};
+	let path = trim_path(cfg, fs_path);
+	let fs_path = cfg
+		.content_root
+		.join(fs_path.strip_prefix("/").unwrap_or(fs_path));
 	let db_article = query!(
 		r#"SELECT updated as "updated: chrono::DateTime<Utc>", meta FROM posts WHERE path = $1"#,
 		path
 	)
 	.fetch_optional(db)
 	.await?;
+	let old_meta = db_article
+		.as_ref()
+		.and_then(|a| serde_json::from_value::<ArticleMeta>(a.meta.clone()).ok());
+	let old_series = old_meta.as_ref().and_then(|m| m.series.clone());
+	// Drafts don't send webmentions, so there's nothing to take back
+	let old_mentions = old_meta.map(|m| if m.ready { m.mentions } else { vec![] });
 
-	let Ok(modified) = tokio::fs::metadata(&fs_path)
-		.await
-		.and_then(|m| m.modified())
-		.map(DateTime::<Utc>::from)
-	else {
-		// File has been removed, delete it
+	let Some(modified) = modified_time(&fs_path).await else {
+		// File has been removed, so fall back to another source for the same post
+		if let Some(other) = source_files(cfg, &path).first() {
+			let other = other.strip_prefix(&cfg.content_root).unwrap();
+			return Box::pin(ingest(cfg, db, other, true)).await;
+		}
 		if db_article.is_some() {
-			query!("DELETE FROM posts WHERE path = $1", path)
-				.execute(db)
-				.await?;
+			remove_post(cfg, db, &path, &old_mentions.unwrap_or_default()).await?;
 		}
+		admin::forget(&path);
 		return Ok(());
 	};
+	let modified = match reader.metadata_file(&fs_path) {
+		Some(metadata) => modified.max(modified_time(&metadata).await.unwrap_or(modified)),
+		None => modified,
+	};
 
-	if db_article.is_some_and(|db_article| db_article.updated >= modified) {
+	if !force && db_article.is_some_and(|db_article| db_article.updated >= modified) {
 		// Database representation is up to date
 		return Ok(());
 	}
 
-	let Some(ast) = pandoc::md_to_ast(&fs_path, cfg).await else {
-		eprintln!("Malformed article {path}");
-		return Ok(());
+	let sources = source_files(cfg, &path);
+	if sources.len() > 1 {
+		eprintln!(
+			"{path} has more than one source file, using {}",
+			fs_path.display()
+		);
+	}
+
+	let ast = match pandoc::source_to_ast(&fs_path, reader, cfg).await {
+		Ok(ast) => ast,
+		Err(e) => {
+			eprintln!("Malformed article {path}");
+			admin::content_error(&path, &fs_path, format!("Pandoc couldn't read this: {e}"));
+			return Ok(());
+		}
 	};
 	let ast = pandoc::run_preproc_filters(db, ast, &path, cfg).await;
-	let Some(meta) = ArticleMeta::try_from(&ast)
-		.inspect_err(|e| eprintln!("Failed to load meta from {path} with {e}"))
-		.ok()
-	else {
-		return Ok(());
+	let mut meta = match ArticleMeta::try_from(&ast) {
+		Ok(meta) => meta,
+		Err(e) => {
+			eprintln!("Failed to load meta from {path} with {e}");
+			admin::content_error(&path, &fs_path, format!("Bad metadata: {e}"));
+			return Ok(());
+		}
 	};
-	if !meta.ready && !cfg.develop {
-		eprintln!("Skip {path} since it's not ready and we're in production");
+	if let Err(e) = cfg.writer.merged(&meta.writer) {
+		eprintln!("Bad writer options in {path}: {e}");
+		admin::content_error(&path, &fs_path, format!("Bad writer options: {e}"));
 		return Ok(());
 	}
+	fill_meta(cfg, &path, &ast, &mut meta);
 	let ast = serde_json::to_value(&ast).unwrap();
 	let meta_json = serde_json::to_value(&meta).unwrap();
 	query!(
@@ -286,21 +536,74 @@ pub async fn update_one(
 	)
 	.execute(db)
 	.await?;
-	// for to_url in meta
-	// 	.mentions
-	// 	.iter()
-	// 	.chain(old_meta.iter().flat_map(|m| m.mentions.iter()))
-	// {
-	// 	tokio::spawn(send_webmention(
-	// 		db.clone(),
-	// 		cfg.clone(),
-	// 		path.clone(),
-	// 		to_url.clone(),
-	// 	));
-	// }
+	// A post leaving one series for another renumbers both
+	for series in old_series.iter().chain(&meta.series) {
+		refresh_series(db, series).await?;
+	}
+	let mentions = if meta.ready { &meta.mentions[..] } else { &[] };
+	match old_mentions {
+		Some(old_mentions) => {
+			webmention::queue_changes(cfg, db, &path, &old_mentions, mentions).await?;
+		}
+		// A post we've never seen before, or one we've had to re-ingest from scratch
+		None => webmention::queue_new(cfg, db, &path, mentions).await?,
+	}
+	admin::ingested(&path);
 	Ok(())
 }
 
+async fn modified_time(file: &Path) -> Option<DateTime<Utc>> {
+	tokio::fs::metadata(file)
+		.await
+		.and_then(|m| m.modified())
+		.map(DateTime::<Utc>::from)
+		.ok()
+}
+
+/// A changed metadata file means the post next to it has changed.
+async fn ingest_metadata_file(
+	cfg: &Config,
+	db: &Pool<Postgres>,
+	fs_path: &Path,
+) -> Result<(), sqlx::Error> {
+	let path = trim_path(cfg, &fs_path.with_extension(""));
+	for source in source_files(cfg, &path) {
+		if reader(cfg, &source).is_some_and(|r| r.metadata_file) {
+			let source = source.strip_prefix(&cfg.content_root).unwrap();
+			ingest(cfg, db, source, true).await?;
+		}
+	}
+	Ok(())
+}
+
+/// Drop a post, notifying everything it used to mention.
+async fn remove_post(
+	cfg: &Config,
+	db: &Pool<Postgres>,
+	path: &str,
+	old_mentions: &[String],
+) -> Result<(), sqlx::Error> {
+	// Drafts were never public, so they can vanish without a trace
+	query!(
+		"INSERT INTO removed_posts (path)
+        SELECT path FROM posts WHERE path = $1 AND meta->>'ready' = 'true'
+        ON CONFLICT (path) DO UPDATE SET removed = NOW()",
+		path
+	)
+	.execute(db)
+	.await?;
+	let removed = query!(
+		r#"DELETE FROM posts WHERE path = $1 RETURNING meta->>'series' AS series"#,
+		path
+	)
+	.fetch_optional(db)
+	.await?;
+	if let Some(series) = removed.and_then(|r| r.series) {
+		refresh_series(db, &series).await?;
+	}
+	webmention::queue_changes(cfg, db, path, old_mentions, &[]).await
+}
+
 #[instrument(skip_all)]
 async fn update_posts(db: &Pool<Postgres>, cfg: &Config) -> color_eyre::Result<()> {
 	#[instrument(skip(db, cfg))]
@@ -310,7 +613,7 @@ async fn update_posts(db: &Pool<Postgres>, cfg: &Config) -> color_eyre::Result<(
 		path: PathBuf,
 	) -> color_eyre::Result<()> {
 		let fs_path = cfg.content_root.join(&path);
-		if fs_path.is_file() && fs_path.extension() == Some(OsStr::new("md")) {
+		if fs_path.is_file() && reader(cfg, &fs_path).is_some() {
 			let fs_path = fs_path.strip_prefix(&cfg.content_root).unwrap();
 			update_one(cfg, db, fs_path.to_str().unwrap()).await?;
 			return Ok(());
@@ -329,20 +632,22 @@ async fn update_posts(db: &Pool<Postgres>, cfg: &Config) -> color_eyre::Result<(
 	}
 	update_walk(db, cfg, PathBuf::new()).await?;
 
-	let existing_posts = query!("SELECT path FROM posts").fetch_all(db).await?;
+	let existing_posts = query!("SELECT path, meta FROM posts").fetch_all(db).await?;
 	for post in existing_posts {
-		let path = cfg.content_root.join(&post.path).with_extension("md");
-		if !path.exists() {
-			query!("DELETE FROM posts WHERE path = $1", post.path)
-				.execute(db)
-				.await?;
+		if source_files(cfg, &post.path).is_empty() {
+			let old_mentions = serde_json::from_value::<ArticleMeta>(post.meta)
+				.ok()
+				.filter(|m| m.ready)
+				.map(|m| m.mentions)
+				.unwrap_or_default();
+			remove_post(cfg, db, &post.path, &old_mentions).await?;
 		}
 	}
 	Ok(())
 }
 
 pub async fn read_post(db: &Pool<Postgres>, path: &str) -> Option<(Pandoc, ArticleMeta)> {
-	let path = path.trim_start_matches(['.', '/']).trim_end_matches(".md");
+	let path = path.trim_start_matches(['.', '/']);
 	let result = query!(r#"SELECT ast, meta FROM posts WHERE path = $1"#, path)
 		.fetch_optional(db)
 		.await
@@ -360,7 +665,7 @@ pub async fn read_post(db: &Pool<Postgres>, path: &str) -> Option<(Pandoc, Artic
 }
 
 pub async fn read_post_meta(db: &Pool<Postgres>, path: &str) -> Option<ArticleMeta> {
-	let path = path.trim_start_matches(['.', '/']).trim_end_matches(".md");
+	let path = path.trim_start_matches(['.', '/']);
 	let result = query!(r#"SELECT meta FROM posts WHERE path = $1"#, path)
 		.fetch_optional(db)
 		.await
@@ -373,92 +678,141 @@ pub async fn read_post_meta(db: &Pool<Postgres>, path: &str) -> Option<ArticleMe
 	Some(meta)
 }
 
-#[cfg(not(debug_assertions))]
-async fn send_webmention(
-	_db: Pool<Postgres>,
-	_cfg: Arc<Config>,
-	_from_path: String,
-	_to_url: String,
-) -> Result<bool, String> {
-	// This is broken
-	Ok(false)
-}
-
-#[cfg(debug_assertions)]
-async fn send_webmention(
-	_db: Pool<Postgres>,
-	cfg: Arc<Config>,
-	from_path: String,
-	to_url: String,
-) -> Result<bool, String> {
-	tokio::time::sleep(Duration::seconds(30).to_std().unwrap()).await;
-	if !cfg.enable_webmention {
-		return Ok(false);
-	}
-	// let post = query!(
-	// 	"SELECT updated FROM visible_posts WHERE path = $1",
-	// 	from_path
-	// )
-	// .fetch_optional(&db)
-	// .await
-	// .map_err(|e| e.to_string())?;
-	let mut to_url = Url::from_str(&to_url).map_err(|e| e.to_string())?;
-	if !to_url.has_host() {
-		to_url.set_host(cfg.origin.host_str()).unwrap();
-		to_url.set_scheme(cfg.origin.scheme()).unwrap();
-	}
-	let Some(response) = reqwest::get(to_url.as_str()).await.ok() else {
-		return Ok(false);
-	};
-	let Some(response) = response.text().await.ok() else {
-		return Ok(false);
-	};
-	let wm_url = {
-		let doc = Document::from(response);
-		let element = doc.select_single("link[rel=webmention], a[rel=webmention]");
-		let wm_url = element.attr("href");
-		let Some(wm_url) = wm_url.as_deref() else {
-			return Ok(false);
-		};
-		let Ok(wm_url) = to_url.join(wm_url) else {
-			return Ok(false);
-		};
-		wm_url
-	};
-	let mut from_url = cfg.origin.clone();
-	let mut callback_url = cfg.origin.clone();
-	from_url.set_path(&from_path);
-	callback_url.set_path("/webmention_callback");
-	let client = Client::new();
-
-	let mut form = HashMap::new();
-	form.insert("source", from_url.as_str());
-	form.insert("target", to_url.as_str());
-	client
-		.post(wm_url)
-		.header("Content-Type", "application/x-www-form-urlencoded")
-		.form(&form)
-		.send()
-		.await
-		.map_err(|e| e.to_string())?;
-	Ok(true)
+#[derive(Debug, Serialize)]
+pub struct OutgoingMention {
+	pub from_path: String,
+	pub to_url: String,
+	pub status: String,
+	pub attempts: i32,
+	pub next_attempt: DateTime<Utc>,
+	pub last_attempt: Option<DateTime<Utc>>,
+	pub endpoint: Option<String>,
+	pub response_code: Option<i32>,
+	pub last_error: Option<String>,
+}
+
+/// Queue webmentions from `from_path` to each of `to_urls`.
+/// With `renotify` set, targets we've already dealt with are queued again.
+pub async fn queue_outgoing_mentions(
+	db: &Pool<Postgres>,
+	from_path: &str,
+	to_urls: &[String],
+	renotify: bool,
+) -> Result<(), sqlx::Error> {
+	query!(
+		"INSERT INTO outgoing_mentions (from_path, to_url)
+            SELECT $1, UNNEST($2::text[])
+            ON CONFLICT (from_path, to_url)
+            DO UPDATE SET
+                status = 'pending',
+                attempts = 0,
+                next_attempt = NOW(),
+                last_error = NULL
+            WHERE $3",
+		from_path,
+		to_urls,
+		renotify
+	)
+	.execute(db)
+	.await
+	.map(|_| ())
+}
+
+/// Take up to `limit` due webmentions off the queue, hiding them from other
+/// workers for `lease` seconds.
+pub async fn claim_outgoing_mentions(
+	db: &Pool<Postgres>,
+	limit: i64,
+	lease: f64,
+) -> Result<Vec<OutgoingMention>, sqlx::Error> {
+	query_as!(
+		OutgoingMention,
+		"UPDATE outgoing_mentions
+            SET next_attempt = NOW() + make_interval(secs => $2)
+            WHERE (from_path, to_url) IN (
+                SELECT from_path, to_url FROM outgoing_mentions
+                WHERE status = 'pending' AND next_attempt <= NOW()
+                ORDER BY next_attempt
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING from_path, to_url, status, attempts, next_attempt,
+                last_attempt, endpoint, response_code, last_error",
+		limit,
+		lease
+	)
+	.fetch_all(db)
+	.await
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn record_outgoing_mention(
+	db: &Pool<Postgres>,
+	from_path: &str,
+	to_url: &str,
+	status: &str,
+	next_attempt: DateTime<Utc>,
+	endpoint: Option<&str>,
+	response_code: Option<i32>,
+	error: Option<&str>,
+) -> Result<(), sqlx::Error> {
+	query!(
+		"UPDATE outgoing_mentions
+            SET status = $3,
+                attempts = attempts + 1,
+                last_attempt = NOW(),
+                next_attempt = $4,
+                endpoint = $5,
+                response_code = $6,
+                last_error = $7
+            WHERE from_path = $1 AND to_url = $2",
+		from_path,
+		to_url,
+		status,
+		next_attempt,
+		endpoint,
+		response_code,
+		error
+	)
+	.execute(db)
+	.await
+	.map(|_| ())
+}
+
+pub async fn outgoing_mentions(
+	db: &Pool<Postgres>,
+	from_path: &str,
+) -> Result<Vec<OutgoingMention>, sqlx::Error> {
+	query_as!(
+		OutgoingMention,
+		"SELECT from_path, to_url, status, attempts, next_attempt, last_attempt,
+            endpoint, response_code, last_error
+            FROM outgoing_mentions WHERE from_path = $1 ORDER BY to_url",
+		from_path
+	)
+	.fetch_all(db)
+	.await
 }
 
+/// Record a verified webmention. New mentions start out pending unless
+/// `approved` is set; mentions we already have keep their moderation status.
 pub async fn get_webmention(
 	db: &Pool<Postgres>,
 	source: &Url,
 	path: &str,
+	approved: bool,
 ) -> Result<(), sqlx::Error> {
 	let now = Utc::now();
 	query!(
-		"INSERT INTO incoming_mentions
-            VALUES ($1, $2, $3, $3)
+		"INSERT INTO incoming_mentions (to_path, from_url, last_mentioned, first_mentioned, status)
+            VALUES ($1, $2, $3, $3, CASE WHEN $4 THEN 'approved' ELSE 'pending' END)
             ON CONFLICT (to_path, from_url)
             DO UPDATE
             SET last_mentioned = $3",
 		path,
 		source.as_str(),
-		now
+		now,
+		approved
 	)
 	.execute(db)
 	.await?;
@@ -483,6 +837,143 @@ pub async fn rm_webmention(
 	Ok(())
 }
 
+#[derive(Debug, Serialize)]
+pub struct MentionRequest {
+	pub id: String,
+	pub source: String,
+	pub target: String,
+	pub to_path: String,
+	pub status: String,
+	pub detail: Option<String>,
+	pub attempts: i32,
+	pub received: DateTime<Utc>,
+	pub processed: Option<DateTime<Utc>>,
+}
+
+/// Whether an id looks like the UUIDs we give requests and bans, so it can be
+/// cast without the database erroring on whatever a visitor typed.
+fn is_uuid(id: &str) -> bool {
+	id.len() == 36
+		&& id.char_indices().all(|(i, c)| match i {
+			8 | 13 | 18 | 23 => c == '-',
+			_ => c.is_ascii_hexdigit(),
+		})
+}
+
+/// Queue a webmention for verification, returning the id of its request.
+/// A source that's already waiting on the same post shares its request, and
+/// one that's being verified right now is queued again once it's done.
+pub async fn queue_mention_request(
+	db: &Pool<Postgres>,
+	source: &Url,
+	target: &Url,
+	path: &str,
+) -> Result<String, sqlx::Error> {
+	query!(
+		r#"INSERT INTO mention_requests (source, target, to_path)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (source, to_path)
+            DO UPDATE SET
+                target = EXCLUDED.target,
+                received = NOW(),
+                -- While a worker has it, leave it be and let the worker queue it again
+                resend = COALESCE(mention_requests.leased_until > NOW(), false),
+                status = CASE WHEN mention_requests.leased_until > NOW()
+                    THEN mention_requests.status ELSE 'queued' END,
+                detail = CASE WHEN mention_requests.leased_until > NOW()
+                    THEN mention_requests.detail END,
+                attempts = CASE WHEN mention_requests.leased_until > NOW()
+                    THEN mention_requests.attempts ELSE 0 END,
+                next_attempt = CASE WHEN mention_requests.leased_until > NOW()
+                    THEN mention_requests.next_attempt
+                    ELSE LEAST(mention_requests.next_attempt, NOW()) END,
+                processed = CASE WHEN mention_requests.leased_until > NOW()
+                    THEN mention_requests.processed END
+            RETURNING id::text as "id!""#,
+		source.as_str(),
+		target.as_str(),
+		path
+	)
+	.fetch_one(db)
+	.await
+	.map(|r| r.id)
+}
+
+/// Take up to `limit` due webmentions off the verification queue, hiding them
+/// from other workers for `lease` seconds.
+pub async fn claim_mention_requests(
+	db: &Pool<Postgres>,
+	limit: i64,
+	lease: f64,
+) -> Result<Vec<MentionRequest>, sqlx::Error> {
+	query_as!(
+		MentionRequest,
+		r#"UPDATE mention_requests
+            SET next_attempt = NOW() + make_interval(secs => $2),
+                leased_until = NOW() + make_interval(secs => $2)
+            WHERE id IN (
+                SELECT id FROM mention_requests
+                WHERE status = 'queued' AND next_attempt <= NOW()
+                ORDER BY next_attempt
+                LIMIT $1
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING id::text as "id!", source, target, to_path, status, detail,
+                attempts, received, processed"#,
+		limit,
+		lease
+	)
+	.fetch_all(db)
+	.await
+}
+
+/// Record how verifying a request went, unless it was sent again meanwhile,
+/// in which case it goes back on the queue.
+pub async fn record_mention_request(
+	db: &Pool<Postgres>,
+	id: &str,
+	status: &str,
+	detail: Option<&str>,
+	next_attempt: DateTime<Utc>,
+) -> Result<(), sqlx::Error> {
+	query!(
+		"UPDATE mention_requests
+            SET status = CASE WHEN resend THEN 'queued' ELSE $2 END,
+                detail = CASE WHEN resend THEN NULL ELSE $3 END,
+                attempts = CASE WHEN resend THEN 0 ELSE attempts + 1 END,
+                next_attempt = CASE WHEN resend THEN NOW() ELSE $4 END,
+                processed = CASE WHEN resend OR $2 = 'queued' THEN NULL ELSE NOW() END,
+                leased_until = NULL,
+                resend = false
+            WHERE id = $1::text::uuid",
+		id,
+		status,
+		detail,
+		next_attempt
+	)
+	.execute(db)
+	.await
+	.map(|_| ())
+}
+
+pub async fn mention_request(
+	db: &Pool<Postgres>,
+	id: &str,
+) -> Result<Option<MentionRequest>, sqlx::Error> {
+	if !is_uuid(id) {
+		return Ok(None);
+	}
+	query_as!(
+		MentionRequest,
+		r#"SELECT id::text as "id!", source, target, to_path, status, detail,
+            attempts, received, processed
+            FROM mention_requests WHERE id = $1::text::uuid"#,
+		id
+	)
+	.fetch_optional(db)
+	.await
+}
+
 #[derive(Debug, Serialize)]
 pub struct Mention {
 	pub from_url: String,
@@ -493,82 +984,533 @@ pub struct Mention {
 pub async fn mentioners(db: &Pool<Postgres>, path: &str) -> Result<Vec<Mention>, sqlx::Error> {
 	query_as!(
 		Mention,
-		"SELECT from_url, last_mentioned, first_mentioned FROM incoming_mentions WHERE to_path = $1",
+		"SELECT from_url, last_mentioned, first_mentioned FROM incoming_mentions
+            WHERE to_path = $1 AND status = 'approved'",
 		path
 	)
 	.fetch_all(db)
 	.await
 }
 
-pub async fn tags(db: &Pool<Postgres>) -> Result<BTreeSet<String>, sqlx::Error> {
-	let results =
-		query!(r#"SELECT (meta->'tags') as "tags: Json<Vec<String>>" FROM visible_posts"#)
-			.fetch_all(db)
-			.await?;
-	Ok(results
-		.into_iter()
-		.filter_map(|r| r.tags)
-		.flat_map(|t| t.0.into_iter())
-		.collect())
+#[derive(Debug, Serialize)]
+pub struct ModeratedMention {
+	pub to_path: String,
+	pub from_url: String,
+	pub status: String,
+	pub last_mentioned: DateTime<Utc>,
+	pub first_mentioned: DateTime<Utc>,
 }
 
-pub async fn tag_counts(db: &Pool<Postgres>) -> Result<HashMap<String, usize>, sqlx::Error> {
-	let results =
-		query!(r#"SELECT (meta->'tags') as "tags: Json<Vec<String>>" FROM visible_posts"#)
-			.fetch_all(db)
-			.await?;
-	Ok(results
-		.into_iter()
-		.filter_map(|r| r.tags)
-		.flat_map(|t| t.0.into_iter())
-		.fold(HashMap::new(), |mut acc, tag| {
-			*acc.entry(tag).or_insert(0) += 1;
-			acc
-		}))
+/// Every mention waiting on a moderator, followed by the `recent` most
+/// recently seen mentions which have already been dealt with.
+pub async fn moderation_queue(
+	db: &Pool<Postgres>,
+	recent: i64,
+) -> Result<Vec<ModeratedMention>, sqlx::Error> {
+	query_as!(
+		ModeratedMention,
+		r#"(SELECT to_path as "to_path!", from_url as "from_url!", status as "status!",
+                last_mentioned as "last_mentioned!", first_mentioned as "first_mentioned!"
+            FROM incoming_mentions WHERE status = 'pending'
+            ORDER BY first_mentioned)
+        UNION ALL
+        (SELECT to_path, from_url, status, last_mentioned, first_mentioned
+            FROM incoming_mentions WHERE status <> 'pending'
+            ORDER BY last_mentioned DESC
+            LIMIT $1)"#,
+		recent
+	)
+	.fetch_all(db)
+	.await
 }
 
-pub type Bounds<B> = (Bound<B>, Bound<B>);
+pub async fn set_mention_status(
+	db: &Pool<Postgres>,
+	path: &str,
+	from_url: &str,
+	status: &str,
+) -> Result<(), sqlx::Error> {
+	query!(
+		"UPDATE incoming_mentions SET status = $3 WHERE to_path = $1 AND from_url = $2",
+		path,
+		from_url,
+		status
+	)
+	.execute(db)
+	.await
+	.map(|_| ())
+}
 
-fn unbounded<B>() -> Bounds<B> {
-	(Bound::Unbounded, Bound::Unbounded)
+/// Every mention which hasn't been rejected, for re-checking against bans.
+pub async fn unrejected_mentions(
+	db: &Pool<Postgres>,
+) -> Result<Vec<(String, String)>, sqlx::Error> {
+	query!("SELECT to_path, from_url FROM incoming_mentions WHERE status <> 'rejected'")
+		.fetch_all(db)
+		.await
+		.map(|rows| rows.into_iter().map(|r| (r.to_path, r.from_url)).collect())
 }
 
-fn bound_value<B>(bound: Bound<B>) -> Option<B> {
-	match bound {
-		Bound::Included(b) | Bound::Excluded(b) => Some(b),
-		Bound::Unbounded => None,
-	}
+#[derive(Debug, Serialize)]
+pub struct WebmentionBan {
+	pub pattern: String,
+	pub reason: Option<String>,
+	pub created: DateTime<Utc>,
 }
 
-#[derive(
-	Serialize, Deserialize, Default, Clone, Copy, Debug, EnumString, strum::Display, FromFormField,
-)]
-pub enum SortType {
-	CreateAsc,
-	#[default]
-	CreateDesc,
-	UpdateAsc,
+pub async fn webmention_bans(db: &Pool<Postgres>) -> Result<Vec<WebmentionBan>, sqlx::Error> {
+	query_as!(
+		WebmentionBan,
+		"SELECT pattern, reason, created FROM webmention_bans ORDER BY pattern"
+	)
+	.fetch_all(db)
+	.await
+}
+
+pub async fn add_webmention_ban(
+	db: &Pool<Postgres>,
+	pattern: &str,
+	reason: Option<&str>,
+) -> Result<(), sqlx::Error> {
+	query!(
+		"INSERT INTO webmention_bans (pattern, reason) VALUES ($1, $2)
+            ON CONFLICT (pattern) DO UPDATE SET reason = EXCLUDED.reason",
+		pattern,
+		reason
+	)
+	.execute(db)
+	.await
+	.map(|_| ())
+}
+
+pub async fn remove_webmention_ban(db: &Pool<Postgres>, pattern: &str) -> Result<(), sqlx::Error> {
+	query!("DELETE FROM webmention_bans WHERE pattern = $1", pattern)
+		.execute(db)
+		.await
+		.map(|_| ())
+}
+
+#[derive(Serialize)]
+pub struct GuestBan {
+	pub id: String,
+	pub provider: Option<String>,
+	pub sub: Option<String>,
+	pub email_domain: Option<String>,
+	pub reason: Option<String>,
+	pub created: DateTime<Utc>,
+	pub expires: Option<DateTime<Utc>>,
+}
+
+/// The ban stopping someone from signing guestbooks, if there is one.
+pub async fn guest_ban(
+	db: &Pool<Postgres>,
+	provider: &str,
+	sub: &str,
+	email: Option<&str>,
+) -> Result<Option<GuestBan>, sqlx::Error> {
+	let email_domain = email
+		.and_then(|e| e.rsplit_once('@'))
+		.map(|(_, domain)| domain.to_lowercase());
+	query_as!(
+		GuestBan,
+		r#"SELECT id::text AS "id!", provider, sub, email_domain, reason, created, expires
+        FROM guest_bans
+        WHERE (expires IS NULL OR expires > now())
+        AND ((provider = $1 AND sub = $2) OR email_domain = $3)
+        ORDER BY expires DESC NULLS FIRST
+        LIMIT 1"#,
+		provider,
+		sub,
+		email_domain
+	)
+	.fetch_optional(db)
+	.await
+}
+
+/// Every ban, including ones that have run out.
+pub async fn guest_bans(db: &Pool<Postgres>) -> Result<Vec<GuestBan>, sqlx::Error> {
+	query_as!(
+		GuestBan,
+		r#"SELECT id::text AS "id!", provider, sub, email_domain, reason, created, expires
+        FROM guest_bans
+        ORDER BY created DESC"#
+	)
+	.fetch_all(db)
+	.await
+}
+
+/// What a ban applies to.
+#[derive(Clone, Copy)]
+pub enum GuestBanTarget<'a> {
+	Identity { provider: &'a str, sub: &'a str },
+	EmailDomain(&'a str),
+}
+
+pub async fn add_guest_ban(
+	db: &Pool<Postgres>,
+	target: GuestBanTarget<'_>,
+	reason: Option<&str>,
+	expires: Option<DateTime<Utc>>,
+) -> Result<(), sqlx::Error> {
+	let (provider, sub, email_domain) = match target {
+		GuestBanTarget::Identity { provider, sub } => (Some(provider), Some(sub), None),
+		GuestBanTarget::EmailDomain(domain) => (None, None, Some(domain.to_lowercase())),
+	};
+	query!(
+		"INSERT INTO guest_bans (provider, sub, email_domain, reason, expires)
+            VALUES ($1, $2, $3, $4, $5)",
+		provider,
+		sub,
+		email_domain,
+		reason,
+		expires
+	)
+	.execute(db)
+	.await
+	.map(|_| ())
+}
+
+pub async fn remove_guest_ban(db: &Pool<Postgres>, id: &str) -> Result<(), sqlx::Error> {
+	if !is_uuid(id) {
+		return Ok(());
+	}
+	query!("DELETE FROM guest_bans WHERE id = $1::text::uuid", id)
+		.execute(db)
+		.await
+		.map(|_| ())
+}
+
+/// Remove every signature a ban covers.
+pub async fn remove_banned_signatures(
+	db: &Pool<Postgres>,
+	target: GuestBanTarget<'_>,
+) -> Result<u64, sqlx::Error> {
+	let result = match target {
+		GuestBanTarget::Identity { provider, sub } => {
+			query!(
+				"DELETE FROM guestbook WHERE provider = $1 AND guest = $2",
+				provider,
+				sub
+			)
+			.execute(db)
+			.await?
+		}
+		GuestBanTarget::EmailDomain(domain) => {
+			query!(
+				"DELETE FROM guestbook USING guests
+                WHERE guestbook.provider = guests.provider AND guestbook.guest = guests.sub
+                AND lower(substring(guests.email from '@([^@]*)$')) = lower($1)",
+				domain
+			)
+			.execute(db)
+			.await?
+		}
+	};
+	Ok(result.rows_affected())
+}
+
+/// Posts that are in the database but not listed anywhere, either because
+/// they're hidden or because they're drafts.
+pub async fn unlisted_posts(
+	db: &Pool<Postgres>,
+) -> Result<Vec<(String, ArticleMeta)>, sqlx::Error> {
+	let result = query!(
+		r#"SELECT path, meta as "meta: sqlx::types::Json<ArticleMeta>" FROM posts
+        WHERE meta->>'hidden' = 'true' OR meta->>'ready' = 'false'
+        ORDER BY path"#
+	)
+	.fetch_all(db)
+	.await?;
+	Ok(result.into_iter().map(|v| (v.path, v.meta.0)).collect())
+}
+
+pub async fn post_count(db: &Pool<Postgres>) -> Result<i64, sqlx::Error> {
+	query!(r#"SELECT COUNT(*) AS "count!" FROM posts"#)
+		.fetch_one(db)
+		.await
+		.map(|r| r.count)
+}
+
+/// How many webmentions are in each state, for incoming mentions, requests
+/// waiting to be verified and mentions we're sending.
+pub async fn webmention_stats(
+	db: &Pool<Postgres>,
+) -> Result<HashMap<String, HashMap<String, i64>>, sqlx::Error> {
+	let rows = query!(
+		r#"SELECT 'incoming' AS "kind!", status AS "status!", COUNT(*) AS "count!"
+            FROM incoming_mentions GROUP BY status
+        UNION ALL
+        SELECT 'requests', status, COUNT(*) FROM mention_requests GROUP BY status
+        UNION ALL
+        SELECT 'outgoing', status, COUNT(*) FROM outgoing_mentions GROUP BY status"#
+	)
+	.fetch_all(db)
+	.await?;
+	let mut stats: HashMap<String, HashMap<String, i64>> = HashMap::new();
+	for row in rows {
+		stats
+			.entry(row.kind)
+			.or_default()
+			.insert(row.status, row.count);
+	}
+	Ok(stats)
+}
+
+pub async fn pending_message_count(db: &Pool<Postgres>) -> Result<i64, sqlx::Error> {
+	query!(
+		r#"SELECT COUNT(*) AS "count!" FROM guestbook
+        WHERE message IS NOT NULL AND message_status = 'pending'"#
+	)
+	.fetch_one(db)
+	.await
+	.map(|r| r.count)
+}
+
+/// Make every post look older than its file, so the next update reads
+/// them all again.
+pub async fn mark_all_stale(db: &Pool<Postgres>) -> Result<(), sqlx::Error> {
+	query!("UPDATE posts SET updated = 'epoch'")
+		.execute(db)
+		.await
+		.map(|_| ())
+}
+
+pub async fn tags(db: &Pool<Postgres>) -> Result<BTreeSet<String>, sqlx::Error> {
+	let results =
+		query!(r#"SELECT (meta->'tags') as "tags: Json<Vec<String>>" FROM visible_posts"#)
+			.fetch_all(db)
+			.await?;
+	Ok(results
+		.into_iter()
+		.filter_map(|r| r.tags)
+		.flat_map(|t| t.0.into_iter())
+		.collect())
+}
+
+pub async fn tag_counts(db: &Pool<Postgres>) -> Result<HashMap<String, usize>, sqlx::Error> {
+	let results =
+		query!(r#"SELECT (meta->'tags') as "tags: Json<Vec<String>>" FROM visible_posts"#)
+			.fetch_all(db)
+			.await?;
+	Ok(results
+		.into_iter()
+		.filter_map(|r| r.tags)
+		.flat_map(|t| t.0.into_iter())
+		.fold(HashMap::new(), |mut acc, tag| {
+			*acc.entry(tag).or_insert(0) += 1;
+			acc
+		}))
+}
+
+/// One of the versions of a post in a translation group.
+#[derive(Serialize)]
+pub struct Translation {
+	pub path: String,
+	pub lang: String,
+	pub title: String,
+}
+
+/// Every published version of the post `original`, itself included, by
+/// language.
+pub async fn translations(
+	cfg: &Config,
+	db: &Pool<Postgres>,
+	original: &str,
+) -> Result<Vec<Translation>, sqlx::Error> {
+	query_as!(
+		Translation,
+		r#"SELECT path AS "path!",
+			COALESCE(meta->>'lang', $2) AS "lang!",
+			meta->>'title' AS "title!"
+		FROM posts
+		WHERE (path = $1 OR meta->>'translation_of' = $1)
+		AND (meta->'ready')::boolean
+		ORDER BY 2, path"#,
+		original,
+		cfg.default_lang
+	)
+	.fetch_all(db)
+	.await
+}
+
+/// The languages visible posts are written in.
+pub async fn languages(cfg: &Config, db: &Pool<Postgres>) -> Result<Vec<String>, sqlx::Error> {
+	let results = query!(
+		r#"SELECT DISTINCT COALESCE(meta->>'lang', $1) AS "lang!" FROM visible_posts
+		ORDER BY 1"#,
+		cfg.default_lang
+	)
+	.fetch_all(db)
+	.await?;
+	Ok(results.into_iter().map(|r| r.lang).collect())
+}
+
+/// Number the published parts of a series, by their `series_order` and then
+/// by date.
+pub async fn refresh_series(db: &Pool<Postgres>, series: &str) -> Result<(), sqlx::Error> {
+	query!(
+		r#"WITH parts AS (
+			SELECT path, (ROW_NUMBER() OVER (
+				ORDER BY (meta->>'series_order')::bigint NULLS LAST, meta->>'created', path
+			))::integer AS position
+			FROM posts
+			WHERE meta->>'series' = $1 AND (meta->'ready')::boolean
+		), gone AS (
+			DELETE FROM series_parts
+			WHERE series = $1 AND path NOT IN (SELECT path FROM parts)
+		)
+		INSERT INTO series_parts (path, series, position)
+		SELECT path, $1, position FROM parts
+		ON CONFLICT (path) DO UPDATE
+			SET series = excluded.series, position = excluded.position"#,
+		series
+	)
+	.execute(db)
+	.await?;
+	Ok(())
+}
+
+/// Where a post sits in its series.
+#[derive(Serialize)]
+pub struct SeriesPart {
+	pub name: String,
+	pub url: String,
+	pub position: i32,
+	pub total: i64,
+	#[serde(skip)]
+	prev: Option<Link>,
+	#[serde(skip)]
+	next: Option<Link>,
+}
+
+impl SeriesPart {
+	/// Fill in whichever of a post's prev, next and up links it doesn't
+	/// give itself.
+	pub fn navigate(&self, meta: &mut ArticleMeta) {
+		meta.prev = meta.prev.take().or_else(|| self.prev.clone());
+		meta.next = meta.next.take().or_else(|| self.next.clone());
+		meta.up = meta.up.take().or_else(|| {
+			Some(Link {
+				link: self.url.clone(),
+				title: self.name.clone(),
+			})
+		});
+	}
+}
+
+/// Where a series' page lives, from the root of the site.
+pub fn series_url(cfg: &Config, series: &str) -> String {
+	let mut url = cfg.origin.join("series").unwrap();
+	url.path_segments_mut().unwrap().push(series);
+	url.path().to_string()
+}
+
+pub async fn series_part(
+	cfg: &Config,
+	db: &Pool<Postgres>,
+	path: &str,
+) -> Result<Option<SeriesPart>, sqlx::Error> {
+	let part = query!(
+		r#"SELECT part.series, part.position,
+			(SELECT COUNT(*) FROM series_parts t WHERE t.series = part.series) AS "total!",
+			prev.path AS "prev_path?", prev.meta->>'title' AS prev_title,
+			next.path AS "next_path?", next.meta->>'title' AS next_title
+		FROM series_parts part
+		LEFT JOIN series_parts before
+			ON before.series = part.series AND before.position = part.position - 1
+		LEFT JOIN posts prev ON prev.path = before.path
+		LEFT JOIN series_parts after
+			ON after.series = part.series AND after.position = part.position + 1
+		LEFT JOIN posts next ON next.path = after.path
+		WHERE part.path = $1"#,
+		path
+	)
+	.fetch_optional(db)
+	.await?;
+	Ok(part.map(|p| SeriesPart {
+		url: series_url(cfg, &p.series),
+		prev: p.prev_path.map(|link| Link {
+			link,
+			title: p.prev_title.unwrap_or_default(),
+		}),
+		next: p.next_path.map(|link| Link {
+			link,
+			title: p.next_title.unwrap_or_default(),
+		}),
+		name: p.series,
+		position: p.position,
+		total: p.total,
+	}))
+}
+
+/// Every part of a series, in order.
+pub async fn series_posts(
+	db: &Pool<Postgres>,
+	series: &str,
+) -> Result<Vec<(String, ArticleMeta)>, sqlx::Error> {
+	let result = query!(
+		r#"SELECT posts.path AS "path!", posts.meta AS "meta!"
+		FROM series_parts JOIN posts USING (path)
+		WHERE series = $1
+		ORDER BY position"#,
+		series
+	)
+	.fetch_all(db)
+	.await?;
+	Ok(result
+		.into_iter()
+		.filter_map(|r| Some((r.path, serde_json::from_value::<ArticleMeta>(r.meta).ok()?)))
+		.collect())
+}
+
+pub type Bounds<B> = (Bound<B>, Bound<B>);
+
+fn unbounded<B>() -> Bounds<B> {
+	(Bound::Unbounded, Bound::Unbounded)
+}
+
+fn bound_value<B>(bound: Bound<B>) -> Option<B> {
+	match bound {
+		Bound::Included(b) | Bound::Excluded(b) => Some(b),
+		Bound::Unbounded => None,
+	}
+}
+
+#[derive(
+	Serialize, Deserialize, Default, Clone, Copy, Debug, EnumString, strum::Display, FromFormField,
+)]
+pub enum SortType {
+	CreateAsc,
+	#[default]
+	CreateDesc,
+	UpdateAsc,
 	UpdateDesc,
 	NameAsc,
 	NameDesc,
+	/// Most viewed over the last month first
+	Popular,
 }
 
 pub type Sorter = dyn Fn(&(String, ArticleMeta), &(String, ArticleMeta)) -> std::cmp::Ordering;
 
 impl SortType {
-	pub fn sort_fn(&self) -> &Sorter {
+	/// How to sort posts that are already loaded. Popularity depends on view
+	/// counts that only the database has, so it can only be sorted by [`search`].
+	pub fn sort_fn(&self) -> Option<&Sorter> {
 		match self {
-			SortType::CreateAsc => &|(_, l), (_, r)| l.created.cmp(&r.created),
-			SortType::CreateDesc => &|(_, l), (_, r)| r.created.cmp(&l.created),
-			SortType::UpdateAsc => &|(_, l), (_, r)| l.updated.cmp(&r.updated),
-			SortType::UpdateDesc => &|(_, l), (_, r)| r.updated.cmp(&l.updated),
-			SortType::NameAsc => &|(_, l), (_, r)| l.title.cmp(&r.title),
-			SortType::NameDesc => &|(_, l), (_, r)| r.title.cmp(&l.title),
+			SortType::CreateAsc => Some(&|(_, l), (_, r)| l.created.cmp(&r.created)),
+			SortType::CreateDesc => Some(&|(_, l), (_, r)| r.created.cmp(&l.created)),
+			SortType::UpdateAsc => Some(&|(_, l), (_, r)| l.updated.cmp(&r.updated)),
+			SortType::UpdateDesc => Some(&|(_, l), (_, r)| r.updated.cmp(&l.updated)),
+			SortType::NameAsc => Some(&|(_, l), (_, r)| l.title.cmp(&r.title)),
+			SortType::NameDesc => Some(&|(_, l), (_, r)| r.title.cmp(&l.title)),
+			SortType::Popular => None,
 		}
 	}
 }
 
 #[derive(Serialize, Deserialize, Debug)]
+#[allow(clippy::struct_field_names)]
 pub struct Search {
 	#[serde(default)]
 	pub search_path: String,
@@ -592,10 +1534,33 @@ pub struct Search {
 	pub limit: Option<u16>,
 	#[serde(default)]
 	pub ignore_hidden: bool,
+	#[serde(default)]
+	pub lang: Option<String>,
 	#[serde(flatten)]
 	pub extra: Value,
 }
 
+impl Default for Search {
+	/// Every visible post, newest first.
+	fn default() -> Self {
+		Search {
+			search_path: String::new(),
+			exclude_paths: vec![],
+			tags: vec![],
+			negative_tags: vec![],
+			post_type: None,
+			created: unbounded(),
+			updated: unbounded(),
+			title_filter: None,
+			sort_type: SortType::default(),
+			limit: None,
+			ignore_hidden: false,
+			lang: None,
+			extra: Value::Object(serde_json::Map::default()),
+		}
+	}
+}
+
 impl<'a> From<&'a Search> for Url {
 	fn from(val: &'a Search) -> Self {
 		let params = [("sort_type", val.sort_type.to_string())]
@@ -632,6 +1597,7 @@ impl<'a> From<&'a Search> for Url {
 					.map(|t| ("title_filter", t.clone())),
 			)
 			.chain(val.post_type.as_ref().map(|t| ("post_type", t.to_string())))
+			.chain(val.lang.as_ref().map(|l| ("lang", l.clone())))
 			.chain(val.limit.map(|l| ("limit", l.to_string())));
 		Url::parse_with_params("http:///search", params).unwrap()
 	}
@@ -652,9 +1618,16 @@ pub async fn search(
 		AND (meta->'created')::text::date <= $9
 		AND NOT (meta->'tags' ?| $5)
 		AND ((NOT (meta->'hidden')::boolean) OR $10)
+		AND (meta->'ready')::boolean
 		AND ($11 = (meta->'post_type')::text OR $11 IS NULL)
 		AND NOT path ^@ ANY($12)
+		AND (meta->>'lang' = $15 OR $15 IS NULL)
 		ORDER BY
+			CASE WHEN $13 = 'popular' THEN (
+				SELECT COALESCE(SUM(views), 0) FROM page_views
+				WHERE page_views.kind = 'post' AND page_views.path = posts.path
+				AND page_views.day > CURRENT_DATE - 30
+			) end desc,
 			CASE WHEN $14 THEN (meta->$13) end asc,
 			CASE WHEN NOT $14 THEN (meta->$13) end desc
 		LIMIT $4"#,
@@ -674,11 +1647,43 @@ pub async fn search(
 			SortType::CreateAsc | SortType::CreateDesc => "created",
 			SortType::UpdateAsc | SortType::UpdateDesc => "updated",
 			SortType::NameAsc | SortType::NameDesc => "name",
+			SortType::Popular => "popular",
 		},
 		match search.sort_type {
 			SortType::CreateAsc | SortType::UpdateAsc | SortType::NameAsc => true,
-			SortType::CreateDesc | SortType::UpdateDesc | SortType::NameDesc => false,
-		}
+			SortType::CreateDesc
+			| SortType::UpdateDesc
+			| SortType::NameDesc
+			| SortType::Popular => false,
+		},
+		search.lang
+	)
+	.fetch_all(db)
+	.await?;
+	Ok(result
+		.into_iter()
+		.filter_map(|r| Some((r.path, serde_json::from_value::<ArticleMeta>(r.meta).ok()?)))
+		.collect())
+}
+
+/// Every visible post that links to `path`, newest first.
+pub async fn backlinks(
+	cfg: &Config,
+	db: &Pool<Postgres>,
+	path: &str,
+) -> Result<Vec<(String, ArticleMeta)>, sqlx::Error> {
+	let result = query!(
+		r#"SELECT path AS "path!", meta AS "meta!" FROM visible_posts
+		WHERE path <> $1 AND (meta->'ready')::boolean
+		AND jsonb_path_exists(
+			ast,
+			'$.** ? (@.t == "Link") ? (@.c[2][0] == $local || @.c[2][0] == $absolute)',
+			jsonb_build_object('local', $2::text, 'absolute', $3::text)
+		)
+		ORDER BY meta->>'created' DESC"#,
+		path,
+		format!("/post/{path}"),
+		format!("{}post/{path}", cfg.origin)
 	)
 	.fetch_all(db)
 	.await?;
@@ -703,7 +1708,8 @@ pub async fn match_wikilink(
 	name: &str,
 ) -> Result<Vec<(String, ArticleMeta)>, sqlx::Error> {
 	let result = query!(
-		r#"SELECT path, meta as "meta: sqlx::types::Json<ArticleMeta>" FROM posts WHERE CONCAT('/', path) LIKE (CONCAT('%/', $1::text))"#,
+		r#"SELECT path, meta as "meta: sqlx::types::Json<ArticleMeta>" FROM posts
+		WHERE CONCAT('/', path) LIKE (CONCAT('%/', $1::text)) AND (meta->'ready')::boolean"#,
 		name
 	)
 	.fetch_all(db)
@@ -715,9 +1721,12 @@ pub async fn match_wikilink(
 pub struct Signature {
 	pub provider: String,
 	pub sub: String,
-	pub email: String,
+	pub email: Option<String>,
 	pub name: String,
 	pub timestamp: DateTime<Local>,
+	/// Rendered message, only present once it's been approved
+	pub message: Option<String>,
+	pub edited: Option<DateTime<Local>>,
 }
 
 pub async fn read_guestbook(
@@ -725,44 +1734,39 @@ pub async fn read_guestbook(
 	path: &str,
 ) -> Result<Vec<Signature>, sqlx::Error> {
 	let result = query_as!(
-        Signature,
-        "SELECT guests.provider, guests.sub, guests.email, guests.name, guestbook.timestamp FROM guestbook INNER JOIN guests ON guestbook.guest = guests.sub WHERE post = $1 ORDER BY timestamp DESC",
-        path
-    )
-    .fetch_all(db)
-    .await?;
+		Signature,
+		r#"SELECT guests.provider, guests.sub, guests.email, guests.name, guestbook.timestamp,
+            CASE WHEN guestbook.message_status = 'approved' THEN guestbook.message_html END AS message,
+            guestbook.edited AS "edited: DateTime<Local>"
+        FROM guestbook
+        INNER JOIN guests ON guestbook.guest = guests.sub AND guestbook.provider = guests.provider
+        WHERE post = $1
+        ORDER BY timestamp DESC"#,
+		path
+	)
+	.fetch_all(db)
+	.await?;
 	Ok(result)
 }
 
-pub async fn add_identity(
-	db: &Pool<Postgres>,
-	provider: &str,
-	identity: Identity,
-) -> Result<(), sqlx::Error> {
-	query!(
-		"INSERT INTO guests VALUES ($1, $2, $3, $4)
-            ON CONFLICT (provider, sub)
-            DO UPDATE SET
-                email = EXCLUDED.email,
-                name = EXCLUDED.name;",
-		provider,
-		identity.sub,
-		identity.email,
-		identity.name
-	)
-	.execute(db)
-	.await
-	.map(|_| ())
+/// A guest's own message on a page, so they can see and edit it.
+#[derive(Serialize)]
+pub struct OwnMessage {
+	pub message: Option<String>,
+	pub message_status: String,
 }
 
-pub async fn get_identity(
+pub async fn own_message(
 	db: &Pool<Postgres>,
+	path: &str,
 	provider: &str,
 	sub: &str,
-) -> Result<Option<Identity>, sqlx::Error> {
+) -> Result<Option<OwnMessage>, sqlx::Error> {
 	query_as!(
-		Identity,
-		"SELECT sub, email, name FROM guests WHERE provider = $1 AND sub = $2",
+		OwnMessage,
+		"SELECT message, message_status FROM guestbook
+        WHERE post = $1 AND provider = $2 AND guest = $3",
+		path,
 		provider,
 		sub
 	)
@@ -770,51 +1774,179 @@ pub async fn get_identity(
 	.await
 }
 
-pub async fn delete_identity(
+/// Whether a guest has had a message or comment approved anywhere before.
+pub async fn is_returning_guest(
 	db: &Pool<Postgres>,
 	provider: &str,
 	sub: &str,
-) -> Result<(), sqlx::Error> {
+) -> Result<bool, sqlx::Error> {
 	query!(
-		"DELETE FROM guests WHERE provider = $1 AND sub = $2",
+		r#"SELECT EXISTS(
+            SELECT 1 FROM guestbook
+            WHERE provider = $1 AND guest = $2
+            AND message IS NOT NULL AND message_status = 'approved'
+        ) OR EXISTS(
+            SELECT 1 FROM comments
+            WHERE provider = $1 AND guest = $2 AND status = 'approved'
+        ) AS "returning!""#,
 		provider,
 		sub
 	)
-	.execute(db)
+	.fetch_one(db)
 	.await
-	.map(|_| ())
+	.map(|r| r.returning)
 }
 
-pub async fn has_signed(
-	db: &Pool<Postgres>,
+#[derive(Serialize)]
+pub struct RecentSignature {
+	pub post: String,
+	pub provider: String,
+	pub guest: String,
+	pub name: String,
+	pub email: Option<String>,
+	pub message_html: Option<String>,
+	pub message_status: String,
+	pub timestamp: DateTime<Local>,
+	pub edited: Option<DateTime<Local>>,
+}
+
+/// Signatures across every post for the moderation page, with messages
+/// waiting for a decision first.
+pub async fn recent_signatures(
+	db: &Pool<Postgres>,
+	limit: i64,
+) -> Result<Vec<RecentSignature>, sqlx::Error> {
+	query_as!(
+		RecentSignature,
+		r#"SELECT guestbook.post, guestbook.provider, guestbook.guest, guests.name, guests.email,
+            guestbook.message_html, guestbook.message_status, guestbook.timestamp,
+            guestbook.edited AS "edited: DateTime<Local>"
+        FROM guestbook
+        INNER JOIN guests ON guestbook.guest = guests.sub AND guestbook.provider = guests.provider
+        ORDER BY guestbook.message IS NOT NULL AND guestbook.message_status = 'pending' DESC,
+            COALESCE(guestbook.edited, guestbook.timestamp) DESC
+        LIMIT $1"#,
+		limit
+	)
+	.fetch_all(db)
+	.await
+}
+
+pub async fn set_message_status(
+	db: &Pool<Postgres>,
+	post: &str,
+	provider: &str,
+	guest: &str,
+	status: &str,
+) -> Result<(), sqlx::Error> {
+	query!(
+		"UPDATE guestbook SET message_status = $4
+        WHERE post = $1 AND provider = $2 AND guest = $3",
+		post,
+		provider,
+		guest,
+		status
+	)
+	.execute(db)
+	.await
+	.map(|_| ())
+}
+
+pub async fn add_identity(
+	db: &Pool<Postgres>,
+	provider: &str,
+	identity: Identity,
+) -> Result<(), sqlx::Error> {
+	query!(
+		"INSERT INTO guests VALUES ($1, $2, $3, $4)
+            ON CONFLICT (provider, sub)
+            DO UPDATE SET
+                email = EXCLUDED.email,
+                name = EXCLUDED.name;",
+		provider,
+		identity.sub,
+		identity.email,
+		identity.name
+	)
+	.execute(db)
+	.await
+	.map(|_| ())
+}
+
+pub async fn get_identity(
+	db: &Pool<Postgres>,
 	provider: &str,
 	sub: &str,
-) -> Result<bool, sqlx::Error> {
+) -> Result<Option<Identity>, sqlx::Error> {
+	query_as!(
+		Identity,
+		"SELECT sub, email, name FROM guests WHERE provider = $1 AND sub = $2",
+		provider,
+		sub
+	)
+	.fetch_optional(db)
+	.await
+}
+
+pub async fn delete_identity(
+	db: &Pool<Postgres>,
+	provider: &str,
+	sub: &str,
+) -> Result<(), sqlx::Error> {
 	query!(
-		"SELECT COUNT(*) as c FROM guestbook WHERE provider = $1 AND guest = $2",
+		"DELETE FROM guests WHERE provider = $1 AND sub = $2",
 		provider,
 		sub
 	)
-	.fetch_one(db)
+	.execute(db)
 	.await
-	.map(|n| n.c.unwrap_or(0) > 0)
+	.map(|_| ())
+}
+
+/// A message to sign a guestbook with, as written and as rendered.
+pub struct GuestbookEntry<'a> {
+	pub message: &'a str,
+	pub message_html: &'a str,
+	pub status: &'a str,
 }
 
+/// Sign a guestbook, or update a signature. A message that hasn't changed
+/// keeps whatever moderation it already had.
 pub async fn sign_guestbook(
 	db: &Pool<Postgres>,
 	path: &str,
 	provider: &str,
 	sub: &str,
+	entry: Option<GuestbookEntry<'_>>,
 ) -> Result<(), sqlx::Error> {
+	let (message, message_html, status) = entry.map_or((None, None, "approved"), |e| {
+		(Some(e.message), Some(e.message_html), e.status)
+	});
 	query!(
-		"INSERT INTO guestbook VALUES ($1, $2, $3, $4)
+		"INSERT INTO guestbook (provider, guest, timestamp, post, message, message_html, message_status)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
             ON CONFLICT (provider, guest, post)
             DO UPDATE SET
-                timestamp = EXCLUDED.timestamp",
+                timestamp = EXCLUDED.timestamp,
+                message = EXCLUDED.message,
+                message_html = EXCLUDED.message_html,
+                message_status = CASE
+                    WHEN guestbook.message IS NOT DISTINCT FROM EXCLUDED.message
+                    THEN guestbook.message_status
+                    ELSE EXCLUDED.message_status
+                END,
+                edited = CASE
+                    WHEN guestbook.message IS NOT DISTINCT FROM EXCLUDED.message
+                    THEN guestbook.edited
+                    ELSE EXCLUDED.timestamp
+                END",
 		provider,
 		sub,
 		Utc::now(),
-		path
+		path,
+		message,
+		message_html,
+		status
 	)
 	.execute(db)
 	.await
@@ -840,3 +1972,1063 @@ pub async fn unsign_guestbook(
 	.await
 	.map(|_| ())
 }
+
+/// How many of each reaction the posts at `paths` have, by post.
+pub async fn reaction_counts(
+	db: &Pool<Postgres>,
+	paths: &[String],
+) -> Result<HashMap<String, HashMap<String, i64>>, sqlx::Error> {
+	let result = query!(
+		r#"SELECT post, emoji, COUNT(*) AS "count!" FROM reactions
+        WHERE post = ANY($1) GROUP BY post, emoji"#,
+		paths
+	)
+	.fetch_all(db)
+	.await?;
+	let mut counts: HashMap<String, HashMap<String, i64>> = HashMap::new();
+	for row in result {
+		counts
+			.entry(row.post)
+			.or_default()
+			.insert(row.emoji, row.count);
+	}
+	Ok(counts)
+}
+
+/// The reactions one guest has left on a post.
+pub async fn own_reactions(
+	db: &Pool<Postgres>,
+	path: &str,
+	provider: &str,
+	sub: &str,
+) -> Result<Vec<String>, sqlx::Error> {
+	query!(
+		"SELECT emoji FROM reactions WHERE post = $1 AND provider = $2 AND guest = $3",
+		path,
+		provider,
+		sub
+	)
+	.fetch_all(db)
+	.await
+	.map(|r| r.into_iter().map(|r| r.emoji).collect())
+}
+
+/// Add a reaction, or take it back if the guest had already left it.
+/// Returns whether the reaction is there now.
+pub async fn toggle_reaction(
+	db: &Pool<Postgres>,
+	path: &str,
+	provider: &str,
+	sub: &str,
+	emoji: &str,
+) -> Result<bool, sqlx::Error> {
+	let removed = query!(
+		"DELETE FROM reactions WHERE post = $1 AND provider = $2 AND guest = $3 AND emoji = $4",
+		path,
+		provider,
+		sub,
+		emoji
+	)
+	.execute(db)
+	.await?;
+	if removed.rows_affected() > 0 {
+		return Ok(false);
+	}
+	query!(
+		"INSERT INTO reactions (post, provider, guest, emoji) VALUES ($1, $2, $3, $4)
+        ON CONFLICT DO NOTHING",
+		path,
+		provider,
+		sub,
+		emoji
+	)
+	.execute(db)
+	.await?;
+	Ok(true)
+}
+
+/// Drop every reaction left by whoever a ban covers.
+pub async fn remove_banned_reactions(
+	db: &Pool<Postgres>,
+	target: GuestBanTarget<'_>,
+) -> Result<u64, sqlx::Error> {
+	let result = match target {
+		GuestBanTarget::Identity { provider, sub } => {
+			query!(
+				"DELETE FROM reactions WHERE provider = $1 AND guest = $2",
+				provider,
+				sub
+			)
+			.execute(db)
+			.await?
+		}
+		GuestBanTarget::EmailDomain(domain) => {
+			query!(
+				"DELETE FROM reactions USING guests
+                WHERE reactions.provider = guests.provider AND reactions.guest = guests.sub
+                AND lower(substring(guests.email from '@([^@]*)$')) = lower($1)",
+				domain
+			)
+			.execute(db)
+			.await?
+		}
+	};
+	Ok(result.rows_affected())
+}
+
+#[derive(Serialize, Clone)]
+pub struct Comment {
+	pub id: i64,
+	pub post: String,
+	pub parent: Option<i64>,
+	pub provider: String,
+	pub guest: String,
+	pub name: String,
+	pub body: String,
+	pub body_html: String,
+	pub status: String,
+	pub created: DateTime<Local>,
+	pub edited: Option<DateTime<Local>>,
+	pub deleted: bool,
+}
+
+/// Every comment on a post, oldest first, whatever its moderation status.
+pub async fn comments(db: &Pool<Postgres>, path: &str) -> Result<Vec<Comment>, sqlx::Error> {
+	query_as!(
+		Comment,
+		r#"SELECT comments.id, comments.post, comments.parent, comments.provider,
+            comments.guest, guests.name, comments.body, comments.body_html, comments.status,
+            comments.created AS "created: DateTime<Local>",
+            comments.edited AS "edited: DateTime<Local>", comments.deleted
+        FROM comments
+        INNER JOIN guests ON comments.guest = guests.sub AND comments.provider = guests.provider
+        WHERE comments.post = $1
+        ORDER BY comments.created"#,
+		path
+	)
+	.fetch_all(db)
+	.await
+}
+
+pub async fn comment(db: &Pool<Postgres>, id: i64) -> Result<Option<Comment>, sqlx::Error> {
+	query_as!(
+		Comment,
+		r#"SELECT comments.id, comments.post, comments.parent, comments.provider,
+            comments.guest, guests.name, comments.body, comments.body_html, comments.status,
+            comments.created AS "created: DateTime<Local>",
+            comments.edited AS "edited: DateTime<Local>", comments.deleted
+        FROM comments
+        INNER JOIN guests ON comments.guest = guests.sub AND comments.provider = guests.provider
+        WHERE comments.id = $1"#,
+		id
+	)
+	.fetch_optional(db)
+	.await
+}
+
+/// Approved comments on a post, newest first, for its comment feed.
+pub async fn approved_comments(
+	db: &Pool<Postgres>,
+	path: &str,
+	limit: i64,
+) -> Result<Vec<Comment>, sqlx::Error> {
+	query_as!(
+		Comment,
+		r#"SELECT comments.id, comments.post, comments.parent, comments.provider,
+            comments.guest, guests.name, comments.body, comments.body_html, comments.status,
+            comments.created AS "created: DateTime<Local>",
+            comments.edited AS "edited: DateTime<Local>", comments.deleted
+        FROM comments
+        INNER JOIN guests ON comments.guest = guests.sub AND comments.provider = guests.provider
+        WHERE comments.post = $1 AND comments.status = 'approved' AND NOT comments.deleted
+        ORDER BY comments.created DESC
+        LIMIT $2"#,
+		path,
+		limit
+	)
+	.fetch_all(db)
+	.await
+}
+
+/// Comments across every post for the moderation page, with comments
+/// waiting for a decision first.
+pub async fn recent_comments(db: &Pool<Postgres>, limit: i64) -> Result<Vec<Comment>, sqlx::Error> {
+	query_as!(
+		Comment,
+		r#"SELECT comments.id, comments.post, comments.parent, comments.provider,
+            comments.guest, guests.name, comments.body, comments.body_html, comments.status,
+            comments.created AS "created: DateTime<Local>",
+            comments.edited AS "edited: DateTime<Local>", comments.deleted
+        FROM comments
+        INNER JOIN guests ON comments.guest = guests.sub AND comments.provider = guests.provider
+        WHERE NOT comments.deleted
+        ORDER BY comments.status = 'pending' DESC,
+            COALESCE(comments.edited, comments.created) DESC
+        LIMIT $1"#,
+		limit
+	)
+	.fetch_all(db)
+	.await
+}
+
+pub async fn comment_count(db: &Pool<Postgres>, path: &str) -> Result<i64, sqlx::Error> {
+	query!(
+		r#"SELECT COUNT(*) AS "count!" FROM comments
+        WHERE post = $1 AND status = 'approved' AND NOT deleted"#,
+		path
+	)
+	.fetch_one(db)
+	.await
+	.map(|r| r.count)
+}
+
+pub async fn pending_comment_count(db: &Pool<Postgres>) -> Result<i64, sqlx::Error> {
+	query!(
+		r#"SELECT COUNT(*) AS "count!" FROM comments
+        WHERE status = 'pending' AND NOT deleted"#
+	)
+	.fetch_one(db)
+	.await
+	.map(|r| r.count)
+}
+
+pub struct NewComment<'a> {
+	pub post: &'a str,
+	pub parent: Option<i64>,
+	pub provider: &'a str,
+	pub guest: &'a str,
+	pub body: &'a str,
+	pub body_html: &'a str,
+	pub status: &'a str,
+}
+
+pub async fn add_comment(db: &Pool<Postgres>, comment: NewComment<'_>) -> Result<i64, sqlx::Error> {
+	query!(
+		"INSERT INTO comments (post, parent, provider, guest, body, body_html, status)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING id",
+		comment.post,
+		comment.parent,
+		comment.provider,
+		comment.guest,
+		comment.body,
+		comment.body_html,
+		comment.status
+	)
+	.fetch_one(db)
+	.await
+	.map(|r| r.id)
+}
+
+/// Replace a comment's text. Like guestbook messages, an edit that changes
+/// nothing keeps whatever moderation the comment already had.
+pub async fn edit_comment(
+	db: &Pool<Postgres>,
+	id: i64,
+	body: &str,
+	body_html: &str,
+	status: &str,
+) -> Result<(), sqlx::Error> {
+	query!(
+		"UPDATE comments
+            SET status = CASE WHEN body = $2 THEN status ELSE $4 END,
+                edited = CASE WHEN body = $2 THEN edited ELSE NOW() END,
+                body = $2,
+                body_html = $3
+            WHERE id = $1 AND NOT deleted",
+		id,
+		body,
+		body_html,
+		status
+	)
+	.execute(db)
+	.await
+	.map(|_| ())
+}
+
+/// Delete a comment. One with replies is blanked instead, so the replies
+/// still have something to hang from.
+pub async fn delete_comment(db: &Pool<Postgres>, id: i64) -> Result<(), sqlx::Error> {
+	let deleted = query!(
+		"DELETE FROM comments
+            WHERE id = $1 AND NOT EXISTS (SELECT 1 FROM comments AS c WHERE c.parent = $1)",
+		id
+	)
+	.execute(db)
+	.await?;
+	if deleted.rows_affected() == 0 {
+		query!(
+			"UPDATE comments SET deleted = true, body = '', body_html = '' WHERE id = $1",
+			id
+		)
+		.execute(db)
+		.await?;
+	}
+	Ok(())
+}
+
+pub async fn set_comment_status(
+	db: &Pool<Postgres>,
+	id: i64,
+	status: &str,
+) -> Result<(), sqlx::Error> {
+	query!("UPDATE comments SET status = $2 WHERE id = $1", id, status)
+		.execute(db)
+		.await
+		.map(|_| ())
+}
+
+/// Hide every comment left by whoever a ban covers. Hiding rather than
+/// deleting keeps replies from other guests in their threads.
+pub async fn hide_banned_comments(
+	db: &Pool<Postgres>,
+	target: GuestBanTarget<'_>,
+) -> Result<u64, sqlx::Error> {
+	let result = match target {
+		GuestBanTarget::Identity { provider, sub } => {
+			query!(
+				"UPDATE comments SET status = 'hidden' WHERE provider = $1 AND guest = $2",
+				provider,
+				sub
+			)
+			.execute(db)
+			.await?
+		}
+		GuestBanTarget::EmailDomain(domain) => {
+			query!(
+				"UPDATE comments SET status = 'hidden' FROM guests
+                WHERE comments.provider = guests.provider AND comments.guest = guests.sub
+                AND lower(substring(guests.email from '@([^@]*)$')) = lower($1)",
+				domain
+			)
+			.execute(db)
+			.await?
+		}
+	};
+	Ok(result.rows_affected())
+}
+
+/// When a visitor last viewed each post.
+pub async fn visitor_views(
+	db: &Pool<Postgres>,
+	visitor: &str,
+) -> Result<HashMap<String, NaiveDate>, sqlx::Error> {
+	let result = query!(
+		"SELECT path, viewed FROM visitor_views WHERE visitor = $1",
+		visitor
+	)
+	.fetch_all(db)
+	.await?;
+	Ok(result.into_iter().map(|r| (r.path, r.viewed)).collect())
+}
+
+/// Note that a visitor has viewed some posts, creating the visitor if this
+/// is the first we've heard of them.
+pub async fn record_views(
+	db: &Pool<Postgres>,
+	visitor: &str,
+	views: &HashMap<String, NaiveDate>,
+) -> Result<(), sqlx::Error> {
+	let (paths, dates): (Vec<_>, Vec<_>) = views.iter().map(|(p, d)| (p.clone(), *d)).unzip();
+	ensure_visitor(db, visitor).await?;
+	query!(
+		"INSERT INTO visitor_views (visitor, path, viewed)
+        SELECT $1, * FROM UNNEST($2::text[], $3::date[])
+        ON CONFLICT (visitor, path) DO UPDATE SET viewed = GREATEST(visitor_views.viewed, excluded.viewed)",
+		visitor,
+		&paths,
+		&dates
+	)
+	.execute(db)
+	.await
+	.map(|_| ())
+}
+
+pub async fn ensure_visitor(db: &Pool<Postgres>, visitor: &str) -> Result<(), sqlx::Error> {
+	query!(
+		"INSERT INTO visitors (id) VALUES ($1)
+        ON CONFLICT (id) DO UPDATE SET last_seen = NOW()",
+		visitor
+	)
+	.execute(db)
+	.await
+	.map(|_| ())
+}
+
+/// How many posts with each tag a visitor hasn't read since they last changed.
+pub async fn unread_tag_counts(
+	db: &Pool<Postgres>,
+	visitor: &str,
+) -> Result<HashMap<String, i64>, sqlx::Error> {
+	let result = query!(
+		r#"SELECT tag AS "tag!", COUNT(*) AS "count!"
+        FROM visible_posts
+        CROSS JOIN jsonb_array_elements_text(visible_posts.meta->'tags') AS tag
+        LEFT JOIN visitor_views ON visitor_views.visitor = $1
+            AND visitor_views.path = visible_posts.path
+        WHERE visitor_views.viewed IS NULL
+            OR visitor_views.viewed < (visible_posts.meta->>'updated')::date
+        GROUP BY tag"#,
+		visitor
+	)
+	.fetch_all(db)
+	.await?;
+	Ok(result.into_iter().map(|r| (r.tag, r.count)).collect())
+}
+
+/// Posts a visitor hasn't read since they last changed, most recently
+/// updated first.
+pub async fn unread_posts(
+	db: &Pool<Postgres>,
+	visitor: &str,
+	limit: i64,
+) -> Result<Vec<(String, ArticleMeta)>, sqlx::Error> {
+	let result = query!(
+		r#"SELECT visible_posts.path AS "path!",
+            visible_posts.meta AS "meta!: sqlx::types::Json<ArticleMeta>"
+        FROM visible_posts
+        LEFT JOIN visitor_views ON visitor_views.visitor = $1
+            AND visitor_views.path = visible_posts.path
+        WHERE visitor_views.viewed IS NULL
+            OR visitor_views.viewed < (visible_posts.meta->>'updated')::date
+        ORDER BY visible_posts.meta->>'updated' DESC
+        LIMIT $2"#,
+		visitor,
+		limit
+	)
+	.fetch_all(db)
+	.await?;
+	Ok(result.into_iter().map(|r| (r.path, r.meta.0)).collect())
+}
+
+/// A visitor's reading list, most recently saved first. Posts that have
+/// since gone back to being drafts are left out.
+pub async fn bookmarks(
+	db: &Pool<Postgres>,
+	visitor: &str,
+) -> Result<Vec<(String, ArticleMeta)>, sqlx::Error> {
+	let result = query!(
+		r#"SELECT posts.path, posts.meta AS "meta: sqlx::types::Json<ArticleMeta>"
+        FROM visitor_bookmarks
+        INNER JOIN posts ON posts.path = visitor_bookmarks.path
+        WHERE visitor_bookmarks.visitor = $1 AND posts.meta->>'ready' = 'true'
+        ORDER BY visitor_bookmarks.added DESC"#,
+		visitor
+	)
+	.fetch_all(db)
+	.await?;
+	Ok(result.into_iter().map(|r| (r.path, r.meta.0)).collect())
+}
+
+pub async fn is_bookmarked(
+	db: &Pool<Postgres>,
+	visitor: &str,
+	path: &str,
+) -> Result<bool, sqlx::Error> {
+	query!(
+		r#"SELECT EXISTS (
+            SELECT 1 FROM visitor_bookmarks WHERE visitor = $1 AND path = $2
+        ) AS "saved!""#,
+		visitor,
+		path
+	)
+	.fetch_one(db)
+	.await
+	.map(|r| r.saved)
+}
+
+pub async fn set_bookmark(
+	db: &Pool<Postgres>,
+	visitor: &str,
+	path: &str,
+	saved: bool,
+) -> Result<(), sqlx::Error> {
+	if saved {
+		ensure_visitor(db, visitor).await?;
+		query!(
+			"INSERT INTO visitor_bookmarks (visitor, path) VALUES ($1, $2)
+            ON CONFLICT DO NOTHING",
+			visitor,
+			path
+		)
+		.execute(db)
+		.await?;
+	} else {
+		query!(
+			"DELETE FROM visitor_bookmarks WHERE visitor = $1 AND path = $2",
+			visitor,
+			path
+		)
+		.execute(db)
+		.await?;
+	}
+	Ok(())
+}
+
+pub async fn visitor_feed_token(
+	db: &Pool<Postgres>,
+	visitor: &str,
+) -> Result<Option<String>, sqlx::Error> {
+	query!("SELECT feed_token FROM visitors WHERE id = $1", visitor)
+		.fetch_optional(db)
+		.await
+		.map(|r| r.and_then(|r| r.feed_token))
+}
+
+/// Give a visitor a new unread feed token, so the old feed URL stops working.
+pub async fn set_visitor_feed_token(
+	db: &Pool<Postgres>,
+	visitor: &str,
+	token: &str,
+) -> Result<(), sqlx::Error> {
+	ensure_visitor(db, visitor).await?;
+	query!(
+		"UPDATE visitors SET feed_token = $2 WHERE id = $1",
+		visitor,
+		token
+	)
+	.execute(db)
+	.await
+	.map(|_| ())
+}
+
+/// Find whose unread feed a token belongs to. Reading the feed counts as
+/// a visit, so feed readers aren't forgotten.
+pub async fn visitor_by_feed_token(
+	db: &Pool<Postgres>,
+	token: &str,
+) -> Result<Option<String>, sqlx::Error> {
+	query!(
+		"UPDATE visitors SET last_seen = NOW() WHERE feed_token = $1 RETURNING id",
+		token
+	)
+	.fetch_optional(db)
+	.await
+	.map(|r| r.map(|r| r.id))
+}
+
+/// Forget visitors we haven't seen in `days`, and views and bookmarks of
+/// posts that no longer exist. Returns how many visitors were forgotten.
+pub async fn prune_visitors(db: &Pool<Postgres>, days: i32) -> Result<u64, sqlx::Error> {
+	let result = query!(
+		"DELETE FROM visitors WHERE last_seen < NOW() - make_interval(days => $1)",
+		days
+	)
+	.execute(db)
+	.await?;
+	query!("DELETE FROM visitor_views WHERE NOT EXISTS (SELECT 1 FROM posts WHERE posts.path = visitor_views.path)")
+		.execute(db)
+		.await?;
+	query!("DELETE FROM visitor_bookmarks WHERE NOT EXISTS (SELECT 1 FROM posts WHERE posts.path = visitor_bookmarks.path)")
+		.execute(db)
+		.await?;
+	Ok(result.rows_affected())
+}
+
+pub struct MicropubToken {
+	pub name: String,
+	pub scope: Vec<String>,
+}
+
+pub async fn add_micropub_token(
+	db: &Pool<Postgres>,
+	token: &str,
+	name: &str,
+	scope: &[String],
+) -> Result<(), sqlx::Error> {
+	query!(
+		"INSERT INTO micropub_tokens (token_hash, name, scope)
+            VALUES (encode(sha256(convert_to($1, 'UTF8')), 'hex'), $2, $3)",
+		token,
+		name,
+		scope
+	)
+	.execute(db)
+	.await
+	.map(|_| ())
+}
+
+/// Look up a micropub token, noting that it's been used.
+pub async fn use_micropub_token(
+	db: &Pool<Postgres>,
+	token: &str,
+) -> Result<Option<MicropubToken>, sqlx::Error> {
+	query_as!(
+		MicropubToken,
+		"UPDATE micropub_tokens SET last_used = NOW()
+            WHERE token_hash = encode(sha256(convert_to($1, 'UTF8')), 'hex')
+            RETURNING name, scope",
+		token
+	)
+	.fetch_optional(db)
+	.await
+}
+
+pub async fn revoke_micropub_tokens(db: &Pool<Postgres>, name: &str) -> Result<u64, sqlx::Error> {
+	query!("DELETE FROM micropub_tokens WHERE name = $1", name)
+		.execute(db)
+		.await
+		.map(|r| r.rows_affected())
+}
+
+pub async fn add_preview_link(
+	db: &Pool<Postgres>,
+	token: &str,
+	path: &str,
+	note: Option<&str>,
+	expires: DateTime<Utc>,
+) -> Result<(), sqlx::Error> {
+	query!(
+		"INSERT INTO preview_links (token_hash, path, note, expires)
+            VALUES (encode(sha256(convert_to($1, 'UTF8')), 'hex'), $2, $3, $4)",
+		token,
+		path,
+		note,
+		expires
+	)
+	.execute(db)
+	.await
+	.map(|_| ())
+}
+
+/// Whether `token` is an unexpired preview link for `path`.
+pub async fn preview_link_valid(
+	db: &Pool<Postgres>,
+	token: &str,
+	path: &str,
+) -> Result<bool, sqlx::Error> {
+	query!(
+		r#"SELECT EXISTS (
+            SELECT 1 FROM preview_links
+            WHERE token_hash = encode(sha256(convert_to($1, 'UTF8')), 'hex')
+            AND path = $2 AND expires > NOW()
+        ) AS "valid!""#,
+		token,
+		path
+	)
+	.fetch_one(db)
+	.await
+	.map(|r| r.valid)
+}
+
+#[derive(Serialize)]
+pub struct PreviewLink {
+	pub id: i32,
+	pub path: String,
+	pub note: Option<String>,
+	pub created: DateTime<Utc>,
+	pub expires: DateTime<Utc>,
+}
+
+/// Preview links that haven't expired yet, soonest to expire first.
+pub async fn preview_links(db: &Pool<Postgres>) -> Result<Vec<PreviewLink>, sqlx::Error> {
+	query_as!(
+		PreviewLink,
+		"SELECT id, path, note, created, expires FROM preview_links
+        WHERE expires > NOW() ORDER BY expires"
+	)
+	.fetch_all(db)
+	.await
+}
+
+pub async fn revoke_preview_link(db: &Pool<Postgres>, id: i32) -> Result<u64, sqlx::Error> {
+	query!("DELETE FROM preview_links WHERE id = $1", id)
+		.execute(db)
+		.await
+		.map(|r| r.rows_affected())
+}
+
+/// Revoke every preview link for a post, returning how many there were.
+pub async fn revoke_preview_links(db: &Pool<Postgres>, path: &str) -> Result<u64, sqlx::Error> {
+	query!("DELETE FROM preview_links WHERE path = $1", path)
+		.execute(db)
+		.await
+		.map(|r| r.rows_affected())
+}
+
+/// Count a view of `path` on `day`. `visitor_key` is hashed with the day's
+/// salt to tell whether this visitor has been counted already, and is never
+/// stored itself.
+pub async fn record_page_view(
+	db: &Pool<Postgres>,
+	kind: &str,
+	path: &str,
+	day: NaiveDate,
+	visitor_key: &str,
+	referrer: Option<&str>,
+) -> Result<(), sqlx::Error> {
+	query!(
+		"WITH salt AS (
+            INSERT INTO analytics_salts (day, salt) VALUES ($3, gen_random_uuid()::text)
+            ON CONFLICT (day) DO UPDATE SET day = EXCLUDED.day
+            RETURNING salt
+        ), seen AS (
+            INSERT INTO page_view_hashes (day, hash)
+            SELECT $3, encode(sha256(convert_to(salt.salt || $4, 'UTF8')), 'hex') FROM salt
+            ON CONFLICT DO NOTHING
+            RETURNING hash
+        )
+        INSERT INTO page_views (kind, path, day, views, visitors)
+        VALUES ($1, $2, $3, 1, (SELECT COUNT(*) FROM seen)::int)
+        ON CONFLICT (kind, path, day) DO UPDATE SET
+            views = page_views.views + 1,
+            visitors = page_views.visitors + EXCLUDED.visitors",
+		kind,
+		path,
+		day,
+		visitor_key
+	)
+	.execute(db)
+	.await?;
+	if let Some(domain) = referrer {
+		query!(
+			"INSERT INTO page_referrers (kind, path, day, domain, views)
+            VALUES ($1, $2, $3, $4, 1)
+            ON CONFLICT (kind, path, day, domain) DO UPDATE SET
+                views = page_referrers.views + 1",
+			kind,
+			path,
+			day,
+			domain
+		)
+		.execute(db)
+		.await?;
+	}
+	Ok(())
+}
+
+/// Throw away the salts, and with them the visitor hashes, of days before
+/// `today`.
+pub async fn prune_analytics_salts(
+	db: &Pool<Postgres>,
+	today: NaiveDate,
+) -> Result<(), sqlx::Error> {
+	query!("DELETE FROM analytics_salts WHERE day < $1", today)
+		.execute(db)
+		.await
+		.map(|_| ())
+}
+
+/// How many times each of `paths` has been viewed, ever.
+pub async fn post_views(
+	db: &Pool<Postgres>,
+	paths: &[String],
+) -> Result<HashMap<String, i64>, sqlx::Error> {
+	let result = query!(
+		r#"SELECT path, SUM(views) AS "views!" FROM page_views
+        WHERE kind = 'post' AND path = ANY($1)
+        GROUP BY path"#,
+		paths
+	)
+	.fetch_all(db)
+	.await?;
+	Ok(result.into_iter().map(|r| (r.path, r.views)).collect())
+}
+
+#[derive(Serialize)]
+pub struct DailyViews {
+	pub day: NaiveDate,
+	pub views: i64,
+	pub visitors: i64,
+}
+
+/// Views of each kind per day since `since`, most recent first.
+pub async fn daily_views(
+	db: &Pool<Postgres>,
+	kind: &str,
+	since: NaiveDate,
+) -> Result<Vec<DailyViews>, sqlx::Error> {
+	query_as!(
+		DailyViews,
+		r#"SELECT day, SUM(views) AS "views!", SUM(visitors) AS "visitors!"
+        FROM page_views WHERE kind = $1 AND day >= $2
+        GROUP BY day ORDER BY day DESC"#,
+		kind,
+		since
+	)
+	.fetch_all(db)
+	.await
+}
+
+#[derive(Serialize)]
+pub struct PathViews {
+	pub kind: String,
+	pub path: String,
+	pub views: i64,
+	pub visitors: i64,
+}
+
+/// The most viewed paths since `since`.
+pub async fn top_paths(
+	db: &Pool<Postgres>,
+	since: NaiveDate,
+	limit: i64,
+) -> Result<Vec<PathViews>, sqlx::Error> {
+	query_as!(
+		PathViews,
+		r#"SELECT kind, path, SUM(views) AS "views!", SUM(visitors) AS "visitors!"
+        FROM page_views WHERE day >= $1
+        GROUP BY kind, path ORDER BY 3 DESC LIMIT $2"#,
+		since,
+		limit
+	)
+	.fetch_all(db)
+	.await
+}
+
+/// The domains that sent the most views since `since`.
+pub async fn top_referrers(
+	db: &Pool<Postgres>,
+	since: NaiveDate,
+	limit: i64,
+) -> Result<Vec<(String, i64)>, sqlx::Error> {
+	let result = query!(
+		r#"SELECT domain, SUM(views) AS "views!" FROM page_referrers
+        WHERE day >= $1
+        GROUP BY domain ORDER BY 2 DESC LIMIT $2"#,
+		since,
+		limit
+	)
+	.fetch_all(db)
+	.await?;
+	Ok(result.into_iter().map(|r| (r.domain, r.views)).collect())
+}
+
+/// Whether a published post used to live at `path`.
+pub async fn post_removed(db: &Pool<Postgres>, path: &str) -> Result<bool, sqlx::Error> {
+	query!(
+		r#"SELECT EXISTS (
+            SELECT 1 FROM removed_posts WHERE path = $1
+        ) AND NOT EXISTS (
+            SELECT 1 FROM posts WHERE path = $1
+        ) AS "removed!""#,
+		path
+	)
+	.fetch_one(db)
+	.await
+	.map(|r| r.removed)
+}
+
+/// The trigrams of each word in some text, padded like `pg_trgm` pads them
+/// so the starts of words count for more.
+fn trigrams(text: &str) -> HashSet<[char; 3]> {
+	text.split(|c: char| !c.is_alphanumeric())
+		.filter(|word| !word.is_empty())
+		.flat_map(|word| {
+			let padded: Vec<char> = "  "
+				.chars()
+				.chain(word.to_lowercase().chars())
+				.chain([' '])
+				.collect();
+			padded
+				.windows(3)
+				.map(|w| [w[0], w[1], w[2]])
+				.collect::<Vec<_>>()
+		})
+		.collect()
+}
+
+/// How alike two pieces of text are, from 0 to 1.
+fn similarity(a: &HashSet<[char; 3]>, b: &HashSet<[char; 3]>) -> f64 {
+	ratio(a.intersection(b).count(), a.union(b).count())
+}
+
+/// How much of `wanted` turns up somewhere in `text`, from 0 to 1.
+fn coverage(wanted: &HashSet<[char; 3]>, text: &HashSet<[char; 3]>) -> f64 {
+	ratio(wanted.intersection(text).count(), wanted.len())
+}
+
+fn ratio(part: usize, whole: usize) -> f64 {
+	match (u32::try_from(part), u32::try_from(whole)) {
+		(Ok(part), Ok(whole)) if whole > 0 => f64::from(part) / f64::from(whole),
+		_ => 0.0,
+	}
+}
+
+/// Published posts whose path or title looks like `wanted`, best match
+/// first. Matched here rather than with `pg_trgm`, which only a superuser
+/// can install.
+pub async fn similar_posts(
+	db: &Pool<Postgres>,
+	wanted: &str,
+	limit: usize,
+) -> Result<Vec<(String, String)>, sqlx::Error> {
+	let posts = query!(
+		r#"SELECT path AS "path!", COALESCE(meta->>'title', '') AS "title!"
+        FROM visible_posts"#
+	)
+	.fetch_all(db)
+	.await?;
+	let wanted_path = trigrams(wanted);
+	let mut scored: Vec<_> = posts
+		.into_iter()
+		.filter_map(|post| {
+			let path = similarity(&trigrams(&post.path), &wanted_path);
+			let title = coverage(&wanted_path, &trigrams(&post.title));
+			(path > 0.2 || title > 0.3).then(|| (path.max(title), post.path, post.title))
+		})
+		.collect();
+	scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+	Ok(scored
+		.into_iter()
+		.take(limit)
+		.map(|(_, path, title)| (path, title))
+		.collect())
+}
+
+/// When a published post last changed, and its preview image if one has
+/// been made since.
+pub async fn og_image(
+	db: &Pool<Postgres>,
+	path: &str,
+) -> Result<Option<(DateTime<Utc>, Option<Vec<u8>>)>, sqlx::Error> {
+	let result = query!(
+		r#"SELECT posts.updated AS "updated: DateTime<Utc>", og_images.png AS "png?"
+		FROM posts
+		LEFT JOIN og_images ON og_images.path = posts.path
+			AND og_images.rendered_for = posts.updated
+		WHERE posts.path = $1 AND (posts.meta->'ready')::boolean"#,
+		path
+	)
+	.fetch_optional(db)
+	.await?;
+	Ok(result.map(|r| (r.updated, r.png)))
+}
+
+pub async fn save_og_image(
+	db: &Pool<Postgres>,
+	path: &str,
+	rendered_for: DateTime<Utc>,
+	png: &[u8],
+) -> Result<(), sqlx::Error> {
+	query!(
+		"INSERT INTO og_images (path, rendered_for, png) VALUES ($1, $2, $3)
+		ON CONFLICT (path) DO UPDATE SET rendered_for = excluded.rendered_for, png = excluded.png",
+		path,
+		rendered_for,
+		png
+	)
+	.execute(db)
+	.await?;
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json::json;
+
+	use super::*;
+
+	fn normalized(meta: Value) -> Value {
+		let Value::Object(mut meta) = meta else {
+			panic!("Metadata should be an object");
+		};
+		normalize_meta(&mut meta);
+		Value::Object(meta)
+	}
+
+	#[test]
+	fn plain_text_metadata_is_normalized() {
+		assert_eq!(
+			normalized(json!({
+				"filetags": ":rust:web:",
+				"ready": "yes",
+				"hidden": "nil",
+				"toc": "no",
+				"date": "<2025-01-02 Thu>",
+			})),
+			json!({
+				"tags": ["rust", "web"],
+				"ready": true,
+				"hidden": false,
+				"toc": [],
+				"date": "<2025-01-02 Thu>",
+				"created": "2025-01-02",
+				"updated": "2025-01-02",
+			})
+		);
+		assert_eq!(
+			normalized(json!({
+				"keywords": "a, b c",
+				"toc": "true",
+				"hidden": "maybe",
+				"created": "2025-01-02",
+				"updated": "[2025-03-04 Tue 10:00]",
+			})),
+			json!({
+				"tags": ["a", "b", "c"],
+				"hidden": "maybe",
+				"created": "2025-01-02",
+				"updated": "2025-03-04",
+			})
+		);
+	}
+
+	#[test]
+	fn markdown_metadata_is_left_alone() {
+		let meta = json!({
+			"tags": ["a"],
+			"keywords": ["b"],
+			"ready": true,
+			"toc": [{"Text": "Custom"}],
+			"created": "2025-01-02",
+			"updated": "2025-01-03",
+		});
+		assert_eq!(normalized(meta.clone()), meta);
+	}
+
+	#[test]
+	fn series_fill_in_links_posts_leave_out() {
+		let cfg = Config::for_tests();
+		let part = SeriesPart {
+			name: "Rust & friends".to_string(),
+			url: series_url(&cfg, "Rust & friends"),
+			position: 2,
+			total: 3,
+			prev: Some(Link {
+				link: "/one".to_string(),
+				title: "One".to_string(),
+			}),
+			next: Some(Link {
+				link: "/three".to_string(),
+				title: "Three".to_string(),
+			}),
+		};
+		assert_eq!(part.url, "/series/Rust%20&%20friends");
+		let mut meta: ArticleMeta = serde_json::from_value(json!({
+			"created": "2026-01-01",
+			"updated": "2026-01-01",
+			"next": {"link": "/elsewhere", "title": "Elsewhere"},
+		}))
+		.unwrap();
+		part.navigate(&mut meta);
+		assert_eq!(meta.prev.unwrap().link, "/one");
+		assert_eq!(meta.next.unwrap().link, "/elsewhere");
+		let up = meta.up.unwrap();
+		assert_eq!(
+			(up.link.as_str(), up.title.as_str()),
+			(part.url.as_str(), "Rust & friends")
+		);
+	}
+
+	#[test]
+	fn only_uuids_reach_the_database() {
+		assert!(is_uuid("0b6f2b9e-5d1c-4a7e-9f3a-2c8d1e4b7a60"));
+		assert!(!is_uuid("0b6f2b9e5d1c4a7e9f3a2c8d1e4b7a60"));
+		assert!(!is_uuid("0b6f2b9e-5d1c-4a7e-9f3a-2c8d1e4b7a6g"));
+		assert!(!is_uuid("'; DROP TABLE posts; --"));
+		assert!(!is_uuid(""));
+	}
+
+	#[test]
+	fn similar_text_is_matched_by_trigrams() {
+		let rust = trigrams("rust-async-intro");
+		assert!(similarity(&rust, &trigrams("rust-async-intro")) > 0.99);
+		assert!(similarity(&rust, &trigrams("rust-asnyc-intro")) > 0.2);
+		assert!(similarity(&rust, &trigrams("knitting-for-cats")) < 0.2);
+		let wanted = trigrams("async intro");
+		assert!(coverage(&wanted, &trigrams("An Intro to Async Rust")) > 0.99);
+		assert!(coverage(&wanted, &trigrams("Knitting for Cats")) < 0.3);
+		assert!(similarity(&trigrams(""), &trigrams("")) == 0.0);
+	}
+}
@@ -0,0 +1,185 @@
+use std::sync::Arc;
+
+use rocket::{
+	Request,
+	http::{ContentType, Status},
+	response::Responder,
+};
+use serde_json::json;
+use sqlx::{Pool, Postgres};
+use tera::Tera;
+use thiserror::Error;
+use tokio::sync::RwLock;
+
+use crate::{Config, cookies::Preferences, db, templates::RenderError};
+
+/// How many similar posts the 404 page suggests.
+const SUGGESTIONS: usize = 5;
+
+/// Why a request failed. Most of these are shown by the error page
+/// catchers rather than responding directly.
+#[derive(Error, Debug)]
+pub enum AppError {
+	#[error("Database error")]
+	Db(#[from] sqlx::Error),
+	#[error("Not found")]
+	NotFound,
+	#[error("This post has been removed")]
+	Gone,
+	#[error("{0}")]
+	Forbidden(String),
+	#[error("{0}")]
+	Internal(String),
+	#[error(transparent)]
+	Render(#[from] RenderError),
+}
+
+/// What went wrong, for the catcher to explain.
+struct ErrorMessage(Option<String>);
+
+impl<'r, 'o: 'r> Responder<'r, 'o> for AppError {
+	fn respond_to(self, request: &'r Request<'_>) -> rocket::response::Result<'o> {
+		let message = self.to_string();
+		let status = match self {
+			AppError::Render(e) => return e.respond_to(request),
+			AppError::Db(e) => {
+				eprintln!("Database error: {e}");
+				Status::InternalServerError
+			}
+			AppError::Internal(e) => {
+				eprintln!("Internal error: {e}");
+				Status::InternalServerError
+			}
+			AppError::NotFound => Status::NotFound,
+			AppError::Gone => Status::Gone,
+			AppError::Forbidden(_) => Status::Forbidden,
+		};
+		caught(request, status, message)
+	}
+}
+
+/// Leave an error for the catcher for `status` to show, with a message
+/// explaining what went wrong.
+pub fn caught<'o>(
+	request: &Request<'_>,
+	status: Status,
+	message: impl Into<String>,
+) -> rocket::response::Result<'o> {
+	request.local_cache(|| ErrorMessage(Some(message.into())));
+	Err(status)
+}
+
+/// Whether whoever's asking would rather have JSON than a web page.
+fn wants_json(request: &Request<'_>) -> bool {
+	request
+		.accept()
+		.is_some_and(|accept| accept.preferred().media_type().is_json())
+}
+
+fn message(request: &Request<'_>, status: Status) -> String {
+	request
+		.local_cache(|| ErrorMessage(None))
+		.0
+		.clone()
+		.unwrap_or_else(|| status.reason_lossy().to_string())
+}
+
+/// Render an error page, or plain text if even that fails.
+async fn page(
+	request: &Request<'_>,
+	status: Status,
+	template: &str,
+	mut context: tera::Context,
+) -> (Status, (ContentType, String)) {
+	let tera = request.rocket().state::<Arc<RwLock<Tera>>>().unwrap();
+	let prefs = request.guard::<Preferences>().await.succeeded();
+	context.insert("prefs", &prefs.unwrap_or_default());
+	match tera.read().await.render(template, &context) {
+		Ok(html) => (status, (ContentType::HTML, html)),
+		Err(e) => {
+			eprintln!("Couldn't render {template}: {e}");
+			(status, (ContentType::Plain, message(request, status)))
+		}
+	}
+}
+
+#[catch(404)]
+pub async fn not_found(request: &Request<'_>) -> (Status, (ContentType, String)) {
+	caught_not_found(Status::NotFound, request).await
+}
+
+#[catch(410)]
+pub async fn gone(request: &Request<'_>) -> (Status, (ContentType, String)) {
+	caught_not_found(Status::Gone, request).await
+}
+
+async fn caught_not_found(
+	status: Status,
+	request: &Request<'_>,
+) -> (Status, (ContentType, String)) {
+	let db = request.rocket().state::<Pool<Postgres>>().unwrap();
+	let config = request.rocket().state::<Arc<Config>>().unwrap();
+	let path = request.uri().path().to_string();
+	let wanted = db::trim_path(
+		config,
+		std::path::Path::new(path.trim_start_matches("/post/").trim_start_matches('/')),
+	);
+	let suggestions = if wanted.is_empty() {
+		vec![]
+	} else {
+		db::similar_posts(db, &wanted, SUGGESTIONS)
+			.await
+			.unwrap_or_default()
+	};
+	let message = message(request, status);
+	if wants_json(request) {
+		let body = json!({
+			"status": status.code,
+			"error": message,
+			"suggestions": suggestions
+				.iter()
+				.map(|(path, title)| json!({
+					"url": format!("{}post/{path}", config.origin),
+					"title": title,
+				}))
+				.collect::<Vec<_>>(),
+		});
+		return (status, (ContentType::JSON, body.to_string()));
+	}
+	page(
+		request,
+		status,
+		"404.html.tera",
+		crate::context!({
+			"status": status.code,
+			"gone": status == Status::Gone,
+			"message": message,
+			"path": path,
+			"suggestions": suggestions,
+		}),
+	)
+	.await
+}
+
+#[catch(default)]
+pub async fn other(status: Status, request: &Request<'_>) -> (Status, (ContentType, String)) {
+	let message = message(request, status);
+	if wants_json(request) {
+		let body = json!({
+			"status": status.code,
+			"error": message,
+		});
+		return (status, (ContentType::JSON, body.to_string()));
+	}
+	page(
+		request,
+		status,
+		"500.html.tera",
+		crate::context!({
+			"status": status.code,
+			"reason": status.reason_lossy(),
+			"message": message,
+		}),
+	)
+	.await
+}
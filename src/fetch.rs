@@ -0,0 +1,221 @@
+use std::{
+	error::Error,
+	net::{IpAddr, Ipv4Addr, SocketAddr},
+	sync::{Arc, LazyLock},
+	time::Duration,
+};
+
+use reqwest::{
+	dns::{Addrs, Name, Resolve, Resolving},
+	redirect::Policy,
+};
+use url::{Host, Url};
+
+/// For fetching whatever visitors and other sites point us at, which can
+/// only ever connect to the public internet.
+static PUBLIC: LazyLock<reqwest::Client> = LazyLock::new(|| client(false));
+/// For fetching anything at all, which is only for trying things out on a
+/// local network.
+static ANY: LazyLock<reqwest::Client> = LazyLock::new(|| client(true));
+
+/// Why a request was refused before it left.
+#[derive(thiserror::Error, Debug)]
+#[error("{0} isn't on the public internet")]
+pub struct NotPublic(String);
+
+/// Whether an address is somewhere on the public internet.
+fn is_public(addr: IpAddr) -> bool {
+	match addr {
+		IpAddr::V4(v4) => is_public_v4(v4),
+		IpAddr::V6(v6) => {
+			if let Some(v4) = v6.to_ipv4_mapped() {
+				return is_public_v4(v4);
+			}
+			let segments = v6.segments();
+			// NAT64 addresses stand for the IPv4 address in their last 32 bits
+			if segments[..6] == [0x64, 0xff9b, 0, 0, 0, 0] {
+				let [.., hi, lo] = segments;
+				return is_public_v4(Ipv4Addr::from(u32::from(hi) << 16 | u32::from(lo)));
+			}
+			!(v6.is_loopback()
+				|| v6.is_unspecified()
+				|| v6.is_multicast()
+				|| v6.is_unique_local()
+				|| v6.is_unicast_link_local()
+				// Documentation
+				|| segments[..2] == [0x2001, 0xdb8])
+		}
+	}
+}
+
+fn is_public_v4(v4: Ipv4Addr) -> bool {
+	let [a, b, c, _] = v4.octets();
+	!(v4.is_private()
+		|| v4.is_loopback()
+		|| v4.is_link_local()
+		|| v4.is_unspecified()
+		|| v4.is_broadcast()
+		|| v4.is_multicast()
+		|| v4.is_documentation()
+		// "This network"
+		|| a == 0
+		// Shared address space for carrier-grade NAT
+		|| (a == 100 && (64..128).contains(&b))
+		// IETF protocol assignments
+		|| (a == 192 && b == 0 && c == 0)
+		// Benchmarking
+		|| (a == 198 && (18..20).contains(&b))
+		// Reserved
+		|| a >= 240)
+}
+
+/// Looks names up as usual, but only hands back public addresses. Checking
+/// as we connect means neither a redirect nor a DNS answer that changes
+/// after we've looked can point a request at our own network.
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+	fn resolve(&self, name: Name) -> Resolving {
+		Box::pin(async move {
+			let addrs: Vec<SocketAddr> = tokio::net::lookup_host((name.as_str(), 0))
+				.await?
+				.filter(|addr| is_public(addr.ip()))
+				.collect();
+			if addrs.is_empty() {
+				return Err(NotPublic(name.as_str().to_string()).into());
+			}
+			Ok(Box::new(addrs.into_iter()) as Addrs)
+		})
+	}
+}
+
+/// Names are checked when they're resolved, so this only needs to catch
+/// URLs that give an address outright.
+fn check_host(url: &Url) -> Result<(), NotPublic> {
+	let public = match url.host() {
+		Some(Host::Ipv4(v4)) => is_public_v4(v4),
+		Some(Host::Ipv6(v6)) => is_public(IpAddr::V6(v6)),
+		Some(Host::Domain(_)) => true,
+		None => false,
+	};
+	if public {
+		Ok(())
+	} else {
+		Err(NotPublic(url.to_string()))
+	}
+}
+
+fn client(allow_private: bool) -> reqwest::Client {
+	let builder = reqwest::Client::builder()
+		.timeout(Duration::from_secs(10))
+		.redirect(Policy::custom(move |attempt| {
+			if attempt.previous().len() >= 5 {
+				attempt.error("Too many redirects")
+			} else if allow_private {
+				attempt.follow()
+			} else if let Err(e) = check_host(attempt.url()) {
+				attempt.error(e)
+			} else {
+				attempt.follow()
+			}
+		}))
+		.user_agent(concat!("wolog/", env!("CARGO_PKG_VERSION")));
+	if allow_private {
+		builder.build().unwrap()
+	} else {
+		// A proxy would look names up for us, out of the resolver's reach
+		builder
+			.no_proxy()
+			.dns_resolver(Arc::new(PublicResolver))
+			.build()
+			.unwrap()
+	}
+}
+
+/// Fetches URLs from outside. It keeps to the public internet unless it's
+/// told otherwise, which is only for trying things out on a local network.
+#[derive(Clone, Copy)]
+pub struct Outside {
+	allow_private: bool,
+}
+
+impl Outside {
+	pub fn new(allow_private: bool) -> Self {
+		Outside { allow_private }
+	}
+
+	fn client(self) -> &'static reqwest::Client {
+		if self.allow_private { &ANY } else { &PUBLIC }
+	}
+
+	fn check(self, url: &Url) -> Result<(), NotPublic> {
+		if self.allow_private {
+			Ok(())
+		} else {
+			check_host(url)
+		}
+	}
+
+	pub fn get(self, url: &Url) -> Result<reqwest::RequestBuilder, NotPublic> {
+		self.check(url)?;
+		Ok(self.client().get(url.clone()))
+	}
+
+	pub fn post(self, url: &Url) -> Result<reqwest::RequestBuilder, NotPublic> {
+		self.check(url)?;
+		Ok(self.client().post(url.clone()))
+	}
+}
+
+/// Whether a request failed because it would have left the public internet.
+pub fn refused(e: &reqwest::Error) -> bool {
+	let mut source = e.source();
+	while let Some(e) = source {
+		if e.is::<NotPublic>() {
+			return true;
+		}
+		source = e.source();
+	}
+	false
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn private_addresses_are_not_public() {
+		for addr in [
+			"127.0.0.1",
+			"10.1.2.3",
+			"172.16.0.1",
+			"192.168.1.1",
+			"169.254.169.254",
+			"100.64.0.1",
+			"0.0.0.0",
+			"::1",
+			"fd00::1",
+			"fe80::1",
+			"::ffff:10.0.0.1",
+			"64:ff9b::7f00:1",
+		] {
+			assert!(!is_public(addr.parse().unwrap()), "{addr}");
+		}
+		for addr in ["93.184.215.14", "2606:2800:21f:cb07:6820:80da:af6b:8b2c"] {
+			assert!(is_public(addr.parse().unwrap()), "{addr}");
+		}
+	}
+
+	#[tokio::test]
+	async fn names_for_private_addresses_are_refused() {
+		let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+		let port = listener.local_addr().unwrap().port();
+		let outside = Outside::new(false);
+		let by_name = Url::parse(&format!("http://localhost:{port}/")).unwrap();
+		let e = outside.get(&by_name).unwrap().send().await.unwrap_err();
+		assert!(refused(&e), "{e:?}");
+		let by_address = Url::parse(&format!("http://127.0.0.1:{port}/")).unwrap();
+		assert!(outside.get(&by_address).is_err());
+		assert!(Outside::new(true).get(&by_address).is_ok());
+	}
+}
@@ -0,0 +1,110 @@
+use std::{
+	collections::{BTreeMap, HashMap},
+	sync::{Arc, Mutex},
+};
+
+use serde::Serialize;
+use sqlx::{Pool, Postgres};
+use tera::{Tera, Value};
+use tokio::runtime::Handle;
+
+use crate::{
+	Config,
+	db::{self, Search},
+};
+
+tokio::task_local! {
+	/// Answers already given while rendering the current page, so a sidebar
+	/// that asks for the same thing on every level doesn't hit the database
+	/// each time.
+	static CACHE: Mutex<HashMap<String, Value>>;
+}
+
+/// Run a render with a fresh cache for the template functions.
+pub async fn cached<F: Future>(render: F) -> F::Output {
+	CACHE.scope(Mutex::default(), render).await
+}
+
+/// Let templates look things up on the site for themselves.
+pub fn register(tera: &mut Tera, db: &Pool<Postgres>, config: &Arc<Config>) {
+	tera.register_function("search", {
+		let db = db.clone();
+		move |args: &HashMap<String, Value>| {
+			let search: Search = serde_json::from_value(Value::Object(
+				args.iter().map(|(k, v)| (k.clone(), v.clone())).collect(),
+			))
+			.map_err(|e| tera::Error::msg(format!("Bad arguments to search: {e}")))?;
+			query("search", args, db::search(&db, &search))
+		}
+	});
+	tera.register_function("get_post", {
+		let db = db.clone();
+		move |args: &HashMap<String, Value>| {
+			let path = path_arg("get_post", args)?;
+			query("get_post", args, async {
+				// Drafts stay hidden, even from templates
+				Ok(db::read_post_meta(&db, path).await.filter(|m| m.ready))
+			})
+		}
+	});
+	tera.register_function("backlinks", {
+		let db = db.clone();
+		let config = config.clone();
+		move |args: &HashMap<String, Value>| {
+			let path = path_arg("backlinks", args)?;
+			query("backlinks", args, db::backlinks(&config, &db, path))
+		}
+	});
+	tera.register_function("tags", {
+		let db = db.clone();
+		move |args: &HashMap<String, Value>| {
+			query("tags", args, async {
+				Ok(db::tag_counts(&db)
+					.await?
+					.into_iter()
+					.collect::<BTreeMap<_, _>>())
+			})
+		}
+	});
+	tera.register_function("mentions", {
+		let db = db.clone();
+		move |args: &HashMap<String, Value>| {
+			let path = path_arg("mentions", args)?;
+			query("mentions", args, db::mentioners(&db, path))
+		}
+	});
+	tera.register_function("guestbook_size", {
+		let db = db.clone();
+		move |args: &HashMap<String, Value>| {
+			let path = path_arg("guestbook_size", args)?;
+			query("guestbook_size", args, db::guestbook_size(&db, path))
+		}
+	});
+}
+
+fn path_arg<'a>(function: &str, args: &'a HashMap<String, Value>) -> tera::Result<&'a str> {
+	args.get("path")
+		.and_then(Value::as_str)
+		.map(|p| p.trim_start_matches(['.', '/']))
+		.ok_or_else(|| tera::Error::msg(format!("{function} needs a `path`")))
+}
+
+/// Answer a template function from the cache, or by waiting on the
+/// database. Tera functions can't be async, so this blocks the thread.
+fn query<T: Serialize>(
+	function: &str,
+	args: &HashMap<String, Value>,
+	answer: impl Future<Output = Result<T, sqlx::Error>>,
+) -> tera::Result<Value> {
+	let sorted: BTreeMap<_, _> = args.iter().collect();
+	let key = format!("{function}{}", serde_json::to_string(&sorted)?);
+	if let Ok(Some(cached)) = CACHE.try_with(|c| c.lock().unwrap().get(&key).cloned()) {
+		return Ok(cached);
+	}
+	let answer = tokio::task::block_in_place(|| Handle::current().block_on(answer))
+		.map_err(|e| tera::Error::msg(format!("{function} failed: {e}")))?;
+	let answer = serde_json::to_value(answer)?;
+	// Renders outside a request have no cache, which is fine
+	let _ = CACHE.try_with(|c| c.lock().unwrap().insert(key, answer.clone()));
+	Ok(answer)
+}
@@ -1,10 +1,15 @@
-use std::{collections::HashSet, path::PathBuf, sync::Arc};
+use std::{
+	collections::{HashMap, HashSet},
+	path::PathBuf,
+	sync::Arc,
+};
 
+use chrono::{DateTime, TimeDelta, Utc};
 use rocket::{
+	Request, State,
 	form::Form,
 	http::Status,
-	response::{content::RawHtml, Responder},
-	Request, State,
+	response::{Redirect, Responder, content::RawHtml},
 };
 use rocket_governor::{Method, Quota, RocketGovernable, RocketGovernor};
 use sqlx::{Pool, Postgres};
@@ -12,36 +17,96 @@ use tera::Tera;
 use thiserror::Error;
 use tokio::{sync::RwLock, task::JoinSet};
 
-use crate::{db, oauth::Identities, Config};
+use crate::{
+	Config,
+	cookies::Preferences,
+	db::{self, GuestBanTarget, GuestbookEntry},
+	errors::{self, AppError},
+	oauth::{Admin, Identities, Identity},
+	pandoc,
+	templates::RenderError,
+};
+
+/// The longest message, in characters, that can be left with a signature.
+const MAX_MESSAGE_LEN: usize = 1000;
+
+#[derive(strum::Display, strum::EnumString, FromFormField, Clone, Copy, PartialEq, Eq)]
+#[strum(serialize_all = "snake_case")]
+pub enum MessageStatus {
+	Pending,
+	Approved,
+	Hidden,
+}
 
 #[derive(Error, Debug)]
 pub enum GuestbookError {
 	#[error("Database error")]
 	DbError(#[from] sqlx::Error),
 	#[error("You are banned")]
-	YouAreBanned,
+	YouAreBanned {
+		reason: Option<String>,
+		expires: Option<DateTime<Utc>>,
+	},
 	#[error("No identity")]
 	NoIdentity,
 	#[error("NotFound")]
 	NotFound,
+	#[error("Message too long")]
+	MessageTooLong,
+	#[error("Couldn't render message")]
+	BadMessage,
+	#[error("Bad ban")]
+	BadBan,
+	#[error("Unknown reaction")]
+	UnknownReaction,
+	#[error(transparent)]
+	Render(#[from] RenderError),
 }
 
 impl<'r, 'o: 'r> Responder<'r, 'o> for GuestbookError {
 	fn respond_to(self, request: &'r Request<'_>) -> rocket::response::Result<'o> {
 		match self {
+			GuestbookError::Render(e) => e.respond_to(request),
 			GuestbookError::DbError(e) => {
 				dbg!(e);
-				(Status::InternalServerError, "Database error").respond_to(request)
+				errors::caught(request, Status::InternalServerError, "Database error")
 			}
-			GuestbookError::YouAreBanned => {
-				(Status::InternalServerError, "Database error").respond_to(request)
+			GuestbookError::YouAreBanned { reason, expires } => {
+				let until = expires
+					.map(|e| format!(" until {}", e.format("%Y-%m-%d %H:%M UTC")))
+					.unwrap_or_default();
+				let reason = reason.map(|r| format!(": {r}")).unwrap_or_default();
+				AppError::Forbidden(format!(
+					"You've been banned from signing guestbooks and reacting to posts{until}{reason}"
+				))
+				.respond_to(request)
 			}
-			GuestbookError::NoIdentity => (
+			GuestbookError::NoIdentity => errors::caught(
+				request,
 				Status::Unauthorized,
 				"You aren't logged in with that identity",
-			)
-				.respond_to(request),
-			GuestbookError::NotFound => (Status::Unauthorized, "Not found").respond_to(request),
+			),
+			GuestbookError::NotFound => errors::caught(request, Status::NotFound, "Not found"),
+			GuestbookError::MessageTooLong => errors::caught(
+				request,
+				Status::PayloadTooLarge,
+				format!("Messages can be at most {MAX_MESSAGE_LEN} characters long"),
+			),
+			GuestbookError::BadMessage => errors::caught(
+				request,
+				Status::BadRequest,
+				"Couldn't understand that message",
+			),
+			GuestbookError::BadBan => errors::caught(
+				request,
+				Status::BadRequest,
+				"Ban either an identity or an email domain",
+			),
+			GuestbookError::UnknownReaction => errors::caught(
+				request,
+				Status::BadRequest,
+				"That isn't one of the reactions",
+			),
 		}
 	}
 }
@@ -54,25 +119,31 @@ pub async fn display(
 	identities: Identities,
 	config: &State<Arc<Config>>,
 	_rl: RocketGovernor<'_, GuestbookRateLimit>,
+	prefs: Preferences,
 ) -> Result<RawHtml<String>, GuestbookError> {
 	let providers = config.oauth_providers.keys().collect::<Vec<_>>();
-	let path = path.to_string_lossy();
-	let path = path.trim_start_matches(['.', '/']).trim_end_matches(".md");
-	let Some(meta) = db::read_post_meta(db, path).await else {
+	let path = &db::trim_path(config, &path);
+	// Drafts don't get a guestbook until they're published
+	let Some(meta) = db::read_post_meta(db, path)
+		.await
+		.filter(|m| m.ready || config.develop)
+	else {
 		return Err(GuestbookError::NotFound);
 	};
 	let guests = db::read_guestbook(db, path).await?;
-	let has_signed: HashSet<String> = identities
+	let own_messages: HashMap<String, db::OwnMessage> = identities
 		.clone()
 		.into_iter()
 		.map(|(provider, identity)| {
 			let db = (*db).clone();
+			let path = path.clone();
 			async move {
 				(
 					provider.clone(),
-					db::has_signed(&db, &provider, &identity.sub)
+					db::own_message(&db, &path, &provider, &identity.sub)
 						.await
-						.unwrap_or(false),
+						.ok()
+						.flatten(),
 				)
 			}
 		})
@@ -80,23 +151,27 @@ pub async fn display(
 		.join_all()
 		.await
 		.into_iter()
-		.filter_map(|(p, s)| s.then_some(p))
+		.filter_map(|(p, m)| m.map(|m| (p, m)))
 		.collect();
-	let content = tera
-		.read()
-		.await
-		.render(
-			"guestbook.html.tera",
-			&crate::context!({
-				"path": path,
-				"meta": meta,
-				"has_signed": has_signed,
-				"guestbook": guests,
-				"identities": identities,
-				"providers": providers,
-			}),
-		)
-		.unwrap();
+	let has_signed: HashSet<&String> = own_messages.keys().collect();
+	let content = crate::templates::render(
+		tera,
+		config,
+		"guestbook.html.tera",
+		&crate::context!({
+			"prefs": prefs,
+			"path": path,
+			"meta": meta,
+			"has_signed": has_signed,
+			"own_messages": own_messages,
+			"max_message_len": MAX_MESSAGE_LEN,
+			"guestbook": guests,
+			"identities": identities,
+			"providers": providers,
+			"indieauth": config.enable_indieauth,
+		}),
+	)
+	.await?;
 	Ok(RawHtml(content))
 }
 
@@ -104,6 +179,7 @@ pub async fn display(
 pub struct GuestbookForm {
 	pub identity: String,
 	pub do_sign: bool,
+	pub message: Option<String>,
 }
 
 pub struct GuestbookRateLimit;
@@ -117,6 +193,34 @@ impl RocketGovernable<'_> for GuestbookRateLimit {
 	}
 }
 
+/// Refuse guests banned either in the config or from the moderation page.
+pub async fn check_bans(
+	config: &Config,
+	db: &Pool<Postgres>,
+	provider: &str,
+	identity: &Identity,
+) -> Result<(), GuestbookError> {
+	if config
+		.guestbook_bans
+		.get(provider)
+		.is_some_and(|bans| bans.contains(&identity.sub))
+	{
+		return Err(GuestbookError::YouAreBanned {
+			reason: None,
+			expires: None,
+		});
+	}
+	let ban = db::guest_ban(db, provider, &identity.sub, identity.email.as_deref()).await?;
+	if let Some(ban) = ban {
+		return Err(GuestbookError::YouAreBanned {
+			reason: ban.reason,
+			expires: ban.expires,
+		});
+	}
+	Ok(())
+}
+
+#[allow(clippy::too_many_arguments)]
 #[post("/<path_..>", data = "<form>")]
 pub async fn sign(
 	form: Form<GuestbookForm>,
@@ -125,24 +229,213 @@ pub async fn sign(
 	tera: &State<Arc<RwLock<Tera>>>,
 	identities: Identities,
 	config: &State<Arc<Config>>,
-	_rl: RocketGovernor<'_, GuestbookRateLimit>,
+	rl: RocketGovernor<'_, GuestbookRateLimit>,
+	prefs: Preferences,
 ) -> Result<RawHtml<String>, GuestbookError> {
-	let path = path_.to_string_lossy();
-	let path = path.trim_start_matches(['.', '/']).trim_end_matches(".md");
+	let path = &db::trim_path(config, &path_);
+	if !db::read_post_meta(db, path)
+		.await
+		.is_some_and(|m| m.ready || config.develop)
+	{
+		return Err(GuestbookError::NotFound);
+	}
 	let Some(identity) = identities.get(&form.identity) else {
 		return Err(GuestbookError::NoIdentity);
 	};
 	if form.do_sign {
-		if config
-			.guestbook_bans
-			.get(&form.identity)
-			.is_some_and(|bans| bans.contains(&identity.sub))
-		{
-			return Err(GuestbookError::YouAreBanned);
-		}
-		db::sign_guestbook(db, path, &form.identity, &identity.sub).await?;
+		check_bans(config, db, &form.identity, identity).await?;
+		let message = form
+			.message
+			.as_deref()
+			.map(str::trim)
+			.filter(|m| !m.is_empty());
+		let entry = match message {
+			Some(message) => {
+				if message.chars().count() > MAX_MESSAGE_LEN {
+					return Err(GuestbookError::MessageTooLong);
+				}
+				let message_html = pandoc::render_untrusted(message)
+					.await
+					.ok_or(GuestbookError::BadMessage)?;
+				let trusted = config.guestbook_auto_approve
+					&& db::is_returning_guest(db, &form.identity, &identity.sub).await?;
+				let status = if trusted {
+					MessageStatus::Approved
+				} else {
+					MessageStatus::Pending
+				};
+				Some((message, message_html, status.to_string()))
+			}
+			None => None,
+		};
+		db::sign_guestbook(
+			db,
+			path,
+			&form.identity,
+			&identity.sub,
+			entry
+				.as_ref()
+				.map(|(message, message_html, status)| GuestbookEntry {
+					message,
+					message_html,
+					status,
+				}),
+		)
+		.await?;
 	} else {
 		db::unsign_guestbook(db, path, &form.identity, &identity.sub).await?;
 	}
-	display(path_, db, tera, identities, config, _rl).await
+	display(path_, db, tera, identities, config, rl, prefs).await
+}
+
+#[get("/")]
+pub async fn moderate(
+	admin: Admin,
+	db: &State<Pool<Postgres>>,
+	tera: &State<Arc<RwLock<Tera>>>,
+	config: &State<Arc<Config>>,
+	prefs: Preferences,
+) -> Result<RawHtml<String>, GuestbookError> {
+	let signatures = db::recent_signatures(db, 100).await?;
+	let bans = db::guest_bans(db).await?;
+	let content = crate::templates::render(
+		tera,
+		config,
+		"admin-guestbook.html.tera",
+		&crate::context!({
+			"prefs": prefs,
+			"admin": admin.identity,
+			"admin_provider": admin.provider,
+			"signatures": signatures,
+			"bans": bans,
+			"config_bans": config.guestbook_bans,
+			"now": Utc::now(),
+		}),
+	)
+	.await?;
+	Ok(RawHtml(content))
+}
+
+#[derive(FromForm)]
+pub struct MessageModerationForm {
+	pub post: String,
+	pub provider: String,
+	pub guest: String,
+	pub status: MessageStatus,
+}
+
+#[post("/moderate", data = "<form>")]
+pub async fn set_status(
+	_admin: Admin,
+	form: Form<MessageModerationForm>,
+	db: &State<Pool<Postgres>>,
+) -> Result<Redirect, GuestbookError> {
+	db::set_message_status(
+		db,
+		&form.post,
+		&form.provider,
+		&form.guest,
+		&form.status.to_string(),
+	)
+	.await?;
+	Ok(Redirect::to("/admin/guestbook"))
+}
+
+#[derive(FromForm)]
+pub struct RemoveSignatureForm {
+	pub post: String,
+	pub provider: String,
+	pub guest: String,
+}
+
+#[post("/remove", data = "<form>")]
+pub async fn remove(
+	_admin: Admin,
+	form: Form<RemoveSignatureForm>,
+	db: &State<Pool<Postgres>>,
+) -> Result<Redirect, GuestbookError> {
+	db::unsign_guestbook(db, &form.post, &form.provider, &form.guest).await?;
+	Ok(Redirect::to("/admin/guestbook"))
+}
+
+/// Ban either an identity or an email domain, whichever was filled in.
+#[derive(FromForm)]
+pub struct BanForm {
+	pub provider: Option<String>,
+	pub sub: Option<String>,
+	pub email_domain: Option<String>,
+	pub reason: Option<String>,
+	/// Leave empty for a ban that never runs out
+	#[field(validate = with(
+		|days: &Option<u32>| days.is_none_or(|days| (1..=3650).contains(&days)),
+		"bans last between 1 and 3650 days"
+	))]
+	pub days: Option<u32>,
+	pub remove_signatures: bool,
+}
+
+#[post("/ban", data = "<form>")]
+pub async fn ban(
+	_admin: Admin,
+	form: Form<BanForm>,
+	db: &State<Pool<Postgres>>,
+) -> Result<Redirect, GuestbookError> {
+	let non_empty = |field: &Option<String>| {
+		field
+			.as_deref()
+			.map(str::trim)
+			.filter(|f| !f.is_empty())
+			.map(ToString::to_string)
+	};
+	let (provider, sub, email_domain) = (
+		non_empty(&form.provider),
+		non_empty(&form.sub),
+		non_empty(&form.email_domain),
+	);
+	let target = match (&provider, &sub, &email_domain) {
+		(Some(provider), Some(sub), None) => GuestBanTarget::Identity { provider, sub },
+		(None, None, Some(domain)) => GuestBanTarget::EmailDomain(domain.trim_start_matches('@')),
+		_ => return Err(GuestbookError::BadBan),
+	};
+	let expires = form
+		.days
+		.map(|days| Utc::now() + TimeDelta::days(days.into()));
+	db::add_guest_ban(db, target, non_empty(&form.reason).as_deref(), expires).await?;
+	if form.remove_signatures {
+		db::remove_banned_signatures(db, target).await?;
+		db::remove_banned_reactions(db, target).await?;
+		db::hide_banned_comments(db, target).await?;
+	}
+	Ok(Redirect::to("/admin/guestbook"))
+}
+
+#[derive(FromForm)]
+pub struct UnbanForm {
+	pub id: String,
+}
+
+#[post("/unban", data = "<form>")]
+pub async fn unban(
+	_admin: Admin,
+	form: Form<UnbanForm>,
+	db: &State<Pool<Postgres>>,
+) -> Result<Redirect, GuestbookError> {
+	db::remove_guest_ban(db, &form.id).await?;
+	Ok(Redirect::to("/admin/guestbook"))
+}
+
+#[cfg(test)]
+mod tests {
+	use rocket::form::Form;
+
+	use super::*;
+
+	#[test]
+	fn bans_cant_outlast_the_calendar() {
+		let days = |query: &str| Form::<BanForm>::parse(query).map(|form| form.days).ok();
+		assert_eq!(days("sub=x"), Some(None));
+		assert_eq!(days("sub=x&days=30"), Some(Some(30)));
+		assert_eq!(days("sub=x&days=0"), None);
+		assert_eq!(days("sub=x&days=4000000000"), None);
+	}
 }
@@ -0,0 +1,174 @@
+use std::{
+	collections::HashMap,
+	process::Stdio,
+	sync::{Arc, LazyLock},
+};
+
+use dashmap::DashMap;
+use rocket::{State, http::ContentType};
+use serde::Deserialize;
+
+use crate::{Config, errors::AppError};
+
+/// The highlight style made from the catppuccin palette the site uses.
+pub const CATPPUCCIN: &str = "catppuccin";
+
+/// Stylesheets already made from pandoc's styles, which never change while
+/// we're running.
+static STYLESHEETS: LazyLock<DashMap<String, String>> = LazyLock::new(DashMap::new);
+
+/// The class pandoc gives each kind of token, and how catppuccin colours
+/// it. Code blocks always sit on macchiato's base, whatever the theme.
+const TOKENS: [(&str, &str, &str); 30] = [
+	("Keyword", "kw", "mauve"),
+	("DataType", "dt", "yellow"),
+	("DecVal", "dv", "peach"),
+	("BaseN", "bn", "peach"),
+	("Float", "fl", "peach"),
+	("Constant", "cn", "peach"),
+	("Char", "ch", "green"),
+	("SpecialChar", "sc", "pink"),
+	("String", "st", "green"),
+	("VerbatimString", "vs", "green"),
+	("SpecialString", "ss", "green"),
+	("Import", "im", "mauve"),
+	("Comment", "co", "overlay2"),
+	("Documentation", "do", "overlay2"),
+	("Annotation", "an", "overlay2"),
+	("CommentVar", "cv", "overlay2"),
+	("Other", "ot", "text"),
+	("Function", "fu", "blue"),
+	("Variable", "va", "text"),
+	("ControlFlow", "cf", "mauve"),
+	("Operator", "op", "sky"),
+	("BuiltIn", "bu", "red"),
+	("Extension", "ex", "mauve"),
+	("Preprocessor", "pp", "pink"),
+	("Attribute", "at", "yellow"),
+	("RegionMarker", "re", "overlay2"),
+	("Information", "in", "teal"),
+	("Warning", "wa", "yellow"),
+	("Alert", "al", "red"),
+	("Error", "er", "red"),
+];
+
+/// One kind of token in a pandoc `.theme`.
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "kebab-case", default)]
+struct TokenStyle {
+	text_color: Option<String>,
+	background_color: Option<String>,
+	bold: bool,
+	italic: bool,
+	underline: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct Theme {
+	text_color: Option<String>,
+	background_color: Option<String>,
+	text_styles: HashMap<String, TokenStyle>,
+}
+
+fn rule(class: &str, style: &TokenStyle) -> String {
+	let declarations = [
+		style.text_color.as_ref().map(|c| format!("color: {c};")),
+		style
+			.background_color
+			.as_ref()
+			.map(|c| format!("background-color: {c};")),
+		style.bold.then(|| "font-weight: bold;".to_string()),
+		style.italic.then(|| "font-style: italic;".to_string()),
+		style
+			.underline
+			.then(|| "text-decoration: underline;".to_string()),
+	];
+	format!(
+		"code span.{class} {{ {} }}\n",
+		declarations
+			.into_iter()
+			.flatten()
+			.collect::<Vec<_>>()
+			.join(" ")
+	)
+}
+
+fn catppuccin() -> String {
+	TOKENS
+		.iter()
+		.map(|(token, class, colour)| {
+			let style = TokenStyle {
+				text_color: Some(format!("var(--ctp-macchiato-{colour})")),
+				italic: matches!(
+					*token,
+					"Comment" | "Documentation" | "Annotation" | "CommentVar"
+				),
+				bold: matches!(*token, "Alert" | "Error"),
+				..TokenStyle::default()
+			};
+			rule(class, &style)
+		})
+		.collect()
+}
+
+/// Turn one of pandoc's styles into a stylesheet for the classes it puts
+/// on highlighted code.
+async fn from_pandoc(style: &str) -> Result<String, String> {
+	let pandoc = tokio::process::Command::new("pandoc")
+		.arg("--print-highlight-style")
+		.arg(style)
+		.stdin(Stdio::null())
+		.stdout(Stdio::piped())
+		.stderr(Stdio::piped())
+		.output()
+		.await
+		.map_err(|e| format!("Couldn't run pandoc: {e}"))?;
+	if !pandoc.status.success() {
+		return Err(String::from_utf8_lossy(&pandoc.stderr).into_owned());
+	}
+	let theme: Theme = serde_json::from_slice(&pandoc.stdout).map_err(|e| e.to_string())?;
+	let mut css = rule(
+		"sourceCode",
+		&TokenStyle {
+			text_color: theme.text_color,
+			background_color: theme.background_color,
+			..TokenStyle::default()
+		},
+	)
+	.replacen("code span.sourceCode", "div.sourceCode", 1);
+	for (token, class, _) in TOKENS {
+		if let Some(style) = theme.text_styles.get(token) {
+			css.push_str(&rule(class, style));
+		}
+	}
+	Ok(css)
+}
+
+#[get("/highlight.css?<style>")]
+pub async fn stylesheet(
+	style: Option<&str>,
+	config: &State<Arc<Config>>,
+) -> Result<(ContentType, String), AppError> {
+	let style = style.unwrap_or(&config.writer.highlight_style);
+	if style == CATPPUCCIN {
+		return Ok((ContentType::CSS, catppuccin()));
+	}
+	// Anything but a built in style name would have pandoc read a file, so
+	// only the site's own style can be one
+	let builtin = style
+		.chars()
+		.all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+	if !builtin && style != config.writer.highlight_style {
+		return Err(AppError::NotFound);
+	}
+	if let Some(css) = STYLESHEETS.get(style) {
+		return Ok((ContentType::CSS, css.clone()));
+	}
+	let css = from_pandoc(style).await.map_err(|e| {
+		eprintln!("Couldn't make a stylesheet for {style}: {e}");
+		AppError::NotFound
+	})?;
+	STYLESHEETS.insert(style.to_string(), css.clone());
+	Ok((ContentType::CSS, css))
+}
@@ -1,32 +1,53 @@
 #![warn(clippy::pedantic)]
 
+mod admin;
+mod analytics;
+mod cli;
+mod comments;
 mod cookies;
 mod db;
+mod errors;
+mod fetch;
+mod functions;
 mod guestbook;
+mod highlight;
+mod indieauth;
+mod micropub;
+mod negotiate;
 mod oauth;
 mod pandoc;
+mod preferences;
+mod preview;
+mod reactions;
+mod reading;
+mod series;
+mod social;
+mod templates;
+mod webmention;
 
 #[macro_use]
 extern crate rocket;
 
-use chrono::{NaiveDate, Utc};
-use cookies::ClientPersist;
+use analytics::{ViewKind, Visit};
+use chrono::NaiveDate;
+use clap::Parser;
+use cookies::{ClientPersist, Preferences};
 use db::{PostType, Search, SortType};
+use errors::AppError;
 use figment::{
 	Figment,
 	providers::{Env, Format, Toml},
 };
-use notify::{EventKind, Watcher, poll};
+use negotiate::{Negotiated, Representation};
+use notify::{EventKind, Watcher};
 use oauth::OAuthProvider;
 use pandoc::{ast_to_html, run_postproc_filters};
-use reqwest::StatusCode;
 use rocket::{
-	State,
-	form::{Form, FromFormField, ValueField},
+	Build, Rocket, State,
+	form::{FromFormField, ValueField},
 	fs::{FileServer, Options},
-	futures::StreamExt,
-	http::{ContentType, CookieJar, Status},
-	response::content::RawHtml,
+	http::{ContentType, CookieJar},
+	response::{Redirect, content::RawHtml},
 };
 use serde::{Deserialize, Serialize};
 use serde_json::json;
@@ -44,6 +65,7 @@ use tokio::sync::RwLock;
 use url::Url;
 
 #[derive(Serialize, Deserialize, Clone)]
+#[allow(clippy::struct_excessive_bools)]
 struct Config {
 	content_root: PathBuf,
 	static_root: PathBuf,
@@ -54,36 +76,88 @@ struct Config {
 	enable_webmention: bool,
 	#[serde(default)]
 	develop: bool,
+	#[serde(default)]
+	enable_indieauth: bool,
 	database_url: String,
 	update_interval: u64,
 	#[serde(default)]
 	author: Option<String>,
+	/// What the site is called where it's shared or syndicated
+	site_name: String,
+	/// The language posts are written in unless they say otherwise
+	default_lang: String,
 	#[serde(default)]
 	webmention_bans: Vec<String>,
 	#[serde(default)]
+	webmention_allowlist: Vec<String>,
+	#[serde(default)]
 	guestbook_bans: HashMap<String, HashSet<String>>,
+	/// Approve messages and comments from guests who've had one approved before
+	#[serde(default)]
+	guestbook_auto_approve: bool,
 	#[serde(default)]
 	oauth_providers: HashMap<String, OAuthProvider>,
 	#[serde(default)]
+	admins: HashMap<String, HashSet<String>>,
+	#[serde(default)]
 	markdown_extensions: Vec<String>,
+	/// Which pandoc reader to use for each file extension in the content root
+	readers: HashMap<String, pandoc::Reader>,
+	micropub_dir: PathBuf,
+	#[serde(default)]
+	syndicate_to: Vec<micropub::SyndicationTarget>,
+	/// The emoji logged-in guests can react to posts with
+	reactions: Vec<String>,
+	/// How long to remember what a visitor has read after their last visit
+	visitor_expiry_days: i32,
+	/// Count page views, without storing anything that identifies visitors
+	enable_analytics: bool,
+	/// The shallowest heading level generated tables of contents include
+	toc_min_depth: i64,
+	/// The deepest heading level generated tables of contents include
+	toc_max_depth: i64,
+	/// How posts are written out as HTML, unless they say otherwise
+	#[serde(default)]
+	writer: pandoc::Writer,
+}
+
+/// Settings used where wolog.toml and the environment don't say otherwise.
+fn default_config() -> serde_json::Value {
+	json!({
+		"content_root": "./articles",
+		"static_root": "./static",
+		"assets_root": "./articles/assets",
+		"templates_root": "./templates/*.tera",
+		"origin": "https://wolo.dev/",
+		"site_name": "Wolog",
+		"default_lang": "en",
+		"update_interval": 60,
+		"database_url": std::env::var("DATABASE_URL").ok(),
+		"markdown_extensions": [],
+		"readers": {
+			"md": {"format": "markdown"},
+			"org": {"format": "org"},
+			"rst": {"format": "rst"},
+			"adoc": {"format": "asciidoc"},
+			"ipynb": {"format": "ipynb", "metadata_file": true},
+		},
+		"micropub_dir": "micropub",
+		"reactions": ["❤️", "👍", "🎉", "🤔", "👀"],
+		"visitor_expiry_days": 365,
+		"enable_analytics": true,
+		"toc_min_depth": 2,
+		"toc_max_depth": 3,
+	})
 }
 
-#[rocket::launch]
-async fn launch() -> _ {
+#[rocket::main]
+async fn main() -> color_eyre::Result<()> {
+	let args = cli::Args::parse();
 	let figment = Figment::new()
 		.merge(Toml::file("wolog.toml"))
 		.merge(Env::prefixed("WOLOG_").split("__"))
 		.join(figment::providers::Serialized::from(
-			json!({
-				"content_root": "./articles",
-				"static_root": "./static",
-				"assets_root": "./articles/assets",
-				"templates_root": "./templates/*.tera",
-				"origin": "https://wolo.dev/",
-				"update_interval": 60,
-				"database_url": std::env::var("DATABASE_URL").ok(),
-				"markdown_extensions": [],
-			}),
+			default_config(),
 			"default",
 		));
 	let config: Arc<Config> = Arc::new(figment.extract().expect("Invalid config"));
@@ -92,9 +166,12 @@ async fn launch() -> _ {
 		.await
 		.expect("Connect to database");
 	migrate!().run(&db).await.expect("Run migrations");
-	let tera = Arc::new(RwLock::new({
-		Tera::new(config.templates_root.to_str().unwrap()).expect("Tera failure")
-	}));
+	if let Some(command) = args.command {
+		return cli::run(command, &config, &db).await;
+	}
+	let tera = Arc::new(RwLock::new(
+		templates::load(&config, &db).expect("Tera failure"),
+	));
 	tokio::spawn({
 		let db = db.clone();
 		let config = config.clone();
@@ -104,12 +181,28 @@ async fn launch() -> _ {
 			}
 		}
 	});
-	setup_watcher(&db, config.clone(), tera.clone());
+	setup_watcher(&db, &config);
+	templates::watch(tera.clone(), config.clone(), db.clone());
+	webmention::spawn_sender(db.clone(), config.clone());
+	webmention::spawn_verifier(db.clone(), config.clone());
+	cookies::spawn_visitor_pruner(db.clone(), config.clone());
+	analytics::spawn_salt_pruner(db.clone());
+
+	mount_routes(
+		rocket::build()
+			.manage(db)
+			.manage(tera.clone())
+			.manage(config.clone()),
+		&config,
+	)
+	.launch()
+	.await?;
+	Ok(())
+}
 
-	rocket::build()
-		.manage(db)
-		.manage(tera.clone())
-		.manage(config.clone())
+/// Everything the site serves, and the catchers for when it can't.
+fn mount_routes(rocket: Rocket<Build>, config: &Config) -> Rocket<Build> {
+	rocket
 		.mount(
 			"/static",
 			FileServer::new(&config.static_root, Options::None),
@@ -120,16 +213,91 @@ async fn launch() -> _ {
 		)
 		.mount(
 			"/",
-			routes![index, page, tags, search, search_feed, webmention],
+			routes![
+				index,
+				page,
+				tags,
+				search,
+				search_feed,
+				highlight::stylesheet
+			],
 		)
 		.mount(
 			"/login",
 			routes![oauth::login, oauth::callback, oauth::clear, oauth::forgetme],
 		)
+		.mount(
+			"/login/indieauth",
+			routes![indieauth::login, indieauth::callback],
+		)
 		.mount("/guestbook", routes![guestbook::display, guestbook::sign,])
+		.mount("/react", routes![reactions::react])
+		.mount("/og", routes![social::image])
+		.mount("/series", routes![series::display, series::feed])
+		.mount("/comments", routes![comments::display, comments::post])
+		.mount("/comment", routes![comments::edit, comments::delete])
+		.mount("/feed/comments", routes![comments::feed])
+		.mount(
+			"/admin/comments",
+			routes![comments::moderate, comments::set_status],
+		)
+		.mount(
+			"/admin",
+			routes![
+				admin::dashboard,
+				admin::update,
+				admin::reload_templates,
+				admin::purge_caches
+			],
+		)
+		.mount(
+			"/admin/guestbook",
+			routes![
+				guestbook::moderate,
+				guestbook::set_status,
+				guestbook::remove,
+				guestbook::ban,
+				guestbook::unban
+			],
+		)
+		.mount(
+			"/webmention",
+			routes![webmention::receive, webmention::status, webmention::sent],
+		)
+		.mount(
+			"/admin/webmentions",
+			routes![
+				webmention::moderate,
+				webmention::set_status,
+				webmention::ban,
+				webmention::unban
+			],
+		)
+		.mount("/micropub", routes![micropub::post, micropub::query])
+		.mount("/admin/preview", routes![preview::create, preview::revoke])
+		.mount(
+			"/reading-list",
+			routes![
+				reading::display,
+				reading::bookmark,
+				reading::mark_read,
+				reading::rotate_feed
+			],
+		)
+		.mount("/feed/unread", routes![reading::unread_feed])
+		.mount("/feed/lang", routes![lang_feed])
+		.mount(
+			"/preferences",
+			routes![preferences::display, preferences::save],
+		)
+		.mount("/admin/stats", routes![analytics::stats])
+		.register(
+			"/",
+			catchers![errors::not_found, errors::gone, errors::other],
+		)
 }
 
-fn setup_watcher(db: &Pool<Postgres>, config: Arc<Config>, tera: Arc<RwLock<Tera>>) {
+fn setup_watcher(db: &Pool<Postgres>, config: &Arc<Config>) {
 	let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
 	let mut inotify_watcher = Box::new(
 		notify::INotifyWatcher::new(
@@ -206,30 +374,26 @@ fn setup_watcher(db: &Pool<Postgres>, config: Arc<Config>, tera: Arc<RwLock<Tera
 
 					if let Err(e) = db::update_one(&cfg, &db, path).await {
 						eprintln!("Error in initial database update: {e}");
-					};
+					}
 				}
 			}
 		}
 	});
-	tokio::spawn(async move {
-		let mut periodic = tokio::time::interval(Duration::from_secs(config.update_interval));
-		loop {
-			if let Err(e) = tera.write().await.full_reload() {
-				eprintln!("Error in periodic template reload: {e}");
-			}
-			periodic.tick().await;
-		}
-	});
 }
 
+#[allow(clippy::too_many_arguments)]
 #[get("/")]
 async fn index(
 	db: &State<Pool<Postgres>>,
 	tera: &State<Arc<RwLock<Tera>>>,
 	cookie: ClientPersist,
 	jar: &CookieJar<'_>,
+	identities: oauth::Identities,
 	config: &State<Arc<Config>>,
-) -> Result<RawHtml<String>, String> {
+	prefs: Preferences,
+	visit: Visit,
+	accepted: Representation,
+) -> Result<Negotiated, AppError> {
 	page(
 		db,
 		tera,
@@ -237,11 +401,28 @@ async fn index(
 		jar,
 		PathBuf::from_str("index").unwrap(),
 		false,
+		None,
+		None,
+		identities,
 		config,
+		prefs,
+		visit,
+		accepted,
 	)
 	.await
 }
 
+/// Send a visitor back to where a form says they came from, as long as
+/// that's somewhere on this site.
+fn local_redirect(return_to: Option<&str>, fallback: &'static str) -> Redirect {
+	match return_to {
+		Some(to) if to.starts_with('/') && !to.starts_with("//") && !to.starts_with("/\\") => {
+			Redirect::to(to.to_string())
+		}
+		_ => Redirect::to(fallback),
+	}
+}
+
 #[macro_export]
 macro_rules! context {
 	($c:tt) => {
@@ -249,7 +430,8 @@ macro_rules! context {
 	};
 }
 
-#[get("/post/<path..>?<bare>")]
+#[allow(clippy::too_many_arguments)]
+#[get("/post/<path..>?<bare>&<preview>")]
 async fn page(
 	db: &State<Pool<Postgres>>,
 	tera: &State<Arc<RwLock<Tera>>>,
@@ -257,122 +439,119 @@ async fn page(
 	jar: &CookieJar<'_>,
 	path: PathBuf,
 	bare: bool,
+	preview: Option<&str>,
+	admin: Option<oauth::Admin>,
+	identities: oauth::Identities,
 	config: &State<Arc<Config>>,
-) -> Result<RawHtml<String>, String> {
-	let path = &db::trim_path(&path);
-	let (ast, meta) = db::read_post(db, path).await.ok_or("Post not found")?;
+	prefs: Preferences,
+	visit: Visit,
+	accepted: Representation,
+) -> Result<Negotiated, AppError> {
+	let (path, asked) = Representation::split_path(path);
+	// Fragments are always HTML, and an extension in the URL beats
+	// whatever the Accept header says
+	let representation = if bare {
+		Representation::Html
+	} else {
+		asked.unwrap_or(accepted)
+	};
+	let path = &db::trim_path(config, &path);
+	// Webmention receivers rely on a missing post being an error status,
+	// and on deleted posts being 410 Gone
+	let Some((ast, mut meta)) = db::read_post(db, path).await else {
+		return Err(if db::post_removed(db, path).await? {
+			AppError::Gone
+		} else {
+			AppError::NotFound
+		});
+	};
+	// Drafts without a preview link look exactly like posts that don't exist
+	if !preview::can_view(config, db, path, &meta, preview, admin.is_some()).await {
+		return Err(AppError::NotFound);
+	}
+	let converted = match representation {
+		Representation::Html => None,
+		Representation::Markdown => Some(negotiate::markdown(config, path, &ast).await),
+		Representation::Json => Some(serde_json::to_string(&ast).ok()),
+		Representation::Plain => Some(pandoc::ast_to_format(&ast, "plain").await),
+	};
+	if let Some(converted) = converted {
+		let converted = converted.ok_or_else(|| {
+			AppError::Internal(format!("Couldn't convert {path} to {representation:?}"))
+		})?;
+		return Ok(Negotiated::new(representation, converted));
+	}
+	// Frontmatter was checked when the post was read, so this only falls
+	// back if the site's options have changed since
+	let writer = config
+		.writer
+		.merged(&meta.writer)
+		.unwrap_or_else(|_| config.writer.clone());
 	let content = run_postproc_filters(db, tera, ast, path, &cookie, config).await;
-	let content = ast_to_html(content)
+	let content = ast_to_html(content, &writer)
 		.await
-		.ok_or("Converting ast to html failed")?;
+		.ok_or_else(|| AppError::Internal("Converting ast to html failed".to_string()))?;
 	if bare {
-		return Ok(RawHtml(content));
+		return Ok(Negotiated::new(Representation::Html, content));
 	}
+	analytics::record(db, config, visit, ViewKind::Post, path);
 	let mentioners = db::mentioners(db, path).await.unwrap_or_default();
 	let guestbook_size = db::guestbook_size(db, path).await.unwrap_or(0);
-	let content = tera
-		.read()
+	let comment_count = db::comment_count(db, path).await.unwrap_or(0);
+	let reactions = reactions::for_post(config, db, path, &identities)
 		.await
-		.render(
-			format!("{}.html.tera", meta.template).as_str(),
-			&context!({
-				"path": path,
-				"toc": meta.toc.iter().map(ToString::to_string).collect::<String>(),
-				"meta": &meta,
-				"cookie": &cookie,
-				"mentioners": &mentioners,
-				"content": &content,
-				"guestbook_size": guestbook_size,
-				"has_oauth": !config.oauth_providers.is_empty()
-			}),
-		)
-		.expect("Tera rendering failure");
-	cookie
-		.viewed
-		.insert(path.to_string(), Utc::now().date_naive());
-	jar.add(cookie);
-	Ok(RawHtml(content))
-}
-
-#[derive(FromForm)]
-struct WebMention {
-	pub source: String,
-	pub target: String,
-}
-
-#[post("/webmention", data = "<wm>")]
-async fn webmention(
-	wm: Form<WebMention>,
-	cfg: &State<Arc<Config>>,
-	db: &State<Pool<Postgres>>,
-) -> Result<&'static str, (Status, String)> {
-	use reqwest::Url;
-	let WebMention { source, target } = wm.into_inner();
-	let source =
-		Url::parse(&source).map_err(|_| (Status::BadRequest, "Bad source URL".to_string()))?;
-	if source.cannot_be_a_base() {
-		return Err((
-			Status::BadRequest,
-			"Source URL should be absolute".to_string(),
-		));
-	}
-	let target =
-		Url::parse(&target).map_err(|_| (Status::BadRequest, "Bad target URL".to_string()))?;
-	if target.cannot_be_a_base() {
-		return Err((
-			Status::BadRequest,
-			"Target URL should be absolute".to_string(),
-		));
-	}
-	if target.host() != cfg.origin.host() {
-		return Err((
-			Status::BadRequest,
-			"Target URL should be one of our pages".to_string(),
-		));
-	}
-	let Some(path) = target
-		.path()
-		.trim_end_matches('/')
-		.trim_end_matches(".md")
-		.get("/post/".len()..)
-	else {
-		return Err((Status::BadRequest, "You can only mention posts".to_string()));
-	};
-
-	let client = reqwest::ClientBuilder::new()
-		.timeout(Duration::from_millis(300))
-		.build()
-		.unwrap();
-	let Ok(response) = client.get(source.clone()).send().await else {
-		return Err((
-			Status::BadRequest,
-			"Couldn't fetch the mentioning page".to_string(),
-		));
-	};
-	if response.status() == StatusCode::NOT_FOUND || response.status() == StatusCode::GONE {
-		let _ = db::rm_webmention(db, &source, path).await;
-		return Err((
-            Status::BadRequest,
-            "The mentioning page is missing? (if we had it before, we've done our best to delete it)".to_string(),
-        ));
-	}
-	let Ok(response) = response.text().await else {
-		return Err((
-			Status::BadRequest,
-			"The mentioning page doesn't seem to be UTF-8?".to_string(),
-		));
-	};
-	if !response.contains(target.as_str()) {
-		let _ = db::rm_webmention(db, &source, path).await;
-		return Err((
-            Status::BadRequest,
-            "The page doesn't mention us like you said it would? (if we had it before, we've deleted it)".to_string(),
-        ));
+		.unwrap_or_default();
+	let bookmarked = db::is_bookmarked(db, &cookie.id, path)
+		.await
+		.unwrap_or(false);
+	let views = db::post_views(db, std::slice::from_ref(path))
+		.await
+		.unwrap_or_default()
+		.remove(path)
+		.unwrap_or(0);
+	let series = db::series_part(config, db, path).await.unwrap_or_default();
+	if let Some(series) = &series {
+		series.navigate(&mut meta);
 	}
-	db::get_webmention(db, &source, path)
+	let lang = meta.lang.as_deref().unwrap_or(&config.default_lang);
+	let original = meta.translation_of.as_deref().unwrap_or(path);
+	let translations = db::translations(config, db, original)
 		.await
-		.map_err(|_| (Status::InternalServerError, "Database error".to_string()))?;
-	Ok("Looks OK! The webmention should be registered now.")
+		.unwrap_or_default();
+	let content = templates::render(
+		tera,
+		config,
+		format!("{}.html.tera", meta.template).as_str(),
+		&context!({
+			"prefs": prefs,
+			"path": path,
+			"toc": meta.toc.iter().flatten().map(ToString::to_string).collect::<String>(),
+			"toc_tree": db::Toc::entries(meta.toc.as_deref().unwrap_or_default(), 1),
+			"meta": &meta,
+			"cookie": &cookie,
+			"mentioners": &mentioners,
+			"content": &content,
+			"guestbook_size": guestbook_size,
+			"comment_count": comment_count,
+			"draft": !meta.ready,
+			"reactions": reactions,
+			"bookmarked": bookmarked,
+			"views": views,
+			"alternates": Representation::alternates(path),
+			"highlight_css": writer.highlight_css(),
+			"og": social::OpenGraph::new(config, path, &meta),
+			"lang": lang,
+			"series": series,
+			// A post on its own has nothing to switch between
+			"translations": if translations.len() > 1 { translations } else { vec![] },
+			"identities": identities,
+			"has_oauth": !config.oauth_providers.is_empty() || config.enable_indieauth
+		}),
+	)
+	.await?;
+	cookie.view(db, path).await;
+	jar.add(cookie);
+	Ok(Negotiated::new(Representation::Html, content))
 }
 
 #[derive(Debug, Clone, Copy, Serialize)]
@@ -416,6 +595,7 @@ struct SearchForm {
 	pub sort_type: SortType,
 	pub post_type: Option<PostType>,
 	pub limit: Option<u16>,
+	pub lang: Option<String>,
 }
 
 impl<'a> From<&'a SearchForm> for Search {
@@ -446,7 +626,9 @@ impl<'a> From<&'a SearchForm> for Search {
 			sort_type: value.sort_type,
 			limit: value.limit,
 			ignore_hidden: false,
-			extra: tera::Value::Object(Default::default()),
+			// The form's "any language" option is empty
+			lang: value.lang.clone().filter(|l| !l.is_empty()),
+			extra: tera::Value::Object(serde_json::Map::default()),
 		}
 	}
 }
@@ -454,17 +636,25 @@ impl<'a> From<&'a SearchForm> for Search {
 #[get("/tags")]
 async fn tags(
 	db: &State<Pool<Postgres>>,
+	cookie: ClientPersist,
 	tera: &State<Arc<RwLock<Tera>>>,
-) -> Result<RawHtml<String>, String> {
-	let tags = db::tag_counts(db).await.map_err(|e| e.to_string())?;
-	let context = context!({
-		"tags": tags
+	prefs: Preferences,
+	config: &State<Arc<Config>>,
+) -> Result<RawHtml<String>, AppError> {
+	let tags = db::tag_counts(db).await?;
+	// Everything is unread to someone who hasn't read anything yet, which
+	// isn't worth saying
+	let unread = if cookie.viewed.is_empty() {
+		HashMap::new()
+	} else {
+		db::unread_tag_counts(db, &cookie.id).await?
+	};
+	let ctx = context!({
+		"prefs": prefs,
+		"tags": tags,
+		"unread": unread
 	});
-	let content = tera
-		.read()
-		.await
-		.render("tag-directory.html.tera", &context)
-		.expect("Tera rendering failure");
+	let content = templates::render(tera, config, "tag-directory.html.tera", &ctx).await?;
 	Ok(RawHtml(content))
 }
 
@@ -474,37 +664,37 @@ async fn search(
 	db: &State<Pool<Postgres>>,
 	cookie: ClientPersist,
 	tera: &State<Arc<RwLock<Tera>>>,
-) -> Result<RawHtml<String>, (Status, String)> {
+	config: &State<Arc<Config>>,
+	prefs: Preferences,
+	visit: Visit,
+) -> Result<RawHtml<String>, AppError> {
 	let search: Search = (&search_form).into();
 	let search_url: Url = (&search).into();
-	let results = db::search(db, &search)
-		.await
-		.map_err(|e| (Status::InternalServerError, e.to_string()))?;
-	let tags = db::tags(db)
-		.await
-		.map_err(|e| (Status::InternalServerError, e.to_string()))?;
-	let context = context!({
+	let search_qs = search_url.query().unwrap_or("");
+	// What visitors search for is theirs, so searches are only counted
+	analytics::record(db, config, visit, ViewKind::Search, "search");
+	let results = db::search(db, &search).await?;
+	let paths: Vec<_> = results.iter().map(|(path, _)| path.clone()).collect();
+	let reactions = reactions::counts(&config.reactions, db, &paths).await?;
+	let views = db::post_views(db, &paths).await?;
+	let tags = db::tags(db).await?;
+	let languages = db::languages(config, db).await?;
+	let ctx = context!({
+		"prefs": prefs,
 		"search": search_form,
+		"languages": languages,
 		"articles": results,
+		"reactions": reactions,
+		"views": views,
 		"cookie": &cookie,
 		"new": results.iter().filter(|(path, meta)| {
 			let Some(viewed) = cookie.viewed.get(path) else {return true};
 			*viewed < meta.updated
 		}).map(|(p, _)| p).collect::<HashSet<_>>(),
-		"search_qs": search_url.query().unwrap_or(""),
+		"search_qs": search_qs,
 		"tags": tags
 	});
-	let content = tera
-		.read()
-		.await
-		.render("page-list.html.tera", &context)
-		.map_err(|e| {
-			dbg!(&e);
-			(
-				Status::InternalServerError,
-				format!("I couldn't finalize rendering this page because: {e}"),
-			)
-		})?;
+	let content = templates::render(tera, config, "page-list.html.tera", &ctx).await?;
 	Ok(RawHtml(content))
 }
 
@@ -514,12 +704,14 @@ async fn search_feed(
 	db: &State<Pool<Postgres>>,
 	tera: &State<Arc<RwLock<Tera>>>,
 	config: &State<Arc<Config>>,
-) -> Result<(ContentType, String), (Status, String)> {
+	visit: Visit,
+) -> Result<(ContentType, String), AppError> {
 	let mut search: Search = (&search_form).into();
 	search.limit = search.limit.map_or(32, |l| l.min(32)).into();
 	search.sort_type = SortType::CreateDesc;
 	let search_url: Url = (&search).into();
 	let search_qs = search_url.query().unwrap_or("");
+	analytics::record(db, config, visit, ViewKind::Feed, "feed/search");
 	search_atom_inner(
 		search,
 		db,
@@ -533,6 +725,39 @@ async fn search_feed(
 	.await
 }
 
+#[get("/<lang>")]
+async fn lang_feed(
+	lang: &str,
+	db: &State<Pool<Postgres>>,
+	tera: &State<Arc<RwLock<Tera>>>,
+	config: &State<Arc<Config>>,
+	visit: Visit,
+) -> Result<(ContentType, String), AppError> {
+	let search = Search {
+		lang: Some(lang.to_string()),
+		limit: Some(32),
+		..Search::default()
+	};
+	analytics::record(
+		db,
+		config,
+		visit,
+		ViewKind::Feed,
+		&format!("feed/lang/{lang}"),
+	);
+	search_atom_inner(
+		search,
+		db,
+		tera,
+		format!("{} ({lang})", config.site_name).into(),
+		"/favicon.ico".to_string().into(),
+		"/banner.png".to_string().into(),
+		config,
+		format!("{}feed/lang/{lang}", config.origin),
+	)
+	.await
+}
+
 #[allow(clippy::too_many_arguments)]
 async fn search_atom_inner(
 	search: Search,
@@ -543,11 +768,9 @@ async fn search_atom_inner(
 	logo: Option<String>,
 	config: &State<Arc<Config>>,
 	feed_url: String,
-) -> Result<(ContentType, String), (Status, String)> {
-	let results = db::search(db, &search)
-		.await
-		.map_err(|e| (Status::InternalServerError, e.to_string()))?;
-	let context = context!({
+) -> Result<(ContentType, String), AppError> {
+	let results = db::search(db, &search).await?;
+	let ctx = context!({
 		"articles": results,
 		"url": feed_url,
 		"title": title,
@@ -558,13 +781,17 @@ async fn search_atom_inner(
 	let content = tera
 		.read()
 		.await
-		.render("page-list.atom.tera", &context)
-		.map_err(|e| {
-			dbg!(&e);
-			(
-				Status::InternalServerError,
-				format!("I couldn't finalize rendering this page because: {e}"),
-			)
-		})?;
+		.render("page-list.atom.tera", &ctx)
+		.map_err(|e| AppError::Internal(format!("Couldn't render a feed: {e}")))?;
 	Ok((ContentType::new("application", "atom+xml"), content))
 }
+
+#[cfg(test)]
+impl Config {
+	/// The default settings, for tests that don't touch the database.
+	fn for_tests() -> Self {
+		let mut config = default_config();
+		config["database_url"] = json!("postgres://localhost/wolog");
+		serde_json::from_value(config).unwrap()
+	}
+}
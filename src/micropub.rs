@@ -0,0 +1,891 @@
+use std::{
+	fmt::Write,
+	path::{Path, PathBuf},
+	sync::Arc,
+};
+
+use chrono::{DateTime, Local, NaiveDate};
+use rocket::{
+	Data, Request, State,
+	data::ToByteUnit,
+	http::{ContentType, Status, uri::Origin},
+	request::{FromRequest, Outcome},
+	response::{
+		Responder,
+		status::{Created, NoContent},
+	},
+	serde::json::Json,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+use sqlx::{Pool, Postgres};
+use thiserror::Error;
+use url::{Url, form_urlencoded};
+
+use crate::{Config, db};
+
+/// Properties of a microformats2 object, each of which is a list of values.
+type Properties = Map<String, Value>;
+
+/// Marks the block of links we generate for replies, likes and reposts, so
+/// it can be told apart from the post's content when reading it back.
+const CONTEXT_DIV: &str = "::: {.micropub-context}";
+
+#[derive(Error, Debug)]
+pub enum MicropubError {
+	#[error("Database error")]
+	DbError(#[from] sqlx::Error),
+	#[error("Filesystem error")]
+	IoError(#[from] std::io::Error),
+	#[error("No access token")]
+	Unauthorized,
+	#[error("Bad access token")]
+	Forbidden,
+	#[error("Token lacks the {0} scope")]
+	InsufficientScope(&'static str),
+	#[error("{0}")]
+	InvalidRequest(String),
+	#[error("Not found")]
+	NotFound,
+	#[error("Unsupported content type")]
+	UnsupportedMediaType,
+}
+
+impl<'r, 'o: 'r> Responder<'r, 'o> for MicropubError {
+	fn respond_to(self, request: &'r Request<'_>) -> rocket::response::Result<'o> {
+		let (status, body) = match &self {
+			MicropubError::DbError(e) => {
+				dbg!(e);
+				(
+					Status::InternalServerError,
+					json!({"error": "server_error"}),
+				)
+			}
+			MicropubError::IoError(e) => {
+				dbg!(e);
+				(
+					Status::InternalServerError,
+					json!({"error": "server_error"}),
+				)
+			}
+			MicropubError::Unauthorized => (Status::Unauthorized, json!({"error": "unauthorized"})),
+			MicropubError::Forbidden => (Status::Forbidden, json!({"error": "forbidden"})),
+			MicropubError::InsufficientScope(scope) => (
+				Status::Forbidden,
+				json!({"error": "insufficient_scope", "scope": scope}),
+			),
+			MicropubError::InvalidRequest(description) => (
+				Status::BadRequest,
+				json!({"error": "invalid_request", "error_description": description}),
+			),
+			MicropubError::NotFound => (
+				Status::BadRequest,
+				json!({"error": "invalid_request", "error_description": "No such post"}),
+			),
+			MicropubError::UnsupportedMediaType => (
+				Status::UnsupportedMediaType,
+				json!({
+					"error": "invalid_request",
+					"error_description": "Send form-encoded or JSON requests"
+				}),
+			),
+		};
+		(status, Json(body)).respond_to(request)
+	}
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct SyndicationTarget {
+	pub uid: String,
+	pub name: String,
+}
+
+#[derive(Responder)]
+pub enum MicropubResponse {
+	Created(Created<()>),
+	Done(NoContent),
+}
+
+/// The token from an `Authorization: Bearer` header, if there was one.
+pub struct BearerToken(Option<String>);
+
+#[async_trait]
+impl<'r> FromRequest<'r> for BearerToken {
+	type Error = std::convert::Infallible;
+
+	async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+		Outcome::Success(BearerToken(
+			request
+				.headers()
+				.get_one("Authorization")
+				.and_then(|h| h.strip_prefix("Bearer "))
+				.map(|t| t.trim().to_string()),
+		))
+	}
+}
+
+async fn authorize(
+	db: &Pool<Postgres>,
+	token: Option<&str>,
+	scope: &'static str,
+) -> Result<db::MicropubToken, MicropubError> {
+	let token = token.ok_or(MicropubError::Unauthorized)?;
+	let token = db::use_micropub_token(db, token)
+		.await?
+		.ok_or(MicropubError::Forbidden)?;
+	// Older clients ask for `post` when they mean `create`
+	let allowed = token
+		.scope
+		.iter()
+		.any(|s| s == scope || (scope == "create" && s == "post"));
+	if !allowed {
+		return Err(MicropubError::InsufficientScope(scope));
+	}
+	Ok(token)
+}
+
+/// A micropub request, whichever way it was encoded.
+#[derive(Deserialize, Default)]
+struct MicropubRequest {
+	#[serde(default)]
+	action: Option<String>,
+	#[serde(default)]
+	url: Option<String>,
+	#[serde(default, rename = "type")]
+	kind: Vec<String>,
+	#[serde(default)]
+	properties: Properties,
+	#[serde(default)]
+	replace: Properties,
+	#[serde(default)]
+	add: Properties,
+	#[serde(default)]
+	delete: Value,
+	#[serde(skip)]
+	access_token: Option<String>,
+}
+
+impl MicropubRequest {
+	fn from_form(body: &str) -> Self {
+		let mut request = MicropubRequest::default();
+		for (key, value) in form_urlencoded::parse(body.as_bytes()) {
+			let value = value.into_owned();
+			match key.as_ref() {
+				"h" => request.kind.push(format!("h-{value}")),
+				"action" => request.action = Some(value),
+				"url" => request.url = Some(value),
+				"access_token" => request.access_token = Some(value),
+				key => {
+					let key = key.trim_end_matches("[]").to_string();
+					if let Value::Array(values) = request.properties.entry(key).or_insert(json!([]))
+					{
+						values.push(Value::String(value));
+					}
+				}
+			}
+		}
+		request
+	}
+}
+
+#[post("/", data = "<data>")]
+pub async fn post(
+	data: Data<'_>,
+	content_type: Option<&ContentType>,
+	token: BearerToken,
+	cfg: &State<Arc<Config>>,
+	db: &State<Pool<Postgres>>,
+) -> Result<MicropubResponse, MicropubError> {
+	let body = data.open(1.mebibytes()).into_string().await?.into_inner();
+	let request = match content_type {
+		Some(c) if c.is_json() => serde_json::from_str::<MicropubRequest>(&body)
+			.map_err(|e| MicropubError::InvalidRequest(e.to_string()))?,
+		Some(c) if c.is_form() => MicropubRequest::from_form(&body),
+		_ => return Err(MicropubError::UnsupportedMediaType),
+	};
+	let token = token.0.or(request.access_token.clone());
+	match request.action.as_deref() {
+		None | Some("create") => {
+			let token = authorize(db, token.as_deref(), "create").await?;
+			if !request.kind.iter().any(|k| k == "h-entry") {
+				return Err(MicropubError::InvalidRequest(
+					"Only h-entry posts are supported".to_string(),
+				));
+			}
+			let path = create(cfg, request.properties).await?;
+			eprintln!("Micropub client {} created {path}", token.name);
+			Ok(MicropubResponse::Created(Created::new(format!(
+				"{}post/{path}",
+				cfg.origin
+			))))
+		}
+		Some("update") => {
+			authorize(db, token.as_deref(), "update").await?;
+			let file = post_file(cfg, request.url.as_deref())?;
+			update(&file, request).await?;
+			Ok(MicropubResponse::Done(NoContent))
+		}
+		Some("delete") => {
+			authorize(db, token.as_deref(), "delete").await?;
+			let file = post_file(cfg, request.url.as_deref())?;
+			if !file.exists() {
+				return Err(MicropubError::NotFound);
+			}
+			tokio::fs::rename(&file, deleted_file(&file)).await?;
+			Ok(MicropubResponse::Done(NoContent))
+		}
+		Some("undelete") => {
+			authorize(db, token.as_deref(), "delete").await?;
+			let path = url_path(cfg, request.url.as_deref())?;
+			let deleted = deleted_files(cfg, &path)
+				.into_iter()
+				.next()
+				.ok_or(MicropubError::NotFound)?;
+			if !db::source_files(cfg, &path).is_empty() {
+				return Err(MicropubError::NotFound);
+			}
+			tokio::fs::rename(&deleted, deleted.with_extension("")).await?;
+			Ok(MicropubResponse::Done(NoContent))
+		}
+		Some(action) => Err(MicropubError::InvalidRequest(format!(
+			"Unknown action {action}"
+		))),
+	}
+}
+
+#[get("/")]
+pub async fn query(
+	origin: &Origin<'_>,
+	token: BearerToken,
+	cfg: &State<Arc<Config>>,
+	db: &State<Pool<Postgres>>,
+) -> Result<Json<Value>, MicropubError> {
+	let params: Vec<(String, String)> = origin
+		.query()
+		.map(|q| {
+			form_urlencoded::parse(q.as_str().as_bytes())
+				.into_owned()
+				.collect()
+		})
+		.unwrap_or_default();
+	let param = |name: &str| {
+		params
+			.iter()
+			.find(|(k, _)| k == name)
+			.map(|(_, v)| v.as_str())
+	};
+	let token = token.0.or(param("access_token").map(ToString::to_string));
+	match authorize(db, token.as_deref(), "create").await {
+		Err(MicropubError::InsufficientScope(_)) => {
+			authorize(db, token.as_deref(), "update").await?;
+		}
+		result => {
+			result?;
+		}
+	}
+	match param("q") {
+		Some("config") => Ok(Json(server_config(cfg))),
+		Some("syndicate-to") => Ok(Json(json!({"syndicate-to": cfg.syndicate_to}))),
+		Some("source") => {
+			let file = post_file(cfg, param("url"))?;
+			let (frontmatter, content) = read_entry(&file).await?;
+			let wanted: Vec<_> = params
+				.iter()
+				.filter(|(k, _)| k == "properties" || k == "properties[]")
+				.map(|(_, v)| v.as_str())
+				.collect();
+			Ok(Json(source(&frontmatter, &content, &wanted)))
+		}
+		_ => Err(MicropubError::InvalidRequest(
+			"Unsupported query".to_string(),
+		)),
+	}
+}
+
+/// What clients can do here, for `q=config`.
+fn server_config(cfg: &Config) -> Value {
+	json!({
+		"q": ["config", "source", "syndicate-to"],
+		"syndicate-to": cfg.syndicate_to,
+		"post-types": [
+			{"type": "note", "name": "Note"},
+			{"type": "article", "name": "Article"},
+			{"type": "reply", "name": "Reply"},
+			{"type": "like", "name": "Like"},
+			{"type": "repost", "name": "Repost"},
+		],
+	})
+}
+
+/// A post as micropub properties, for `q=source`. Asking for particular
+/// properties leaves out the type and everything else.
+fn source(frontmatter: &Frontmatter, content: &str, wanted: &[&str]) -> Value {
+	let mut properties = to_properties(frontmatter, content);
+	if wanted.is_empty() {
+		json!({"type": ["h-entry"], "properties": properties})
+	} else {
+		properties.retain(|k, _| wanted.contains(&k.as_str()));
+		json!({"properties": properties})
+	}
+}
+
+/// The frontmatter of a post written through micropub. Anything we don't know
+/// about is kept, so hand-edited posts can be updated too.
+#[derive(Serialize, Deserialize, Default)]
+struct Frontmatter {
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	title: Option<String>,
+	#[serde(default, skip_serializing_if = "String::is_empty")]
+	blurb: String,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	tags: Vec<String>,
+	created: NaiveDate,
+	updated: NaiveDate,
+	#[serde(default)]
+	ready: bool,
+	#[serde(default, skip_serializing_if = "std::ops::Not::not")]
+	hidden: bool,
+	#[serde(default, rename = "in-reply-to", skip_serializing_if = "Vec::is_empty")]
+	in_reply_to: Vec<String>,
+	#[serde(default, rename = "like-of", skip_serializing_if = "Vec::is_empty")]
+	like_of: Vec<String>,
+	#[serde(default, rename = "repost-of", skip_serializing_if = "Vec::is_empty")]
+	repost_of: Vec<String>,
+	#[serde(default, rename = "bookmark-of", skip_serializing_if = "Vec::is_empty")]
+	bookmark_of: Vec<String>,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	photo: Vec<String>,
+	#[serde(flatten)]
+	extra: Map<String, Value>,
+}
+
+/// Every value of a property as a string, looking inside nested objects for
+/// a URL or plain value.
+fn strings(properties: &Properties, key: &str) -> Vec<String> {
+	fn string(value: &Value) -> Option<String> {
+		match value {
+			Value::String(s) => Some(s.clone()),
+			Value::Object(o) => o
+				.get("html")
+				.or_else(|| o.get("value"))
+				.or_else(|| o.get("text"))
+				.and_then(string)
+				.or_else(|| {
+					o.get("properties")
+						.and_then(|p| p.get("url"))
+						.and_then(|u| u.get(0))
+						.and_then(string)
+				}),
+			_ => None,
+		}
+	}
+	match properties.get(key) {
+		Some(Value::Array(values)) => values.iter().filter_map(string).collect(),
+		Some(value) => string(value).into_iter().collect(),
+		None => vec![],
+	}
+}
+
+fn first(properties: &Properties, key: &str) -> Option<String> {
+	strings(properties, key).into_iter().next()
+}
+
+fn parse_date(date: &str) -> Option<NaiveDate> {
+	DateTime::parse_from_rfc3339(date)
+		.map(|d| d.date_naive())
+		.ok()
+		.or_else(|| NaiveDate::parse_from_str(date.get(..10)?, "%Y-%m-%d").ok())
+}
+
+/// Turn micropub properties into a post, starting from what it used to be.
+fn from_properties(properties: &Properties, mut frontmatter: Frontmatter) -> (Frontmatter, String) {
+	let today = Local::now().date_naive();
+	frontmatter.title = first(properties, "name").filter(|n| !n.trim().is_empty());
+	frontmatter.blurb = first(properties, "summary").unwrap_or_default();
+	frontmatter.tags = strings(properties, "category");
+	if let Some(published) = first(properties, "published").and_then(|p| parse_date(&p)) {
+		frontmatter.created = published;
+	}
+	frontmatter.updated = first(properties, "updated")
+		.and_then(|u| parse_date(&u))
+		.unwrap_or(today)
+		.max(frontmatter.created);
+	frontmatter.ready = first(properties, "post-status").is_none_or(|s| s != "draft");
+	frontmatter.hidden = first(properties, "visibility").is_some_and(|v| v != "public");
+	frontmatter.in_reply_to = strings(properties, "in-reply-to");
+	frontmatter.like_of = strings(properties, "like-of");
+	frontmatter.repost_of = strings(properties, "repost-of");
+	frontmatter.bookmark_of = strings(properties, "bookmark-of");
+	frontmatter.photo = strings(properties, "photo");
+	let content = first(properties, "content").unwrap_or_default();
+	(frontmatter, content)
+}
+
+fn to_properties(frontmatter: &Frontmatter, content: &str) -> Properties {
+	let mut properties = Properties::new();
+	let mut set = |key: &str, values: Vec<String>| {
+		if !values.is_empty() {
+			properties.insert(key.to_string(), json!(values));
+		}
+	};
+	set("name", frontmatter.title.iter().cloned().collect());
+	set(
+		"summary",
+		Some(frontmatter.blurb.clone())
+			.filter(|b| !b.is_empty())
+			.into_iter()
+			.collect(),
+	);
+	set("category", frontmatter.tags.clone());
+	set("published", vec![frontmatter.created.to_string()]);
+	set("updated", vec![frontmatter.updated.to_string()]);
+	set(
+		"post-status",
+		vec![
+			if frontmatter.ready {
+				"published"
+			} else {
+				"draft"
+			}
+			.to_string(),
+		],
+	);
+	if frontmatter.hidden {
+		set("visibility", vec!["unlisted".to_string()]);
+	}
+	set("in-reply-to", frontmatter.in_reply_to.clone());
+	set("like-of", frontmatter.like_of.clone());
+	set("repost-of", frontmatter.repost_of.clone());
+	set("bookmark-of", frontmatter.bookmark_of.clone());
+	set("photo", frontmatter.photo.clone());
+	set(
+		"content",
+		Some(content.trim().to_string())
+			.filter(|c| !c.is_empty())
+			.into_iter()
+			.collect(),
+	);
+	properties
+}
+
+/// Render a post as markdown with YAML frontmatter.
+fn render_entry(frontmatter: &Frontmatter, content: &str) -> String {
+	let yaml = serde_yml::to_string(frontmatter).unwrap();
+	let mut out = format!("---\n{yaml}---\n\n");
+	// These links are what `find_links` uses to decide what kind of post this is
+	let links: Vec<_> = [
+		("u-in-reply-to", "In reply to", &frontmatter.in_reply_to),
+		("u-like-of", "Liked", &frontmatter.like_of),
+		("u-repost-of", "Reposted", &frontmatter.repost_of),
+		("u-bookmark-of", "Bookmarked", &frontmatter.bookmark_of),
+	]
+	.into_iter()
+	.flat_map(|(class, label, urls)| {
+		urls.iter()
+			.map(move |url| format!("{label} [{url}]({url}){{.mention .{class}}}\\\n"))
+	})
+	.collect();
+	if !links.is_empty() {
+		out.push_str(CONTEXT_DIV);
+		out.push('\n');
+		for line in links {
+			out.push_str(&line);
+		}
+		out.push_str(":::\n\n");
+	}
+	for photo in &frontmatter.photo {
+		let _ = write!(out, "![]({photo}){{.u-photo}}\n\n");
+	}
+	out.push_str(content.trim());
+	out.push('\n');
+	out
+}
+
+/// Split a post back into its frontmatter and content.
+fn parse_entry(source: &str) -> Option<(Frontmatter, String)> {
+	let rest = source.strip_prefix("---\n")?;
+	let end = rest.find("\n---\n").or_else(|| rest.find("\n...\n"))?;
+	let frontmatter = serde_yml::from_str(&rest[..end]).ok()?;
+	let mut content = rest[end + 5..].trim_start();
+	if let Some(after) = content.strip_prefix(CONTEXT_DIV) {
+		content = after
+			.find("\n:::\n")
+			.map_or(after, |end| &after[end + 5..])
+			.trim_start();
+	}
+	// Photos are generated from the frontmatter too
+	while let Some(line) = content.lines().next().filter(|l| l.ends_with("{.u-photo}")) {
+		content = content[line.len()..].trim_start();
+	}
+	Some((frontmatter, content.to_string()))
+}
+
+async fn read_entry(file: &Path) -> Result<(Frontmatter, String), MicropubError> {
+	let source = tokio::fs::read_to_string(file)
+		.await
+		.map_err(|_| MicropubError::NotFound)?;
+	parse_entry(&source).ok_or_else(|| {
+		MicropubError::InvalidRequest("This post can't be edited through micropub".to_string())
+	})
+}
+
+/// The post a URL refers to.
+fn url_path(cfg: &Config, url: Option<&str>) -> Result<String, MicropubError> {
+	let url = url
+		.and_then(|u| Url::parse(u).ok())
+		.ok_or_else(|| MicropubError::InvalidRequest("Missing or bad url".to_string()))?;
+	db::post_path(cfg, &url).ok_or(MicropubError::NotFound)
+}
+
+/// The file a post URL refers to.
+fn post_file(cfg: &Config, url: Option<&str>) -> Result<PathBuf, MicropubError> {
+	let path = url_path(cfg, url)?;
+	// Posts we wrote are markdown, but hand-written ones can be anything
+	Ok(db::source_files(cfg, &path)
+		.into_iter()
+		.next()
+		.unwrap_or_else(|| cfg.content_root.join(format!("{path}.md"))))
+}
+
+/// Where a deleted post's source file is kept so it can be undeleted. No
+/// reader is configured for `.deleted`, so ingesting it removes the post.
+fn deleted_file(file: &Path) -> PathBuf {
+	let mut deleted = file.as_os_str().to_owned();
+	deleted.push(".deleted");
+	PathBuf::from(deleted)
+}
+
+/// A post's deleted source files, whatever format they were written in.
+fn deleted_files(cfg: &Config, path: &str) -> Vec<PathBuf> {
+	let mut extensions: Vec<_> = cfg.readers.keys().collect();
+	extensions.sort();
+	extensions
+		.into_iter()
+		.map(|ext| deleted_file(&cfg.content_root.join(format!("{path}.{ext}"))))
+		.filter(|file| file.is_file())
+		.collect()
+}
+
+fn slugify(text: &str) -> String {
+	let slug = text
+		.to_lowercase()
+		.split(|c: char| !c.is_alphanumeric())
+		.filter(|w| !w.is_empty())
+		.collect::<Vec<_>>()
+		.join("-");
+	slug.chars()
+		.take(48)
+		.collect::<String>()
+		.trim_end_matches('-')
+		.to_string()
+}
+
+async fn create(cfg: &Config, properties: Properties) -> Result<String, MicropubError> {
+	let now = Local::now();
+	let (mut frontmatter, content) = from_properties(
+		&properties,
+		Frontmatter {
+			created: now.date_naive(),
+			..Frontmatter::default()
+		},
+	);
+	for target in strings(&properties, "mp-syndicate-to") {
+		if cfg.syndicate_to.iter().any(|t| t.uid == target) {
+			frontmatter
+				.extra
+				.entry("mentions")
+				.or_insert(json!([]))
+				.as_array_mut()
+				.into_iter()
+				.for_each(|m| m.push(Value::String(target.clone())));
+		}
+	}
+	let slug = first(&properties, "mp-slug")
+		.or_else(|| frontmatter.title.clone())
+		.map(|s| slugify(&s))
+		.filter(|s| !s.is_empty())
+		.unwrap_or_else(|| now.format("%m%d-%H%M%S").to_string());
+	let dir = cfg
+		.micropub_dir
+		.join(frontmatter.created.format("%Y").to_string());
+	tokio::fs::create_dir_all(cfg.content_root.join(&dir)).await?;
+	let rendered = render_entry(&frontmatter, &content);
+	for n in 1.. {
+		let path = if n == 1 {
+			dir.join(&slug)
+		} else {
+			dir.join(format!("{slug}-{n}"))
+		};
+		let path = db::trim_path(cfg, &path);
+		let file = cfg.content_root.join(format!("{path}.md"));
+		if !db::source_files(cfg, &path).is_empty() || !deleted_files(cfg, &path).is_empty() {
+			continue;
+		}
+		tokio::fs::write(&file, &rendered).await?;
+		return Ok(path);
+	}
+	unreachable!()
+}
+
+async fn update(file: &Path, request: MicropubRequest) -> Result<(), MicropubError> {
+	let (frontmatter, content) = read_entry(file).await?;
+	let mut properties = to_properties(&frontmatter, &content);
+	edit(&mut properties, request)?;
+	let (frontmatter, content) = from_properties(&properties, frontmatter);
+	tokio::fs::write(file, render_entry(&frontmatter, &content)).await?;
+	Ok(())
+}
+
+/// Apply an update's replacements, additions and deletions, in that order.
+fn edit(properties: &mut Properties, request: MicropubRequest) -> Result<(), MicropubError> {
+	for (key, values) in request.replace {
+		properties.insert(key, values);
+	}
+	for (key, values) in request.add {
+		let Value::Array(values) = values else {
+			return Err(MicropubError::InvalidRequest(format!(
+				"Values to add to {key} should be a list"
+			)));
+		};
+		if let Value::Array(existing) = properties.entry(key).or_insert(json!([])) {
+			existing.extend(values);
+		}
+	}
+	match request.delete {
+		Value::Array(keys) => {
+			for key in keys.iter().filter_map(Value::as_str) {
+				properties.remove(key);
+			}
+		}
+		Value::Object(removals) => {
+			for (key, values) in removals {
+				let Value::Array(values) = values else {
+					continue;
+				};
+				if let Some(Value::Array(existing)) = properties.get_mut(&key) {
+					existing.retain(|v| !values.contains(v));
+				}
+			}
+		}
+		_ => {}
+	}
+	// Editing a post always bumps its updated date
+	properties.remove("updated");
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn properties(value: Value) -> Properties {
+		serde_json::from_value(value).unwrap()
+	}
+
+	fn request(value: Value) -> MicropubRequest {
+		serde_json::from_value(value).unwrap()
+	}
+
+	#[test]
+	fn slugs_are_safe_file_names() {
+		assert_eq!(slugify("Hello, World! Ça va?"), "hello-world-ça-va");
+		assert_eq!(slugify("../../etc/passwd"), "etc-passwd");
+		assert_eq!(slugify("🎉"), "");
+		assert_eq!(
+			slugify(&"abc ".repeat(20)),
+			"abc-".repeat(12).trim_end_matches('-')
+		);
+	}
+
+	#[test]
+	fn urls_only_reach_posts_in_the_content_root() {
+		let cfg = Config::for_tests();
+		let file = |url: &str| post_file(&cfg, Some(url)).ok();
+		assert_eq!(
+			file("https://wolo.dev/post/micropub/2026/hello"),
+			Some(cfg.content_root.join("micropub/2026/hello.md"))
+		);
+		// The format is ours to pick, not the client's
+		assert_eq!(
+			file("https://wolo.dev/post/notes/hi.org"),
+			Some(cfg.content_root.join("notes/hi.md"))
+		);
+		assert_eq!(
+			file("https://wolo.dev/post/%2Fetc/passwd"),
+			Some(cfg.content_root.join("etc/passwd.md"))
+		);
+		assert_eq!(
+			file("https://wolo.dev/post/a/..%2F..%2F..%2Fetc/passwd"),
+			None
+		);
+		assert_eq!(file("https://wolo.dev/post/%2e%2e/etc/passwd"), None);
+		assert_eq!(file("https://elsewhere.example/post/hello"), None);
+		assert_eq!(file("https://wolo.dev/about"), None);
+		assert!(matches!(
+			post_file(&cfg, Some("not a url")),
+			Err(MicropubError::InvalidRequest(_))
+		));
+	}
+
+	#[test]
+	fn deleted_posts_are_found_in_any_format() {
+		let root = std::env::temp_dir().join(format!("wolog-micropub-{}", std::process::id()));
+		std::fs::create_dir_all(root.join("notes")).unwrap();
+		let cfg = Config {
+			content_root: root.clone(),
+			..Config::for_tests()
+		};
+		let file = root.join("notes/hi.org");
+		let deleted = deleted_file(&file);
+		assert_eq!(deleted, root.join("notes/hi.org.deleted"));
+		// Nothing reads a deleted file as a post
+		assert!(db::reader(&cfg, &deleted).is_none());
+		std::fs::write(&deleted, "").unwrap();
+		assert_eq!(
+			deleted_files(&cfg, "notes/hi"),
+			std::slice::from_ref(&deleted)
+		);
+		assert_eq!(deleted.with_extension(""), file);
+		assert!(deleted_files(&cfg, "notes/other").is_empty());
+		std::fs::remove_dir_all(root).unwrap();
+	}
+
+	#[test]
+	fn forms_are_read_as_properties() {
+		let request = MicropubRequest::from_form(
+			"h=entry&content=Hi&category[]=a&category[]=b&access_token=t",
+		);
+		assert_eq!(request.kind, ["h-entry"]);
+		assert_eq!(request.access_token.as_deref(), Some("t"));
+		assert_eq!(request.properties["content"], json!(["Hi"]));
+		assert_eq!(request.properties["category"], json!(["a", "b"]));
+	}
+
+	#[test]
+	fn properties_map_to_frontmatter_and_back() {
+		let (frontmatter, content) = from_properties(
+			&properties(json!({
+				"name": ["Hello"],
+				"summary": ["A greeting"],
+				"category": ["rust", "web"],
+				"published": ["2026-01-02T03:04:05+01:00"],
+				"post-status": ["draft"],
+				"visibility": ["unlisted"],
+				"in-reply-to": [{"type": ["h-cite"], "properties": {"url": ["https://example.com/a"]}}],
+				"content": [{"html": "<p>Hi</p>"}],
+			})),
+			Frontmatter::default(),
+		);
+		assert_eq!(frontmatter.title.as_deref(), Some("Hello"));
+		assert_eq!(frontmatter.blurb, "A greeting");
+		assert_eq!(frontmatter.tags, ["rust", "web"]);
+		assert_eq!(
+			frontmatter.created,
+			NaiveDate::from_ymd_opt(2026, 1, 2).unwrap()
+		);
+		assert!(!frontmatter.ready);
+		assert!(frontmatter.hidden);
+		assert_eq!(frontmatter.in_reply_to, ["https://example.com/a"]);
+		assert_eq!(content, "<p>Hi</p>");
+
+		let written = to_properties(&frontmatter, &content);
+		assert_eq!(written["published"], json!(["2026-01-02"]));
+		assert_eq!(written["post-status"], json!(["draft"]));
+		assert_eq!(written["visibility"], json!(["unlisted"]));
+		assert_eq!(written["in-reply-to"], json!(["https://example.com/a"]));
+
+		// Clients that don't say otherwise are publishing
+		let (frontmatter, _) =
+			from_properties(&properties(json!({"name": [" "]})), Frontmatter::default());
+		assert!(frontmatter.ready);
+		assert!(!frontmatter.hidden);
+		assert_eq!(frontmatter.title, None);
+	}
+
+	#[test]
+	fn source_reads_back_what_was_written() {
+		let (frontmatter, content) = from_properties(
+			&properties(json!({
+				"name": ["Liked"],
+				"like-of": ["https://example.com/b"],
+				"photo": ["https://example.com/p.jpg"],
+				"content": ["Nice *post*"],
+			})),
+			Frontmatter::default(),
+		);
+		let (frontmatter, content) = parse_entry(&render_entry(&frontmatter, &content)).unwrap();
+		let full = source(&frontmatter, &content, &[]);
+		assert_eq!(full["type"], json!(["h-entry"]));
+		// The links and photos generated from the frontmatter aren't content
+		assert_eq!(full["properties"]["content"], json!(["Nice *post*"]));
+		assert_eq!(
+			full["properties"]["like-of"],
+			json!(["https://example.com/b"])
+		);
+		assert_eq!(
+			full["properties"]["photo"],
+			json!(["https://example.com/p.jpg"])
+		);
+		assert_eq!(
+			source(&frontmatter, &content, &["name", "category"]),
+			json!({"properties": {"name": ["Liked"]}})
+		);
+		// Hand-written posts without frontmatter can't be edited
+		assert!(parse_entry("Just text\n").is_none());
+	}
+
+	#[test]
+	fn config_lists_syndication_targets() {
+		let mut cfg = Config::for_tests();
+		cfg.syndicate_to.push(SyndicationTarget {
+			uid: "https://social.example/@me".to_string(),
+			name: "Fediverse".to_string(),
+		});
+		let config = server_config(&cfg);
+		assert!(config["q"].as_array().unwrap().contains(&json!("source")));
+		assert_eq!(
+			config["syndicate-to"],
+			json!([{"uid": "https://social.example/@me", "name": "Fediverse"}])
+		);
+	}
+
+	#[test]
+	fn updates_replace_then_add_then_delete() {
+		let mut properties = properties(json!({
+			"name": ["Old"],
+			"category": ["a", "b", "c"],
+			"summary": ["Gone soon"],
+			"updated": ["2020-01-01"],
+			"content": ["Text"],
+		}));
+		edit(
+			&mut properties,
+			request(json!({
+				"action": "update",
+				"url": "https://wolo.dev/post/x",
+				"replace": {"name": ["New"]},
+				"add": {"category": ["d"], "syndication": ["https://elsewhere.example/1"]},
+				"delete": {"category": ["b"]},
+			})),
+		)
+		.unwrap();
+		assert_eq!(properties["name"], json!(["New"]));
+		assert_eq!(properties["category"], json!(["a", "c", "d"]));
+		assert_eq!(
+			properties["syndication"],
+			json!(["https://elsewhere.example/1"])
+		);
+		assert_eq!(properties["content"], json!(["Text"]));
+		assert!(!properties.contains_key("updated"));
+
+		edit(
+			&mut properties,
+			request(json!({"delete": ["summary", "category"]})),
+		)
+		.unwrap();
+		assert!(!properties.contains_key("summary"));
+		assert!(!properties.contains_key("category"));
+
+		assert!(edit(&mut properties, request(json!({"add": {"category": "e"}}))).is_err());
+	}
+}
@@ -0,0 +1,224 @@
+use std::path::PathBuf;
+
+use pandoc_ast::Pandoc;
+use rocket::{
+	Request,
+	http::{Accept, ContentType, Header, MediaType},
+	outcome::Outcome,
+	request::{self, FromRequest},
+};
+
+use crate::{Config, db, pandoc};
+
+/// The ways a post can be served.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Representation {
+	Html,
+	/// The source as written, or pandoc's markdown for posts written in
+	/// something else
+	Markdown,
+	/// Pandoc's JSON AST
+	Json,
+	Plain,
+}
+
+impl Representation {
+	pub const ALL: [Representation; 4] = [
+		Representation::Html,
+		Representation::Markdown,
+		Representation::Json,
+		Representation::Plain,
+	];
+
+	pub fn content_type(self) -> ContentType {
+		match self {
+			Representation::Html => ContentType::HTML,
+			Representation::Markdown => {
+				ContentType::new("text", "markdown").with_params(("charset", "utf-8"))
+			}
+			Representation::Json => ContentType::JSON,
+			Representation::Plain => ContentType::Plain,
+		}
+	}
+
+	/// The extension that asks for this representation in a post URL.
+	pub fn extension(self) -> Option<&'static str> {
+		match self {
+			Representation::Html => None,
+			Representation::Markdown => Some("md"),
+			Representation::Json => Some("json"),
+			Representation::Plain => Some("txt"),
+		}
+	}
+
+	fn from_media_type(media_type: &MediaType) -> Option<Self> {
+		if media_type.top() == "*" {
+			return Some(Representation::Html);
+		}
+		Representation::ALL
+			.into_iter()
+			.find(|r| r.content_type().media_type() == media_type)
+	}
+
+	/// The representation an `Accept` header likes best.
+	fn negotiate(accept: Option<&Accept>) -> Self {
+		let Some(accept) = accept else {
+			return Representation::Html;
+		};
+		// A weight of zero means it isn't acceptable at all
+		let mut wanted: Vec<_> = accept.iter().filter(|m| m.weight_or(1.0) > 0.0).collect();
+		// The sort is stable, so equally weighted types stay in the order
+		// they were asked for
+		wanted.sort_by(|a, b| b.weight_or(1.0).total_cmp(&a.weight_or(1.0)));
+		wanted
+			.into_iter()
+			.find_map(|m| Representation::from_media_type(m.media_type()))
+			.unwrap_or(Representation::Html)
+	}
+
+	/// Split a representation's extension off a post path like
+	/// `notes/rust.md`.
+	pub fn split_path(path: PathBuf) -> (PathBuf, Option<Self>) {
+		let wanted = path.extension().and_then(|ext| {
+			Representation::ALL
+				.into_iter()
+				.find(|r| r.extension().is_some_and(|e| ext.eq_ignore_ascii_case(e)))
+		});
+		match wanted {
+			Some(wanted) => (path.with_extension(""), Some(wanted)),
+			None => (path, None),
+		}
+	}
+
+	/// Links to every other representation of the post at `path`, for
+	/// `<link rel="alternate">`.
+	pub fn alternates(path: &str) -> Vec<(String, String)> {
+		Representation::ALL
+			.into_iter()
+			.filter_map(|r| {
+				let ext = r.extension()?;
+				let media_type = r.content_type();
+				Some((
+					format!("{}/{}", media_type.top(), media_type.sub()),
+					format!("/post/{path}.{ext}"),
+				))
+			})
+			.collect()
+	}
+}
+
+/// What the `Accept` header asks for, when the URL doesn't say. Browsers
+/// and anything that doesn't care get HTML.
+#[async_trait]
+impl<'r> FromRequest<'r> for Representation {
+	type Error = std::convert::Infallible;
+
+	async fn from_request(request: &'r Request<'_>) -> request::Outcome<Self, Self::Error> {
+		Outcome::Success(Representation::negotiate(request.accept()))
+	}
+}
+
+/// A post in whichever representation was asked for. Caches need to know
+/// the same URL can give different answers.
+#[derive(Responder)]
+pub struct Negotiated {
+	body: (ContentType, String),
+	vary: Header<'static>,
+}
+
+impl Negotiated {
+	pub fn new(representation: Representation, body: String) -> Self {
+		Negotiated {
+			body: (representation.content_type(), body),
+			vary: Header::new("Vary", "Accept"),
+		}
+	}
+}
+
+/// Whether a source file can be served as markdown without converting it.
+pub fn is_markdown(format: &str) -> bool {
+	let base = format.split(['+', '-']).next().unwrap_or_default();
+	matches!(
+		base,
+		"markdown"
+			| "markdown_strict"
+			| "markdown_phpextra"
+			| "markdown_mmd"
+			| "commonmark"
+			| "commonmark_x"
+			| "gfm"
+	)
+}
+
+/// A post as markdown. Markdown sources are served as they were written,
+/// and anything else is converted.
+pub async fn markdown(config: &Config, path: &str, ast: &Pandoc) -> Option<String> {
+	let source = db::source_files(config, path).into_iter().next();
+	if let Some(source) = source
+		&& db::reader(config, &source).is_some_and(|r| is_markdown(&r.format) && !r.metadata_file)
+	{
+		return tokio::fs::read_to_string(source).await.ok();
+	}
+	pandoc::ast_to_format(ast, "markdown").await
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn negotiated(accept: &str) -> Representation {
+		Representation::negotiate(Some(&accept.parse().unwrap()))
+	}
+
+	#[test]
+	fn accept_headers_pick_a_representation() {
+		assert_eq!(Representation::negotiate(None), Representation::Html);
+		assert_eq!(
+			negotiated("text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8"),
+			Representation::Html
+		);
+		assert_eq!(negotiated("application/json"), Representation::Json);
+		assert_eq!(
+			negotiated("text/markdown; charset=utf-8"),
+			Representation::Markdown
+		);
+		assert_eq!(
+			negotiated("text/plain;q=0.5, text/markdown"),
+			Representation::Markdown
+		);
+		assert_eq!(
+			negotiated("application/json, text/markdown"),
+			Representation::Json
+		);
+		assert_eq!(
+			negotiated("image/png, text/plain;q=0.1"),
+			Representation::Plain
+		);
+		assert_eq!(negotiated("image/png"), Representation::Html);
+		assert_eq!(negotiated("application/json;q=0"), Representation::Html);
+	}
+
+	#[test]
+	fn extensions_pick_a_representation() {
+		let split = |path: &str| Representation::split_path(PathBuf::from(path));
+		assert_eq!(
+			split("notes/rust.MD"),
+			(PathBuf::from("notes/rust"), Some(Representation::Markdown))
+		);
+		assert_eq!(
+			split("notes/rust.json"),
+			(PathBuf::from("notes/rust"), Some(Representation::Json))
+		);
+		assert_eq!(split("notes/rust"), (PathBuf::from("notes/rust"), None));
+		assert_eq!(split("notes/v1.2"), (PathBuf::from("notes/v1.2"), None));
+	}
+
+	#[test]
+	fn markdown_sources_are_recognised() {
+		assert!(is_markdown("markdown"));
+		assert!(is_markdown("markdown+smart-citations"));
+		assert!(is_markdown("gfm"));
+		assert!(!is_markdown("org"));
+		assert!(!is_markdown("ipynb"));
+	}
+}
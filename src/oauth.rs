@@ -6,16 +6,16 @@ use std::{
 
 use dashmap::DashMap;
 use openidconnect::{
-	core::{CoreAuthenticationFlow, CoreClient, CoreProviderMetadata},
 	AccessTokenHash, AuthorizationCode, ClientId, ClientSecret, ConfigurationError, CsrfToken,
 	IssuerUrl, LanguageTag, Nonce, OAuth2TokenResponse, PkceCodeChallenge, PkceCodeVerifier,
 	RedirectUrl, Scope, TokenResponse,
+	core::{CoreAuthenticationFlow, CoreClient, CoreProviderMetadata},
 };
 use rocket::{
+	Request, State,
 	http::{Cookie, CookieJar, SameSite, Status},
 	request::{FromRequest, Outcome},
 	response::{Redirect, Responder},
-	Request, State,
 };
 use rocket_governor::{Method, Quota, RocketGovernable, RocketGovernor};
 use serde::{Deserialize, Serialize};
@@ -25,7 +25,7 @@ use thiserror::Error;
 use tokio::{sync::RwLock, task::JoinSet};
 use tracing::debug;
 
-use crate::{cookies::SecurePersist, db, Config};
+use crate::{Config, cookies::SecurePersist, db, errors};
 
 #[derive(Error, Debug)]
 pub enum LoginError {
@@ -47,6 +47,10 @@ pub enum LoginError {
 	NoVerifiedEmail,
 	#[error("No name")]
 	NoName,
+	#[error("Bad profile URL")]
+	BadProfileUrl,
+	#[error("No IndieAuth server")]
+	NoIndieAuthServer,
 }
 
 impl<'r, 'o: 'r> Responder<'r, 'o> for LoginError {
@@ -54,36 +58,50 @@ impl<'r, 'o: 'r> Responder<'r, 'o> for LoginError {
 		match self {
 			LoginError::DbError(e) => {
 				dbg!(e);
-				(Status::InternalServerError, "Database error").respond_to(request)
+				errors::caught(request, Status::InternalServerError, "Database error")
 			}
-			LoginError::NoProviderConfigured(p) => (
+			LoginError::NoProviderConfigured(p) => errors::caught(
+				request,
 				Status::InternalServerError,
 				format!("Provider {p} not configured"),
-			)
-				.respond_to(request),
+			),
 			LoginError::NoOAuthState => {
-				(Status::BadRequest, "Missing oauth state").respond_to(request)
+				errors::caught(request, Status::BadRequest, "Missing oauth state")
 			}
 			LoginError::BadOAuthState(e) => {
 				dbg!(e);
-				(Status::BadRequest, "Invalid oauth state").respond_to(request)
+				errors::caught(request, Status::BadRequest, "Invalid oauth state")
 			}
 			LoginError::BadExchangeCode(e) => {
 				dbg!(e);
-				(Status::Unauthorized, "Bad exchange code").respond_to(request)
-			}
-			LoginError::FailedAuthToken => {
-				(Status::InternalServerError, "Couldn't get auth token").respond_to(request)
+				errors::caught(request, Status::Unauthorized, "Bad exchange code")
 			}
+			LoginError::FailedAuthToken => errors::caught(
+				request,
+				Status::InternalServerError,
+				"Couldn't get auth token",
+			),
 			LoginError::BadAuthToken => {
-				(Status::Unauthorized, "Couldn't get auth token").respond_to(request)
-			}
-			LoginError::NoVerifiedEmail => {
-				(Status::Unauthorized, "A verified email is required").respond_to(request)
+				errors::caught(request, Status::Unauthorized, "Couldn't get auth token")
 			}
+			LoginError::NoVerifiedEmail => errors::caught(
+				request,
+				Status::Unauthorized,
+				"A verified email is required",
+			),
 			LoginError::NoName => {
-				(Status::Unauthorized, "A user name is required").respond_to(request)
+				errors::caught(request, Status::Unauthorized, "A user name is required")
 			}
+			LoginError::BadProfileUrl => errors::caught(
+				request,
+				Status::BadRequest,
+				"That isn't a usable profile URL",
+			),
+			LoginError::NoIndieAuthServer => errors::caught(
+				request,
+				Status::BadRequest,
+				"Couldn't find an IndieAuth server for that URL",
+			),
 		}
 	}
 }
@@ -108,7 +126,8 @@ pub struct OAuthProvider {
 #[derive(Serialize, Deserialize, Clone)]
 pub struct Identity {
 	pub sub: String,
-	pub email: String,
+	/// Always present for OIDC providers, but optional for website logins
+	pub email: Option<String>,
 	pub name: String,
 }
 
@@ -130,6 +149,13 @@ impl RocketGovernable<'_> for OAuthRateLimit {
 	}
 }
 
+/// Drop cached provider metadata, so it's fetched again on the next login.
+pub fn forget_providers() -> usize {
+	let amt = PROVIDERS.len();
+	PROVIDERS.clear();
+	amt
+}
+
 async fn provider(url_: &str) -> CoreProviderMetadata {
 	if let Some(p) = PROVIDERS.get(url_) {
 		return p.value().clone();
@@ -144,7 +170,7 @@ async fn provider(url_: &str) -> CoreProviderMetadata {
 }
 
 #[get("/clear")]
-pub async fn clear(mut secure_persist: SecurePersist, cookies: &CookieJar<'_>) -> String {
+pub fn clear(mut secure_persist: SecurePersist, cookies: &CookieJar<'_>) -> String {
 	let amt = secure_persist.identities.len();
 	secure_persist.identities.clear();
 	cookies.add_private(secure_persist);
@@ -294,7 +320,7 @@ pub async fn callback(
 
 	let identity = Identity {
 		sub: claims.subject().to_string(),
-		email: email.to_string(),
+		email: Some(email.to_string()),
 		name: name.to_string(),
 	};
 
@@ -319,7 +345,7 @@ impl<'r> FromRequest<'r> for Identities {
 		let cookies = request.cookies();
 		let Outcome::Success(mut secure_persist) = SecurePersist::from_request(request).await
 		else {
-			return Outcome::Success(Default::default());
+			return Outcome::Success(Identities::default());
 		};
 		let db: Pool<Postgres> = request.rocket().state::<Pool<Postgres>>().unwrap().clone();
 		let identities: Vec<(String, Result<Option<Identity>, _>)> = secure_persist
@@ -367,3 +393,33 @@ impl Deref for Identities {
 		&self.0
 	}
 }
+
+/// A visitor logged in with an identity listed in `Config.admins`.
+pub struct Admin {
+	pub provider: String,
+	pub identity: Identity,
+}
+
+#[async_trait]
+impl<'r> FromRequest<'r> for Admin {
+	type Error = ();
+
+	async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+		let Outcome::Success(identities) = request.guard::<Identities>().await else {
+			return Outcome::Error((Status::Forbidden, ()));
+		};
+		let cfg = request.rocket().state::<Arc<Config>>().unwrap();
+		identities
+			.0
+			.into_iter()
+			.find(|(provider, identity)| {
+				cfg.admins
+					.get(provider)
+					.is_some_and(|subs| subs.contains(&identity.sub))
+			})
+			.map_or(
+				Outcome::Error((Status::Forbidden, ())),
+				|(provider, identity)| Outcome::Success(Admin { provider, identity }),
+			)
+	}
+}
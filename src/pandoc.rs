@@ -2,14 +2,17 @@ use std::{
 	collections::HashSet,
 	fs::Permissions,
 	io::{Write, stderr},
+	iter::Peekable,
+	ops::RangeInclusive,
 	os::unix::fs::PermissionsExt,
-	path::Path,
+	path::{Path, PathBuf},
 	process::{Command, Stdio},
 	sync::Arc,
 };
 
-use pandoc_ast::{Attr, Block, Format, Inline, MetaValue, MutVisitor, Pandoc};
-use serde_json::json;
+use pandoc_ast::{Block, Format, Inline, MetaValue, MutVisitor, Pandoc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
 use sqlx::{Pool, Postgres, query};
 use tempfile::NamedTempFile;
 use tera::{Context, Tera};
@@ -20,31 +23,181 @@ use url::Url;
 use crate::{
 	Config,
 	cookies::ClientPersist,
-	db::{PostType, Search},
+	db::{PostType, Search, Toc},
+	highlight,
 };
 
-pub async fn md_to_ast(file: &impl AsRef<Path>, config: &Config) -> Option<Pandoc> {
+/// How to turn one kind of source file into a post.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Reader {
+	/// The pandoc reader to use, like `org` or `rst`
+	pub format: String,
+	/// Pandoc extensions to turn on or off for this reader, like `+smart`
+	#[serde(default)]
+	pub extensions: Vec<String>,
+	/// Also read metadata from a YAML file next to the post, for formats with
+	/// no good place to put it
+	#[serde(default)]
+	pub metadata_file: bool,
+}
+
+impl Reader {
+	/// The sidecar metadata file for a post, if this format uses one.
+	pub fn metadata_file(&self, file: &Path) -> Option<PathBuf> {
+		self.metadata_file.then(|| file.with_extension("yaml"))
+	}
+}
+
+/// How pandoc writes posts out as HTML. Set for the whole site in the
+/// config, and for one post under `writer` in its frontmatter.
+#[serde_with::serde_as]
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct Writer {
+	pub math: MathMethod,
+	/// The server to render math with when `math = "webtex"`, or pandoc's default
+	pub webtex_url: Option<String>,
+	/// `catppuccin` to match the site, a pandoc style name or `.theme` file,
+	/// or `none` to leave code plain
+	pub highlight_style: String,
+	/// Wrap each section in a `<section>`
+	#[serde_as(as = "serde_with::PickFirst<(_, serde_with::DisplayFromStr)>")]
+	pub section_divs: bool,
+	/// Shift every heading down this many levels, so a post's top level
+	/// headings can sit under the page title
+	#[serde_as(as = "serde_with::PickFirst<(_, serde_with::DisplayFromStr)>")]
+	pub heading_offset: i8,
+	pub footnotes: FootnotePlacement,
+}
+
+impl Default for Writer {
+	fn default() -> Self {
+		Writer {
+			math: MathMethod::default(),
+			webtex_url: None,
+			highlight_style: highlight::CATPPUCCIN.to_string(),
+			section_divs: false,
+			heading_offset: 0,
+			footnotes: FootnotePlacement::default(),
+		}
+	}
+}
+
+impl Writer {
+	/// These options with a post's frontmatter laid over the top.
+	pub fn merged(&self, overrides: &serde_json::Map<String, Value>) -> serde_json::Result<Writer> {
+		let Value::Object(mut writer) = serde_json::to_value(self)? else {
+			unreachable!("Writer is a struct");
+		};
+		writer.extend(overrides.clone());
+		serde_json::from_value(Value::Object(writer))
+	}
+
+	/// The stylesheet highlighted code needs, if it's highlighted at all.
+	pub fn highlight_css(&self) -> Option<String> {
+		(self.highlight_style != "none").then(|| {
+			let style: String =
+				url::form_urlencoded::byte_serialize(self.highlight_style.as_bytes()).collect();
+			format!("/highlight.css?style={style}")
+		})
+	}
+
+	fn args(&self) -> Vec<String> {
+		let mut args = vec![match (self.math, &self.webtex_url) {
+			(MathMethod::Mathml, _) => "--mathml".to_string(),
+			(MathMethod::Webtex, Some(url)) => format!("--webtex={url}"),
+			(MathMethod::Webtex, None) => "--webtex".to_string(),
+			// Pandoc writes TeX as best it can in plain HTML by default
+			(MathMethod::Plain, _) => String::new(),
+		}];
+		match self.highlight_style.as_str() {
+			"none" => args.push("--no-highlight".to_string()),
+			// Only standalone documents get pandoc's styles, so this just
+			// needs to be something pandoc knows
+			highlight::CATPPUCCIN => {}
+			style => args.extend(["--highlight-style".to_string(), style.to_string()]),
+		}
+		if self.section_divs {
+			args.push("--section-divs".to_string());
+		}
+		if self.heading_offset != 0 {
+			args.push(format!("--shift-heading-level-by={}", self.heading_offset));
+		}
+		args.push(format!("--reference-location={}", self.footnotes));
+		args.retain(|a| !a.is_empty());
+		args
+	}
+}
+
+/// How TeX math in posts is shown.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum MathMethod {
+	/// Written out as `MathML`, which browsers draw themselves
+	#[default]
+	Mathml,
+	/// Rendered to images by a `WebTeX` server
+	Webtex,
+	/// Left as text
+	Plain,
+}
+
+/// Where footnotes go.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, strum::Display)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum FootnotePlacement {
+	/// All together at the end of the post
+	#[default]
+	Document,
+	/// At the end of the section they're in
+	Section,
+	/// After the paragraph or other block they're in
+	Block,
+}
+
+pub async fn source_to_ast(
+	file: &impl AsRef<Path>,
+	reader: &Reader,
+	config: &Config,
+) -> Result<Pandoc, String> {
 	let file = file.as_ref();
-	let pandoc = tokio::process::Command::new("pandoc")
-		.arg(format!("-fmarkdown{}", config.markdown_extensions.join("")))
-		.arg("-tjson")
+	// The top-level option predates per-format readers
+	let legacy = if reader.format == "markdown" {
+		config.markdown_extensions.join("")
+	} else {
+		String::new()
+	};
+	let mut command = tokio::process::Command::new("pandoc");
+	command
+		.arg(format!(
+			"-f{}{legacy}{}",
+			reader.format,
+			reader.extensions.join("")
+		))
+		.arg("-tjson");
+	if let Some(metadata) = reader.metadata_file(file).filter(|m| m.is_file()) {
+		command.arg("--metadata-file").arg(metadata);
+	}
+	let pandoc = command
 		.arg(file.as_os_str())
 		.stdin(Stdio::null())
 		.stdout(Stdio::piped())
+		.stderr(Stdio::piped())
 		.output()
 		.await
-		.inspect_err(|e| eprintln!("Failed to get pandoc ast from {file:?} with {e}"))
-		.ok()?;
+		.map_err(|e| format!("Couldn't run pandoc: {e}"))?;
 	if !pandoc.status.success() {
-		return None;
+		return Err(String::from_utf8_lossy(&pandoc.stderr).into_owned());
 	}
-	let pandoc = String::from_utf8(pandoc.stdout).ok()?;
-	Some(Pandoc::from_json(&pandoc))
+	let pandoc = String::from_utf8(pandoc.stdout).map_err(|e| e.to_string())?;
+	Ok(Pandoc::from_json(&pandoc))
 }
 
-pub async fn ast_to_html(ast: Pandoc) -> Option<String> {
+pub async fn ast_to_html(ast: Pandoc, writer: &Writer) -> Option<String> {
 	let mut pandoc = tokio::process::Command::new("pandoc")
-		.args(["-fjson", "-thtml", "--highlight-style", "kate"])
+		.args(["-fjson", "-thtml"])
+		.args(writer.args())
 		.stdin(Stdio::piped())
 		.stdout(Stdio::piped())
 		.spawn()
@@ -64,6 +217,165 @@ pub async fn ast_to_html(ast: Pandoc) -> Option<String> {
 	Some(pandoc)
 }
 
+/// A table of contents built from the headings of a post, leaving out any
+/// marked `.unlisted` the way pandoc's own does.
+pub fn table_of_contents(ast: &Pandoc, depths: RangeInclusive<i64>) -> Vec<Toc> {
+	fn headings(
+		blocks: &[Block],
+		depths: &RangeInclusive<i64>,
+		found: &mut Vec<(i64, String, String)>,
+	) {
+		for block in blocks {
+			match block {
+				Block::Header(level, (anchor, classes, _), contents)
+					if depths.contains(level) && !classes.iter().any(|c| c == "unlisted") =>
+				{
+					let label = contents.iter().map(inline_text).collect::<String>();
+					found.push((*level, anchor.clone(), label.trim().to_string()));
+				}
+				// Sections are divs, but quotes and the like aren't part of the outline
+				Block::Div(_, contents) => headings(contents, depths, found),
+				_ => {}
+			}
+		}
+	}
+	fn nest(
+		found: &mut Peekable<impl Iterator<Item = (i64, String, String)>>,
+		level: i64,
+	) -> Vec<Toc> {
+		let mut toc = vec![];
+		while let Some((next, ..)) = found.peek()
+			&& *next >= level
+		{
+			let (next, anchor, label) = found.next().unwrap();
+			let subheadings = nest(found, next + 1);
+			toc.push(if anchor.is_empty() && subheadings.is_empty() {
+				Toc::Text(label)
+			} else {
+				Toc::Heading {
+					label,
+					anchor,
+					subheadings,
+				}
+			});
+		}
+		toc
+	}
+	let mut found = vec![];
+	headings(&ast.blocks, &depths, &mut found);
+	nest(&mut found.into_iter().peekable(), i64::MIN)
+}
+
+/// The text of some inline content, without any formatting.
+fn inline_text(inline: &Inline) -> String {
+	match inline {
+		Inline::Str(s) | Inline::Code(_, s) | Inline::Math(_, s) => s.clone(),
+		Inline::Space | Inline::SoftBreak | Inline::LineBreak => " ".to_string(),
+		Inline::Emph(contents)
+		| Inline::Underline(contents)
+		| Inline::Strong(contents)
+		| Inline::Strikeout(contents)
+		| Inline::Superscript(contents)
+		| Inline::Subscript(contents)
+		| Inline::SmallCaps(contents)
+		| Inline::Quoted(_, contents)
+		| Inline::Cite(_, contents)
+		| Inline::Link(_, contents, _)
+		| Inline::Image(_, contents, _)
+		| Inline::Span(_, contents) => contents.iter().map(inline_text).collect(),
+		Inline::RawInline(..) | Inline::Note(_) => String::new(),
+	}
+}
+
+/// Write a post out in another pandoc format, like `markdown` or `plain`,
+/// with its metadata.
+pub async fn ast_to_format(ast: &Pandoc, format: &str) -> Option<String> {
+	let mut pandoc = tokio::process::Command::new("pandoc")
+		.args(["-fjson", &format!("-t{format}"), "--standalone"])
+		.stdin(Stdio::piped())
+		.stdout(Stdio::piped())
+		.spawn()
+		.ok()?;
+	pandoc
+		.stdin
+		.as_mut()
+		.unwrap()
+		.write_all(serde_json::to_string(ast).unwrap().as_bytes())
+		.await
+		.ok()?;
+	let pandoc = pandoc.wait_with_output().await.ok()?;
+	if !pandoc.status.success() {
+		return None;
+	}
+	String::from_utf8(pandoc.stdout).ok()
+}
+
+/// Flatten anything in a visitor's document that could load resources, run
+/// scripts or mess with the page's outline into plain text.
+fn sanitize(ast: &mut Pandoc) {
+	struct SanitizeVisitor;
+	impl MutVisitor for SanitizeVisitor {
+		fn visit_inline(&mut self, inline: &mut Inline) {
+			self.walk_inline(inline);
+			match inline {
+				Inline::Image(_, alt, _) => {
+					*inline = Inline::Span(Default::default(), std::mem::take(alt));
+				}
+				Inline::Link(attr, contents, (target, _)) => {
+					let safe = Url::parse(target)
+						.is_ok_and(|u| matches!(u.scheme(), "http" | "https" | "mailto"));
+					if safe {
+						*attr = (
+							String::new(),
+							vec![],
+							vec![("rel".to_string(), "nofollow ugc".to_string())],
+						);
+					} else {
+						*inline = Inline::Span(Default::default(), std::mem::take(contents));
+					}
+				}
+				Inline::RawInline(_, _) => *inline = Inline::Str(String::new()),
+				_ => {}
+			}
+		}
+
+		fn visit_block(&mut self, block: &mut Block) {
+			self.walk_block(block);
+			match block {
+				Block::Header(_, _, contents) => *block = Block::Para(std::mem::take(contents)),
+				Block::RawBlock(_, _) => *block = Block::Null,
+				_ => {}
+			}
+		}
+	}
+	SanitizeVisitor.walk_pandoc(ast);
+}
+
+/// Render markdown written by a visitor. Raw HTML is turned off in the
+/// reader, and the result is sanitized before it's written out.
+pub async fn render_untrusted(markdown: &str) -> Option<String> {
+	let mut pandoc = tokio::process::Command::new("pandoc")
+		.args(["-fcommonmark-raw_html", "-tjson"])
+		.stdin(Stdio::piped())
+		.stdout(Stdio::piped())
+		.spawn()
+		.ok()?;
+	pandoc
+		.stdin
+		.as_mut()
+		.unwrap()
+		.write_all(markdown.as_bytes())
+		.await
+		.ok()?;
+	let pandoc = pandoc.wait_with_output().await.ok()?;
+	if !pandoc.status.success() {
+		return None;
+	}
+	let mut ast = Pandoc::from_json(&String::from_utf8(pandoc.stdout).ok()?);
+	sanitize(&mut ast);
+	ast_to_html(ast, &Writer::default()).await
+}
+
 pub async fn run_preproc_filters(
 	db: &Pool<Postgres>,
 	ast: Pandoc,
@@ -80,31 +392,31 @@ pub async fn run_postproc_filters(
 	db: &Pool<Postgres>,
 	tera: &Arc<RwLock<Tera>>,
 	ast: Pandoc,
-	_path: &str,
+	path: &str,
 	cookie: &ClientPersist,
 	config: &Config,
 ) -> Pandoc {
 	// let ast = attach_mentioners(db, ast, path).await;
 	let ast = include(db, tera, ast).await;
 
-	frag_search_results(db, tera, ast, cookie).await
+	frag_search_results(db, tera, ast, path, cookie, &config.reactions).await
 }
 
 fn find_links(mut ast: Pandoc) -> Pandoc {
 	struct LinkVisitor(Vec<String>, PostType);
 	impl MutVisitor for LinkVisitor {
 		fn visit_inline(&mut self, inline: &mut Inline) {
-			if let Inline::Link((_, classes, _), _contents, (target, _)) = inline {
-				if classes.iter().any(|c| c == "mention") {
-					self.0.push((*target).to_string());
-					for class in classes.iter() {
-						if matches!(self.1, PostType::Note) {
-							self.1 = match class.as_str() {
-								"u-like-of" => PostType::Like,
-								"u-repost-of" => PostType::Repost,
-								"u-in-reply-to" => PostType::Reply,
-								_ => PostType::Note,
-							}
+			if let Inline::Link((_, classes, _), _contents, (target, _)) = inline
+				&& classes.iter().any(|c| c == "mention")
+			{
+				self.0.push(target.clone());
+				for class in classes.iter() {
+					if matches!(self.1, PostType::Note) {
+						self.1 = match class.as_str() {
+							"u-like-of" => PostType::Like,
+							"u-repost-of" => PostType::Repost,
+							"u-in-reply-to" => PostType::Reply,
+							_ => PostType::Note,
 						}
 					}
 				}
@@ -166,14 +478,13 @@ async fn resolve_wikilinks(mut ast: Pandoc, db: &Pool<Postgres>, path: &str) ->
 			if let Inline::Link(_, _, (target, t)) = inline
 				&& t == "wikilink"
 			{
-				let handle = Handle::current();
 				let Ok(matching) = self
 					.handle
 					.block_on(crate::db::match_wikilink(&self.db, target))
 				else {
 					return;
 				};
-				let [(destination, dest_meta), rest @ ..] = matching.as_slice() else {
+				let [(destination, _), rest @ ..] = matching.as_slice() else {
 					error!(
 						"Link to {target} in {} doesn't have any potential destinations!",
 						self.path
@@ -205,21 +516,17 @@ async fn resolve_wikilinks(mut ast: Pandoc, db: &Pool<Postgres>, path: &str) ->
 		handle: Handle::current(),
 		in_meta: false,
 	};
-	let ast = tokio::task::spawn_blocking(move || {
+	tokio::task::spawn_blocking(move || {
 		visitor.walk_pandoc(&mut ast);
 		ast
 	})
 	.await
-	.unwrap();
-
-	ast
+	.unwrap()
 }
 
-fn dynamic(mut ast: Pandoc, config: &Config) -> Pandoc {
-	struct DynamicVisitor<'a> {
-		config: &'a Config,
-	}
-	impl<'a> MutVisitor for DynamicVisitor<'a> {
+fn dynamic(mut ast: Pandoc, _config: &Config) -> Pandoc {
+	struct DynamicVisitor;
+	impl MutVisitor for DynamicVisitor {
 		fn visit_block(&mut self, block: &mut Block) {
 			if let Block::CodeBlock((_, classes, attr), contents) = block {
 				if !classes.iter().any(|c| c == "dynamic") {
@@ -260,7 +567,7 @@ fn dynamic(mut ast: Pandoc, config: &Config) -> Pandoc {
 			self.walk_block(block);
 		}
 	}
-	(DynamicVisitor { config }).walk_pandoc(&mut ast);
+	DynamicVisitor.walk_pandoc(&mut ast);
 	ast
 }
 
@@ -268,9 +575,18 @@ async fn frag_search_results(
 	db: &Pool<Postgres>,
 	tera: &Arc<RwLock<Tera>>,
 	mut ast: Pandoc,
+	path: &str,
 	cookie: &ClientPersist,
+	reactions: &[String],
 ) -> Pandoc {
-	struct FragSearchVisitor(Handle, Pool<Postgres>, Arc<RwLock<Tera>>, ClientPersist);
+	struct FragSearchVisitor(
+		Handle,
+		Pool<Postgres>,
+		Arc<RwLock<Tera>>,
+		ClientPersist,
+		Vec<String>,
+		String,
+	);
 	impl MutVisitor for FragSearchVisitor {
 		fn visit_block(&mut self, block: &mut Block) {
 			if let Block::CodeBlock((_, classes, _), contents) = block {
@@ -289,9 +605,20 @@ async fn frag_search_results(
 				};
 
 				let search_url: Url = (&search_spec).into();
+				let paths: Vec<_> = search.iter().map(|(path, _)| path.clone()).collect();
+				let reactions = self
+					.0
+					.block_on(crate::reactions::counts(&self.4, &self.1, &paths))
+					.unwrap_or_default();
+				let views = self
+					.0
+					.block_on(crate::db::post_views(&self.1, &paths))
+					.unwrap_or_default();
 
 				let ctx = json!({
 					"articles": search,
+					"reactions": reactions,
+					"views": views,
 					"search_qs": search_url.query().unwrap_or(""),
 					"cookie": &self.3,
 					"new": search.iter().filter(|(path, meta)| {
@@ -299,6 +626,7 @@ async fn frag_search_results(
 						*viewed < meta.updated
 					}).map(|(p, _)| p).collect::<HashSet<_>>(),
 					"search": search_spec,
+					"path": &self.5,
 				});
 				let ctx = Context::from_serialize(ctx).unwrap();
 
@@ -313,8 +641,14 @@ async fn frag_search_results(
 			}
 		}
 	}
-	let mut visitor =
-		FragSearchVisitor(Handle::current(), db.clone(), tera.clone(), cookie.clone());
+	let mut visitor = FragSearchVisitor(
+		Handle::current(),
+		db.clone(),
+		tera.clone(),
+		cookie.clone(),
+		reactions.to_vec(),
+		path.to_string(),
+	);
 
 	tokio::task::spawn_blocking(move || {
 		visitor.walk_pandoc(&mut ast);
@@ -433,3 +767,101 @@ async fn attach_mentioners(db: &Pool<Postgres>, mut ast: Pandoc, path: &str) ->
 
 	ast
 }
+
+#[cfg(test)]
+mod tests {
+	use serde_json::{Value, json};
+
+	use super::*;
+
+	fn sanitized(blocks: &Value) -> Value {
+		let mut ast = Pandoc::from_json(
+			&json!({"pandoc-api-version": [1, 23, 1], "meta": {}, "blocks": blocks}).to_string(),
+		);
+		sanitize(&mut ast);
+		serde_json::from_str::<Value>(&ast.to_json()).unwrap()["blocks"].clone()
+	}
+
+	fn text(s: &str) -> Value {
+		json!([{"t": "Str", "c": s}])
+	}
+
+	fn plain(attr: &Value, contents: &Value) -> Value {
+		json!({"t": "Span", "c": [attr, contents]})
+	}
+
+	#[test]
+	fn visitor_markup_is_flattened() {
+		let none = json!(["", [], []]);
+		let blocks = json!([
+			{"t": "Header", "c": [1, ["top", [], []], text("Big")]},
+			{"t": "RawBlock", "c": ["html", "<iframe src=\"https://evil.example\">"]},
+			{"t": "Para", "c": [
+				{"t": "Image", "c": [none, text("alt"), ["https://evil.example/track.png", ""]]},
+				{"t": "Link", "c": [["", ["button"], [["onclick", "steal()"]]], text("run"), ["javascript:steal()", ""]]},
+				{"t": "Link", "c": [none, text("admin"), ["/admin", ""]]},
+				{"t": "RawInline", "c": ["html", "<script>steal()</script>"]},
+			]},
+		]);
+		assert_eq!(
+			sanitized(&blocks),
+			json!([
+				{"t": "Para", "c": text("Big")},
+				{"t": "Null"},
+				{"t": "Para", "c": [
+					plain(&none, &text("alt")),
+					plain(&none, &text("run")),
+					plain(&none, &text("admin")),
+					{"t": "Str", "c": ""},
+				]},
+			])
+		);
+	}
+
+	#[test]
+	fn web_links_are_kept_but_marked() {
+		let blocks = json!([{"t": "Para", "c": [
+			{"t": "Link", "c": [["me", ["u-url"], [["rel", "me"]]], text("site"), ["https://example.com/", ""]]},
+			{"t": "Link", "c": [["", [], []], text("mail"), ["mailto:me@example.com", ""]]},
+		]}]);
+		let rel = json!(["", [], [["rel", "nofollow ugc"]]]);
+		assert_eq!(
+			sanitized(&blocks),
+			json!([{"t": "Para", "c": [
+				{"t": "Link", "c": [rel, text("site"), ["https://example.com/", ""]]},
+				{"t": "Link", "c": [rel, text("mail"), ["mailto:me@example.com", ""]]},
+			]}])
+		);
+	}
+
+	#[test]
+	fn tables_of_contents_nest_headings_as_plain_text() {
+		let heading = |level: i64, anchor: &str, classes: Value, label: &str| json!({"t": "Header", "c": [level, [anchor, classes, []], text(label)]});
+		let ast = Pandoc::from_json(
+			&json!({"pandoc-api-version": [1, 23, 1], "meta": {}, "blocks": [
+				heading(1, "title", json!([]), "Title"),
+				heading(2, "fish", json!([]), "Fish & <chips>"),
+				heading(3, "cod", json!([]), "Cod"),
+				heading(3, "secret", json!(["unlisted"]), "Secret"),
+				heading(4, "deep", json!([]), "Too deep"),
+				{"t": "Div", "c": [["", ["section"], []], [heading(2, "", json!([]), "Peas")]]},
+			]})
+			.to_string(),
+		);
+		let toc = table_of_contents(&ast, 2..=3);
+		assert_eq!(
+			serde_json::to_value(&toc).unwrap(),
+			json!([
+				{"Heading": {"label": "Fish & <chips>", "anchor": "fish", "subheadings": [
+					{"Heading": {"label": "Cod", "anchor": "cod", "subheadings": []}},
+				]}},
+				{"Text": "Peas"},
+			])
+		);
+		assert_eq!(
+			toc.iter().map(ToString::to_string).collect::<String>(),
+			"<li><a href=\"#fish\">Fish &amp; &lt;chips&gt;</a><ul>\
+			 <li><a href=\"#cod\">Cod</a></li></ul></li><li>Peas</li>"
+		);
+	}
+}
@@ -0,0 +1,45 @@
+use std::sync::Arc;
+
+use rocket::{
+	State,
+	form::Form,
+	http::CookieJar,
+	response::{Redirect, content::RawHtml},
+};
+use tera::Tera;
+use tokio::sync::RwLock;
+
+use crate::{Config, cookies::Preferences, local_redirect, templates::RenderError};
+
+#[get("/?<return_to>")]
+pub async fn display(
+	return_to: Option<&str>,
+	config: &State<Arc<Config>>,
+	prefs: Preferences,
+	tera: &State<Arc<RwLock<Tera>>>,
+) -> Result<RawHtml<String>, RenderError> {
+	let content = crate::templates::render(
+		tera,
+		config,
+		"preferences.html.tera",
+		&crate::context!({
+			"prefs": prefs,
+			"return_to": return_to.filter(|r| r.starts_with('/')),
+		}),
+	)
+	.await?;
+	Ok(RawHtml(content))
+}
+
+#[derive(FromForm)]
+pub struct PreferencesForm {
+	pub prefs: Preferences,
+	pub return_to: Option<String>,
+}
+
+#[post("/", data = "<form>")]
+pub fn save(form: Form<PreferencesForm>, jar: &CookieJar<'_>) -> Redirect {
+	let PreferencesForm { prefs, return_to } = form.into_inner();
+	jar.add(prefs);
+	local_redirect(return_to.as_deref(), "/preferences")
+}
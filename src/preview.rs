@@ -0,0 +1,141 @@
+use std::{path::Path, sync::Arc};
+
+use chrono::{TimeDelta, Utc};
+use openidconnect::CsrfToken;
+use rocket::{
+	State,
+	form::Form,
+	response::{Flash, Redirect},
+};
+use sqlx::{Pool, Postgres};
+
+use crate::{Config, db, oauth};
+
+/// Preview links last a week unless asked otherwise.
+pub const DEFAULT_DAYS: u16 = 7;
+
+/// Make a link that shows the draft at `path` to anyone who has it, until it
+/// expires after `days`. `path` may also name the post's source file.
+/// Returns `None` if there's no such post.
+pub async fn issue(
+	cfg: &Config,
+	db: &Pool<Postgres>,
+	path: &str,
+	days: u16,
+	note: Option<&str>,
+) -> Result<Option<String>, sqlx::Error> {
+	let path = db::trim_path(cfg, Path::new(path));
+	if db::read_post_meta(db, &path).await.is_none() {
+		return Ok(None);
+	}
+	let token = CsrfToken::new_random_len(32).secret().clone();
+	let expires = Utc::now() + TimeDelta::days(i64::from(days));
+	db::add_preview_link(db, &token, &path, note, expires).await?;
+	Ok(Some(format!("{}post/{path}?preview={token}", cfg.origin)))
+}
+
+/// Whether someone may read a post. Published posts are for everyone, but
+/// drafts need an admin, a valid preview link or develop mode.
+pub async fn can_view(
+	cfg: &Config,
+	db: &Pool<Postgres>,
+	path: &str,
+	meta: &db::ArticleMeta,
+	token: Option<&str>,
+	admin: bool,
+) -> bool {
+	if meta.ready || cfg.develop || admin {
+		return true;
+	}
+	let Some(token) = token else {
+		return false;
+	};
+	db::preview_link_valid(db, token, path)
+		.await
+		.unwrap_or(false)
+}
+
+#[derive(FromForm)]
+pub struct PreviewForm {
+	path: String,
+	#[field(default = DEFAULT_DAYS, validate = range(1..=365))]
+	days: u16,
+	note: Option<String>,
+}
+
+#[post("/", data = "<form>")]
+pub async fn create(
+	_admin: oauth::Admin,
+	form: Form<PreviewForm>,
+	db: &State<Pool<Postgres>>,
+	cfg: &State<Arc<Config>>,
+) -> Flash<Redirect> {
+	let note = form
+		.note
+		.as_deref()
+		.map(str::trim)
+		.filter(|n| !n.is_empty());
+	match issue(cfg, db, &form.path, form.days, note).await {
+		Ok(Some(url)) => Flash::success(
+			Redirect::to("/admin"),
+			format!(
+				"Preview link for {}, valid for {} days: {url}",
+				form.path, form.days
+			),
+		),
+		Ok(None) => Flash::error(
+			Redirect::to("/admin"),
+			format!("There's no post at {}", form.path),
+		),
+		Err(e) => Flash::error(
+			Redirect::to("/admin"),
+			format!("Couldn't make a preview link: {e}"),
+		),
+	}
+}
+
+#[post("/<id>/revoke")]
+pub async fn revoke(_admin: oauth::Admin, id: i32, db: &State<Pool<Postgres>>) -> Flash<Redirect> {
+	match db::revoke_preview_link(db, id).await {
+		Ok(0) => Flash::error(Redirect::to("/admin"), "That preview link is already gone"),
+		Ok(_) => Flash::success(Redirect::to("/admin"), "Revoked the preview link"),
+		Err(e) => Flash::error(
+			Redirect::to("/admin"),
+			format!("Couldn't revoke the preview link: {e}"),
+		),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::time::Duration;
+
+	use sqlx::postgres::PgPoolOptions;
+
+	use super::*;
+
+	#[tokio::test]
+	async fn drafts_need_a_valid_preview_link() {
+		let cfg = Config::for_tests();
+		// Nothing listens here, so checking a preview link always fails
+		let db = PgPoolOptions::new()
+			.acquire_timeout(Duration::from_millis(200))
+			.connect_lazy("postgres://localhost:1/wolog")
+			.unwrap();
+		let draft = db::ArticleMeta::default();
+		let published = db::ArticleMeta {
+			ready: true,
+			..db::ArticleMeta::default()
+		};
+		assert!(can_view(&cfg, &db, "post", &published, None, false).await);
+		assert!(can_view(&cfg, &db, "post", &draft, None, true).await);
+		assert!(!can_view(&cfg, &db, "post", &draft, None, false).await);
+		// Links that can't be checked don't let anyone in
+		assert!(!can_view(&cfg, &db, "post", &draft, Some("token"), false).await);
+		let develop = Config {
+			develop: true,
+			..Config::for_tests()
+		};
+		assert!(can_view(&develop, &db, "post", &draft, None, false).await);
+	}
+}
@@ -0,0 +1,100 @@
+use std::{collections::HashMap, path::PathBuf, sync::Arc};
+
+use rocket::{State, form::Form, response::Redirect};
+use rocket_governor::RocketGovernor;
+use serde::Serialize;
+use sqlx::{Pool, Postgres};
+
+use crate::{
+	Config, db,
+	guestbook::{self, GuestbookError, GuestbookRateLimit},
+	oauth::Identities,
+};
+
+/// One of the configured reactions, as shown under a post.
+#[derive(Serialize)]
+pub struct Reaction {
+	pub emoji: String,
+	pub count: i64,
+	/// Whether one of the visitor's identities left this reaction
+	pub mine: bool,
+}
+
+/// Reaction counts for each of `paths`, in the configured order. Posts
+/// without reactions and reactions that are no longer offered are left out.
+pub async fn counts(
+	choices: &[String],
+	db: &Pool<Postgres>,
+	paths: &[String],
+) -> Result<HashMap<String, Vec<(String, i64)>>, sqlx::Error> {
+	Ok(db::reaction_counts(db, paths)
+		.await?
+		.into_iter()
+		.map(|(path, counts)| {
+			let counts = choices
+				.iter()
+				.filter_map(|emoji| Some((emoji.clone(), *counts.get(emoji)?)))
+				.collect();
+			(path, counts)
+		})
+		.collect())
+}
+
+/// Every configured reaction for a post, with the visitor's own marked.
+pub async fn for_post(
+	cfg: &Config,
+	db: &Pool<Postgres>,
+	path: &str,
+	identities: &Identities,
+) -> Result<Vec<Reaction>, sqlx::Error> {
+	let mut counts = db::reaction_counts(db, &[path.to_string()]).await?;
+	let counts = counts.remove(path).unwrap_or_default();
+	let mut mine = Vec::new();
+	for (provider, identity) in identities.iter() {
+		mine.extend(db::own_reactions(db, path, provider, &identity.sub).await?);
+	}
+	Ok(cfg
+		.reactions
+		.iter()
+		.map(|emoji| Reaction {
+			emoji: emoji.clone(),
+			count: counts.get(emoji).copied().unwrap_or(0),
+			mine: mine.contains(emoji),
+		})
+		.collect())
+}
+
+#[derive(FromForm)]
+pub struct ReactionForm {
+	pub identity: String,
+	pub emoji: String,
+}
+
+/// Toggle a reaction, then go back to the post. Reacting shares the
+/// guestbook's rate limit and bans.
+#[post("/<path..>", data = "<form>")]
+pub async fn react(
+	form: Form<ReactionForm>,
+	path: PathBuf,
+	db: &State<Pool<Postgres>>,
+	identities: Identities,
+	config: &State<Arc<Config>>,
+	_rl: RocketGovernor<'_, GuestbookRateLimit>,
+) -> Result<Redirect, GuestbookError> {
+	let path = &db::trim_path(config, &path);
+	if !db::read_post_meta(db, path)
+		.await
+		.is_some_and(|m| m.ready || config.develop)
+	{
+		return Err(GuestbookError::NotFound);
+	}
+	let Some(identity) = identities.get(&form.identity) else {
+		return Err(GuestbookError::NoIdentity);
+	};
+	if !config.reactions.contains(&form.emoji) {
+		return Err(GuestbookError::UnknownReaction);
+	}
+	guestbook::check_bans(config, db, &form.identity, identity).await?;
+	db::toggle_reaction(db, path, &form.identity, &identity.sub, &form.emoji).await?;
+	Ok(Redirect::to(format!("/post/{path}#reactions")))
+}
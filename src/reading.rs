@@ -0,0 +1,148 @@
+use std::{
+	collections::{HashMap, HashSet},
+	sync::Arc,
+};
+
+use chrono::Utc;
+use openidconnect::CsrfToken;
+use rocket::{
+	State,
+	form::Form,
+	http::{ContentType, CookieJar},
+	response::{Redirect, content::RawHtml},
+};
+use sqlx::{Pool, Postgres};
+use tera::Tera;
+use tokio::sync::RwLock;
+
+use crate::{
+	Config,
+	analytics::{self, ViewKind, Visit},
+	cookies::{ClientPersist, Preferences},
+	db,
+	errors::AppError,
+	local_redirect,
+};
+
+/// How many posts go in a visitor's unread feed.
+const FEED_LEN: i64 = 32;
+
+#[get("/")]
+pub async fn display(
+	cookie: ClientPersist,
+	db: &State<Pool<Postgres>>,
+	tera: &State<Arc<RwLock<Tera>>>,
+	config: &State<Arc<Config>>,
+	prefs: Preferences,
+) -> Result<RawHtml<String>, AppError> {
+	let bookmarks = db::bookmarks(db, &cookie.id).await?;
+	let feed_token = db::visitor_feed_token(db, &cookie.id).await?;
+	let content = crate::templates::render(
+		tera,
+		config,
+		"reading-list.html.tera",
+		&crate::context!({
+			"prefs": prefs,
+			"articles": bookmarks,
+			"new": bookmarks.iter().filter(|(path, meta)| {
+				cookie.viewed.get(path).is_none_or(|viewed| *viewed < meta.updated)
+			}).map(|(p, _)| p).collect::<HashSet<_>>(),
+			"feed_url": feed_token.map(|t| format!("{}feed/unread/{t}", config.origin)),
+		}),
+	)
+	.await?;
+	Ok(RawHtml(content))
+}
+
+#[derive(FromForm)]
+pub struct BookmarkForm {
+	pub path: String,
+	pub save: bool,
+	pub return_to: Option<String>,
+}
+
+#[post("/", data = "<form>")]
+pub async fn bookmark(
+	form: Form<BookmarkForm>,
+	cookie: ClientPersist,
+	jar: &CookieJar<'_>,
+	db: &State<Pool<Postgres>>,
+) -> Result<Redirect, AppError> {
+	// Drafts can't be saved, so they don't show up in anyone's list later
+	if form.save
+		&& !db::read_post_meta(db, &form.path)
+			.await
+			.is_some_and(|m| m.ready)
+	{
+		return Err(AppError::NotFound);
+	}
+	db::set_bookmark(db, &cookie.id, &form.path, form.save).await?;
+	jar.add(cookie);
+	Ok(local_redirect(form.return_to.as_deref(), "/reading-list"))
+}
+
+#[derive(FromForm)]
+pub struct MarkReadForm {
+	#[field(validate = len(..=1000))]
+	pub path: Vec<String>,
+	pub return_to: Option<String>,
+}
+
+#[post("/mark-read", data = "<form>")]
+pub async fn mark_read(
+	form: Form<MarkReadForm>,
+	cookie: ClientPersist,
+	jar: &CookieJar<'_>,
+	db: &State<Pool<Postgres>>,
+) -> Result<Redirect, AppError> {
+	let today = Utc::now().date_naive();
+	let views: HashMap<_, _> = form.path.iter().map(|p| (p.clone(), today)).collect();
+	db::record_views(db, &cookie.id, &views).await?;
+	jar.add(cookie);
+	Ok(local_redirect(form.return_to.as_deref(), "/reading-list"))
+}
+
+/// Make a new unread feed URL, which also stops the old one working.
+#[post("/feed")]
+pub async fn rotate_feed(
+	cookie: ClientPersist,
+	jar: &CookieJar<'_>,
+	db: &State<Pool<Postgres>>,
+) -> Result<Redirect, AppError> {
+	let token = CsrfToken::new_random_len(32).secret().clone();
+	db::set_visitor_feed_token(db, &cookie.id, &token).await?;
+	jar.add(cookie);
+	Ok(Redirect::to("/reading-list#feed"))
+}
+
+#[get("/<token>")]
+pub async fn unread_feed(
+	token: &str,
+	db: &State<Pool<Postgres>>,
+	tera: &State<Arc<RwLock<Tera>>>,
+	config: &State<Arc<Config>>,
+	visit: Visit,
+) -> Result<(ContentType, String), AppError> {
+	let visitor = db::visitor_by_feed_token(db, token)
+		.await?
+		.ok_or(AppError::NotFound)?;
+	// Feeds are counted together, since the token is a secret
+	analytics::record(db, config, visit, ViewKind::Feed, "feed/unread");
+	let unread = db::unread_posts(db, &visitor, FEED_LEN).await?;
+	let content = tera
+		.read()
+		.await
+		.render(
+			"page-list.atom.tera",
+			&crate::context!({
+				"articles": unread,
+				"url": format!("{}feed/unread/{token}", config.origin),
+				"title": format!("{} (Unread)", config.site_name),
+				"icon": "/favicon.ico",
+				"logo": "/banner.png",
+				"config": (*config).clone(),
+			}),
+		)
+		.map_err(|e| AppError::Internal(format!("Couldn't render a feed: {e}")))?;
+	Ok((ContentType::new("application", "atom+xml"), content))
+}
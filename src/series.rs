@@ -0,0 +1,88 @@
+use std::{collections::HashSet, sync::Arc};
+
+use rocket::{State, http::ContentType, response::content::RawHtml};
+use sqlx::{Pool, Postgres};
+use tera::Tera;
+use tokio::sync::RwLock;
+
+use crate::{
+	Config,
+	analytics::{self, ViewKind, Visit},
+	cookies::{ClientPersist, Preferences},
+	db,
+	errors::AppError,
+};
+
+/// How many parts a series feed lists.
+const FEED_LEN: usize = 32;
+
+#[get("/<name>")]
+pub async fn display(
+	name: &str,
+	db: &State<Pool<Postgres>>,
+	cookie: ClientPersist,
+	tera: &State<Arc<RwLock<Tera>>>,
+	config: &State<Arc<Config>>,
+	prefs: Preferences,
+) -> Result<RawHtml<String>, AppError> {
+	let parts = db::series_posts(db, name).await?;
+	if parts.is_empty() {
+		return Err(AppError::NotFound);
+	}
+	let url = db::series_url(config, name);
+	let content = crate::templates::render(
+		tera,
+		config,
+		"series.html.tera",
+		&crate::context!({
+			"prefs": prefs,
+			"name": name,
+			"articles": parts,
+			"url": url,
+			"feed_url": format!("{url}/feed"),
+			"new": parts.iter().filter(|(path, meta)| {
+				cookie.viewed.get(path).is_none_or(|viewed| *viewed < meta.updated)
+			}).map(|(p, _)| p).collect::<HashSet<_>>(),
+		}),
+	)
+	.await?;
+	Ok(RawHtml(content))
+}
+
+#[get("/<name>/feed")]
+pub async fn feed(
+	name: &str,
+	db: &State<Pool<Postgres>>,
+	tera: &State<Arc<RwLock<Tera>>>,
+	config: &State<Arc<Config>>,
+	visit: Visit,
+) -> Result<(ContentType, String), AppError> {
+	let mut parts = db::series_posts(db, name).await?;
+	if parts.is_empty() {
+		return Err(AppError::NotFound);
+	}
+	let feed = format!(
+		"{}/feed",
+		db::series_url(config, name).trim_start_matches('/')
+	);
+	analytics::record(db, config, visit, ViewKind::Feed, &feed);
+	// The newest parts are the ones a reader hasn't seen yet
+	parts.reverse();
+	parts.truncate(FEED_LEN);
+	let content = tera
+		.read()
+		.await
+		.render(
+			"page-list.atom.tera",
+			&crate::context!({
+				"articles": parts,
+				"url": format!("{}{feed}", config.origin),
+				"title": format!("{} ({name})", config.site_name),
+				"icon": "/favicon.ico",
+				"logo": "/banner.png",
+				"config": (*config).clone(),
+			}),
+		)
+		.map_err(|e| AppError::Internal(format!("Couldn't render a feed: {e}")))?;
+	Ok((ContentType::new("application", "atom+xml"), content))
+}
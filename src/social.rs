@@ -0,0 +1,220 @@
+use std::{
+	path::PathBuf,
+	sync::{Arc, OnceLock},
+};
+
+use resvg::{
+	tiny_skia,
+	usvg::{self, fontdb},
+};
+use rocket::{
+	State,
+	http::{ContentType, Header},
+};
+use serde::Serialize;
+use serde_json::json;
+use sqlx::{Pool, Postgres};
+use tera::Tera;
+use tokio::sync::RwLock;
+
+use crate::{Config, db, errors::AppError};
+
+/// How many characters of a title fit on one line of a preview image.
+const LINE_LEN: usize = 26;
+/// How many lines of title fit on a preview image.
+const MAX_LINES: usize = 4;
+
+/// Fonts for drawing preview images: the system's, and any the site ships
+/// that aren't web-only formats.
+static FONTS: OnceLock<Arc<fontdb::Database>> = OnceLock::new();
+
+/// What a post looks like when it's shared somewhere, for Open Graph and
+/// Twitter card tags.
+#[derive(Serialize)]
+pub struct OpenGraph {
+	title: String,
+	description: String,
+	url: String,
+	image: String,
+	site_name: String,
+	author: Option<String>,
+	published: String,
+	modified: String,
+	tags: Vec<String>,
+	/// The same, as schema.org JSON-LD ready to go in a `<script>`
+	json_ld: String,
+}
+
+impl OpenGraph {
+	pub fn new(config: &Config, path: &str, meta: &db::ArticleMeta) -> Self {
+		let url = format!("{}post/{path}", config.origin);
+		let image = match meta.cover.as_deref() {
+			Some(cover) => config
+				.origin
+				.join(cover)
+				.map_or_else(|_| cover.to_string(), String::from),
+			None => format!("{}og/{path}.png", config.origin),
+		};
+		let json_ld = json!({
+			"@context": "https://schema.org",
+			"@type": "BlogPosting",
+			"headline": meta.title,
+			"description": meta.blurb,
+			"url": url,
+			"mainEntityOfPage": url,
+			"image": image,
+			"datePublished": meta.created,
+			"dateModified": meta.updated,
+			"keywords": meta.tags,
+			"author": config.author.as_ref().map(|name| json!({
+				"@type": "Person",
+				"name": name,
+				"url": config.origin,
+			})),
+			"publisher": {
+				"@type": "Organization",
+				"name": config.site_name,
+				"url": config.origin,
+			},
+		});
+		OpenGraph {
+			title: meta.title.clone(),
+			description: meta.blurb.clone(),
+			image,
+			site_name: config.site_name.clone(),
+			author: config.author.clone(),
+			published: meta.created.to_string(),
+			modified: meta.updated.to_string(),
+			tags: meta.tags.clone(),
+			// Nothing in the data can close the script tag early
+			json_ld: json_ld.to_string().replace("</", "<\\/"),
+			url,
+		}
+	}
+}
+
+/// Break a title into lines short enough for a preview image, cutting it
+/// off with an ellipsis if there's too much.
+fn wrap(title: &str) -> Vec<String> {
+	let mut lines: Vec<String> = vec![];
+	for word in title.split_whitespace() {
+		match lines.last_mut() {
+			Some(line) if line.chars().count() + 1 + word.chars().count() <= LINE_LEN => {
+				line.push(' ');
+				line.push_str(word);
+			}
+			_ => lines.push(word.to_string()),
+		}
+	}
+	if lines.len() > MAX_LINES {
+		lines.truncate(MAX_LINES);
+		lines[MAX_LINES - 1].push('…');
+	}
+	lines
+}
+
+fn fonts(config: &Config) -> Arc<fontdb::Database> {
+	FONTS
+		.get_or_init(|| {
+			let mut fonts = fontdb::Database::new();
+			fonts.load_system_fonts();
+			fonts.load_fonts_dir(config.static_root.join("font"));
+			// Templates can ask for `serif` without knowing what's installed,
+			// so point it at something that is
+			let family = |fonts: &fontdb::Database, wanted: &str| {
+				fonts
+					.faces()
+					.flat_map(|face| &face.families)
+					.map(|(family, _)| family.clone())
+					.find(|family| family.contains(wanted))
+			};
+			if let Some(serif) = family(&fonts, "Serif").or_else(|| family(&fonts, "")) {
+				fonts.set_serif_family(serif);
+			}
+			if let Some(sans) = family(&fonts, "Sans").or_else(|| family(&fonts, "")) {
+				fonts.set_sans_serif_family(sans);
+			}
+			Arc::new(fonts)
+		})
+		.clone()
+}
+
+/// Draw a preview image from the SVG the `og.svg.tera` template makes.
+fn render_png(svg: &str, config: &Config) -> Result<Vec<u8>, String> {
+	let options = usvg::Options {
+		fontdb: fonts(config),
+		font_family: "Vollkorn".to_string(),
+		..usvg::Options::default()
+	};
+	let tree = usvg::Tree::from_str(svg, &options).map_err(|e| e.to_string())?;
+	let size = tree.size().to_int_size();
+	let mut pixmap = tiny_skia::Pixmap::new(size.width(), size.height())
+		.ok_or("The preview image has no size")?;
+	resvg::render(&tree, tiny_skia::Transform::default(), &mut pixmap.as_mut());
+	pixmap.encode_png().map_err(|e| e.to_string())
+}
+
+#[derive(Responder)]
+pub struct Png {
+	body: (ContentType, Vec<u8>),
+	cache: Header<'static>,
+}
+
+/// Make the preview image for a post that has no cover of its own.
+async fn draw(
+	path: &str,
+	db: &Pool<Postgres>,
+	tera: &RwLock<Tera>,
+	config: &Arc<Config>,
+) -> Result<Vec<u8>, AppError> {
+	let meta = db::read_post_meta(db, path)
+		.await
+		.ok_or(AppError::NotFound)?;
+	let svg = tera
+		.read()
+		.await
+		.render(
+			"og.svg.tera",
+			&crate::context!({
+				"lines": wrap(&meta.title),
+				"tags": meta.tags,
+				"created": meta.created,
+				"site_name": config.site_name,
+				"host": config.origin.host_str(),
+				"author": config.author,
+			}),
+		)
+		.map_err(|e| AppError::Internal(format!("Couldn't render og.svg.tera: {e}")))?;
+	let config = config.clone();
+	tokio::task::spawn_blocking(move || render_png(&svg, &config))
+		.await
+		.map_err(|e| AppError::Internal(e.to_string()))?
+		.map_err(|e| AppError::Internal(format!("Couldn't draw a preview of {path}: {e}")))
+}
+
+#[get("/<path..>")]
+pub async fn image(
+	path: PathBuf,
+	db: &State<Pool<Postgres>>,
+	tera: &State<Arc<RwLock<Tera>>>,
+	config: &State<Arc<Config>>,
+) -> Result<Png, AppError> {
+	if path.extension().is_none_or(|ext| ext != "png") {
+		return Err(AppError::NotFound);
+	}
+	let path = db::trim_path(config, &path.with_extension(""));
+	let (updated, cached) = db::og_image(db, &path).await?.ok_or(AppError::NotFound)?;
+	let png = if let Some(png) = cached {
+		png
+	} else {
+		let png = draw(&path, db, tera, config).await?;
+		db::save_og_image(db, &path, updated, &png).await?;
+		png
+	};
+	Ok(Png {
+		body: (ContentType::PNG, png),
+		// The URL stays the same when the post changes, so don't hold on
+		// to it for too long
+		cache: Header::new("Cache-Control", "public, max-age=3600"),
+	})
+}
@@ -0,0 +1,203 @@
+use std::{
+	error::Error as _,
+	path::{Path, PathBuf},
+	sync::{Arc, Mutex},
+	time::Duration,
+};
+
+use notify::{EventKind, Watcher};
+use rocket::{
+	Request,
+	http::Status,
+	response::{Responder, content::RawHtml},
+};
+use sqlx::{Pool, Postgres};
+use tera::{Context, Tera};
+use thiserror::Error;
+use tokio::sync::RwLock;
+
+use crate::{Config, functions};
+
+/// Why the templates on disk couldn't be loaded, while we carry on with
+/// the last ones that could.
+static RELOAD_ERROR: Mutex<Option<String>> = Mutex::new(None);
+
+#[derive(Error, Debug)]
+#[error("Couldn't render {template}: {details}")]
+pub struct RenderError {
+	template: String,
+	details: String,
+	develop: bool,
+}
+
+impl<'r, 'o: 'r> Responder<'r, 'o> for RenderError {
+	fn respond_to(self, request: &'r Request<'_>) -> rocket::response::Result<'o> {
+		eprintln!("{self}");
+		if self.develop {
+			let page = with_overlay(
+				"<!DOCTYPE html>\n<html><body></body></html>".to_string(),
+				&format!("Couldn't render {}", self.template),
+				&self.details,
+			);
+			return (Status::InternalServerError, RawHtml(page)).respond_to(request);
+		}
+		// The error page catcher takes it from here
+		Err(Status::InternalServerError)
+	}
+}
+
+pub fn load(config: &Arc<Config>, db: &Pool<Postgres>) -> tera::Result<Tera> {
+	let mut tera = Tera::new(config.templates_root.to_str().unwrap())?;
+	functions::register(&mut tera, db, config);
+	Ok(tera)
+}
+
+/// A Tera error and everything that led to it, one per line. For syntax
+/// errors this includes the file, line and column.
+fn describe(error: &tera::Error) -> String {
+	let mut lines = vec![error.to_string()];
+	let mut source = error.source();
+	while let Some(cause) = source {
+		lines.push(cause.to_string());
+		source = cause.source();
+	}
+	lines.join("\n")
+}
+
+/// Read the templates again. If any of them is broken, the ones already
+/// loaded stay in use.
+pub async fn reload(
+	tera: &RwLock<Tera>,
+	config: &Arc<Config>,
+	db: &Pool<Postgres>,
+) -> Result<(), String> {
+	match load(config, db) {
+		Ok(fresh) => {
+			*tera.write().await = fresh;
+			*RELOAD_ERROR.lock().unwrap() = None;
+			Ok(())
+		}
+		Err(e) => {
+			let details = describe(&e);
+			eprintln!("Error reloading templates: {details}");
+			*RELOAD_ERROR.lock().unwrap() = Some(details.clone());
+			Err(details)
+		}
+	}
+}
+
+/// Put a box describing an error over the top of a page.
+fn with_overlay(mut html: String, title: &str, details: &str) -> String {
+	let overlay = format!(
+		r#"<div role="alert" style="position: fixed; inset: 2em; z-index: 1000; overflow: auto; padding: 1em 2em; background: #1e1e2e; color: #f38ba8; border: 4px solid #f38ba8; border-radius: 8px; font-family: monospace;"><h1>{}</h1><pre style="white-space: pre-wrap; color: #cdd6f4;">{}</pre><p>This only shows up in develop mode.</p></div>"#,
+		tera::escape_html(title),
+		tera::escape_html(details)
+	);
+	match html.rfind("</body>") {
+		Some(end) => html.insert_str(end, &overlay),
+		None => html.push_str(&overlay),
+	}
+	html
+}
+
+/// Render an HTML page. In develop mode, template errors are shown on the
+/// page rather than only logged.
+pub async fn render(
+	tera: &RwLock<Tera>,
+	config: &Config,
+	template: &str,
+	context: &Context,
+) -> Result<String, RenderError> {
+	let html = functions::cached(async { tera.read().await.render(template, context) })
+		.await
+		.map_err(|e| RenderError {
+			template: template.to_string(),
+			details: describe(&e),
+			develop: config.develop,
+		})?;
+	if config.develop
+		&& let Some(details) = RELOAD_ERROR.lock().unwrap().as_deref()
+	{
+		return Ok(with_overlay(
+			html,
+			"Couldn't reload templates, so this page uses the last ones that worked",
+			details,
+		));
+	}
+	Ok(html)
+}
+
+/// The directory a template glob like `./templates/**/*.tera` looks in.
+fn glob_root(glob: &Path) -> PathBuf {
+	let root: PathBuf = glob
+		.components()
+		.take_while(|c| {
+			!c.as_os_str()
+				.to_string_lossy()
+				.contains(['*', '?', '[', '{'])
+		})
+		.collect();
+	if root.as_os_str().is_empty() {
+		PathBuf::from(".")
+	} else {
+		root
+	}
+}
+
+/// Reload the templates as soon as anything in their directory changes.
+pub fn watch(tera: Arc<RwLock<Tera>>, config: Arc<Config>, db: Pool<Postgres>) {
+	let root = glob_root(&config.templates_root);
+	let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+	let mut inotify_watcher = Box::new(
+		notify::INotifyWatcher::new(
+			{
+				let tx = tx.clone();
+				move |e| {
+					if let Ok(e) = e {
+						tx.send(e).unwrap();
+					}
+				}
+			},
+			notify::Config::default().with_follow_symlinks(true),
+		)
+		.unwrap(),
+	);
+	// inotify doesn't see changes on every filesystem, so poll as well
+	let mut poll_watcher = Box::new(
+		notify::PollWatcher::new(
+			move |e| {
+				if let Ok(e) = e {
+					tx.send(e).unwrap();
+				}
+			},
+			notify::Config::default()
+				.with_poll_interval(Duration::from_secs(config.update_interval)),
+		)
+		.unwrap(),
+	);
+	inotify_watcher
+		.watch(&root, notify::RecursiveMode::Recursive)
+		.unwrap();
+	poll_watcher
+		.watch(&root, notify::RecursiveMode::Recursive)
+		.unwrap();
+	Box::leak(inotify_watcher);
+	Box::leak(poll_watcher);
+	tokio::spawn(async move {
+		while let Some(event) = rx.recv().await {
+			if !matches!(
+				event.kind,
+				EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+			) {
+				continue;
+			}
+			// Editors tend to touch a file several times when saving it,
+			// so let them finish and reload once
+			tokio::time::sleep(Duration::from_millis(100)).await;
+			while rx.try_recv().is_ok() {}
+			if reload(&tera, &config, &db).await.is_ok() {
+				eprintln!("Reloaded templates");
+			}
+		}
+	});
+}
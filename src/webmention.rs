@@ -0,0 +1,958 @@
+use std::{
+	collections::{HashMap, HashSet},
+	path::PathBuf,
+	sync::Arc,
+	time::Duration,
+};
+
+use chrono::{DateTime, Utc};
+use dom_query::Document;
+use reqwest::{
+	StatusCode,
+	header::{ACCEPT, CONTENT_TYPE, LINK},
+};
+use rocket::{
+	State,
+	form::Form,
+	http::{Header, Status},
+	response::{Redirect, content::RawHtml},
+	serde::json::Json,
+};
+use sqlx::{Pool, Postgres};
+use tera::Tera;
+use thiserror::Error;
+use tokio::{sync::RwLock, task::JoinSet};
+use url::Url;
+
+use crate::{
+	Config,
+	cookies::Preferences,
+	db::{self, MentionRequest, OutgoingMention},
+	errors::AppError,
+	fetch::{self, Outside},
+	oauth::Admin,
+};
+
+/// How often the sender checks the queue for due webmentions.
+const SEND_INTERVAL: Duration = Duration::from_secs(15);
+/// How many webmentions the sender works on at once.
+const SEND_BATCH: i64 = 8;
+/// How long a claimed webmention stays hidden from other workers.
+const SEND_LEASE: f64 = 600.0;
+/// Give up on a target after this many failed attempts.
+const MAX_ATTEMPTS: i32 = 8;
+/// The most we'll read from a remote page while looking for an endpoint.
+const MAX_BODY: usize = 1 << 20;
+
+#[derive(strum::Display, Clone, Copy, PartialEq, Eq, Debug)]
+#[strum(serialize_all = "snake_case")]
+pub enum DeliveryStatus {
+	Pending,
+	Sent,
+	NoEndpoint,
+	Failed,
+}
+
+#[derive(Error, Debug)]
+pub enum SendError {
+	#[error("Bad URL: {0}")]
+	BadUrl(#[from] url::ParseError),
+	#[error("Request failed: {0}")]
+	Request(#[from] reqwest::Error),
+	#[error(transparent)]
+	NotPublic(#[from] fetch::NotPublic),
+	#[error("No webmention endpoint advertised")]
+	NoEndpoint,
+	#[error("Endpoint rejected the webmention with {0}")]
+	Rejected(StatusCode),
+	#[error("Endpoint failed with {0}")]
+	Unavailable(StatusCode),
+}
+
+impl SendError {
+	fn status(&self) -> DeliveryStatus {
+		match self {
+			SendError::NoEndpoint => DeliveryStatus::NoEndpoint,
+			SendError::BadUrl(_) | SendError::NotPublic(_) | SendError::Rejected(_) => {
+				DeliveryStatus::Failed
+			}
+			SendError::Request(e) if fetch::refused(e) => DeliveryStatus::Failed,
+			SendError::Request(_) | SendError::Unavailable(_) => DeliveryStatus::Pending,
+		}
+	}
+}
+
+/// Resolve a mention from post metadata to something we can send a webmention to.
+fn mention_target(cfg: &Config, mention: &str) -> Option<String> {
+	let url = cfg.origin.join(mention).ok()?;
+	matches!(url.scheme(), "http" | "https").then(|| url.to_string())
+}
+
+/// Queue every mention of a post we haven't seen before, skipping targets
+/// which have already heard from it.
+pub async fn queue_new(
+	cfg: &Config,
+	db: &Pool<Postgres>,
+	path: &str,
+	mentions: &[String],
+) -> Result<(), sqlx::Error> {
+	if !cfg.enable_webmention {
+		return Ok(());
+	}
+	let targets: Vec<_> = mentions
+		.iter()
+		.filter_map(|m| mention_target(cfg, m))
+		.collect();
+	db::queue_outgoing_mentions(db, path, &targets, false).await
+}
+
+/// Queue every mention which was added or removed between two versions of a post.
+pub async fn queue_changes(
+	cfg: &Config,
+	db: &Pool<Postgres>,
+	path: &str,
+	old_mentions: &[String],
+	new_mentions: &[String],
+) -> Result<(), sqlx::Error> {
+	if !cfg.enable_webmention {
+		return Ok(());
+	}
+	let old: HashSet<_> = old_mentions
+		.iter()
+		.filter_map(|m| mention_target(cfg, m))
+		.collect();
+	let new: HashSet<_> = new_mentions
+		.iter()
+		.filter_map(|m| mention_target(cfg, m))
+		.collect();
+	let changed: Vec<_> = old.symmetric_difference(&new).cloned().collect();
+	if changed.is_empty() {
+		return Ok(());
+	}
+	db::queue_outgoing_mentions(db, path, &changed, true).await
+}
+
+/// Work through the outgoing webmention queue in the background.
+pub fn spawn_sender(db: Pool<Postgres>, cfg: Arc<Config>) {
+	if !cfg.enable_webmention {
+		return;
+	}
+	tokio::spawn(async move {
+		let mut interval = tokio::time::interval(SEND_INTERVAL);
+		loop {
+			interval.tick().await;
+			let jobs = match db::claim_outgoing_mentions(&db, SEND_BATCH, SEND_LEASE).await {
+				Ok(jobs) => jobs,
+				Err(e) => {
+					eprintln!("Failed to read the webmention queue: {e}");
+					continue;
+				}
+			};
+			jobs.into_iter()
+				.map(|job| {
+					let db = db.clone();
+					let cfg = cfg.clone();
+					async move { deliver(&db, &cfg, job).await }
+				})
+				.collect::<JoinSet<_>>()
+				.join_all()
+				.await;
+		}
+	});
+}
+
+async fn deliver(db: &Pool<Postgres>, cfg: &Config, job: OutgoingMention) {
+	let source = format!("{}post/{}", cfg.origin, job.from_path);
+	let delivery = attempt(Outside::new(cfg.develop), &source, &job).await;
+	if let Err(e) = db::record_outgoing_mention(
+		db,
+		&job.from_path,
+		&job.to_url,
+		&delivery.status.to_string(),
+		delivery.next_attempt,
+		delivery.endpoint.as_deref(),
+		delivery.response_code,
+		delivery.error.as_deref(),
+	)
+	.await
+	{
+		eprintln!(
+			"Failed to record webmention from {} to {}: {e}",
+			job.from_path, job.to_url
+		);
+	}
+}
+
+/// How an attempt at sending a webmention went, and when to try again.
+#[derive(Debug)]
+struct Delivery {
+	status: DeliveryStatus,
+	next_attempt: DateTime<Utc>,
+	endpoint: Option<String>,
+	response_code: Option<i32>,
+	error: Option<String>,
+}
+
+async fn attempt(outside: Outside, source: &str, job: &OutgoingMention) -> Delivery {
+	let now = Utc::now();
+	match send(outside, source, &job.to_url).await {
+		Ok((endpoint, code)) => Delivery {
+			status: DeliveryStatus::Sent,
+			next_attempt: now,
+			endpoint: Some(endpoint),
+			response_code: Some(i32::from(code.as_u16())),
+			error: None,
+		},
+		Err(e) => {
+			let mut status = e.status();
+			if status == DeliveryStatus::Pending && job.attempts + 1 >= MAX_ATTEMPTS {
+				status = DeliveryStatus::Failed;
+			}
+			// Back off exponentially, starting at a minute
+			let backoff = chrono::Duration::minutes(1 << job.attempts.clamp(0, 16));
+			let response_code = match e {
+				SendError::Rejected(code) | SendError::Unavailable(code) => {
+					Some(i32::from(code.as_u16()))
+				}
+				_ => None,
+			};
+			Delivery {
+				status,
+				next_attempt: now + backoff,
+				endpoint: None,
+				response_code,
+				error: Some(e.to_string()),
+			}
+		}
+	}
+}
+
+/// Send a single webmention, returning the endpoint used and its response.
+async fn send(
+	outside: Outside,
+	source: &str,
+	target: &str,
+) -> Result<(String, StatusCode), SendError> {
+	let target = Url::parse(target)?;
+	let endpoint = discover_endpoint(outside, &target)
+		.await?
+		.ok_or(SendError::NoEndpoint)?;
+	let mut form = HashMap::new();
+	form.insert("source", source);
+	form.insert("target", target.as_str());
+	let response = outside.post(&endpoint)?.form(&form).send().await?;
+	let status = response.status();
+	if status.is_success() {
+		Ok((endpoint.to_string(), status))
+	} else if status.is_server_error()
+		|| status == StatusCode::TOO_MANY_REQUESTS
+		|| status == StatusCode::REQUEST_TIMEOUT
+	{
+		Err(SendError::Unavailable(status))
+	} else {
+		Err(SendError::Rejected(status))
+	}
+}
+
+/// Find the webmention endpoint a page advertises, first in its `Link`
+/// headers and then in its HTML.
+pub async fn discover_endpoint(outside: Outside, target: &Url) -> Result<Option<Url>, SendError> {
+	let response = outside.get(target)?.send().await?;
+	// Relative endpoints are relative to wherever we got redirected to
+	let base = response.url().clone();
+	for value in response.headers().get_all(LINK) {
+		let Ok(value) = value.to_str() else {
+			continue;
+		};
+		if let Some(href) = link_header_rel(value, "webmention") {
+			return Ok(Some(base.join(href)?));
+		}
+	}
+	let is_html = response
+		.headers()
+		.get(CONTENT_TYPE)
+		.and_then(|c| c.to_str().ok())
+		.is_some_and(|c| c.trim_start().starts_with("text/html"));
+	if !is_html {
+		return Ok(None);
+	}
+	let body = read_limited(response, MAX_BODY).await?;
+	let href = {
+		let doc = Document::from(body.as_str());
+		doc.select_single(r#"link[rel~="webmention"][href], a[rel~="webmention"][href]"#)
+			.attr("href")
+			.map(|h| h.to_string())
+	};
+	Ok(href.map(|href| base.join(&href)).transpose()?)
+}
+
+/// Read at most `limit` bytes of a response body, ignoring the rest.
+pub async fn read_limited(
+	mut response: reqwest::Response,
+	limit: usize,
+) -> Result<String, reqwest::Error> {
+	let mut body = Vec::new();
+	while let Some(chunk) = response.chunk().await? {
+		body.extend_from_slice(&chunk);
+		if body.len() >= limit {
+			body.truncate(limit);
+			break;
+		}
+	}
+	Ok(String::from_utf8_lossy(&body).into_owned())
+}
+
+/// Find the first link with the given `rel` in a `Link` header value.
+pub fn link_header_rel<'a>(value: &'a str, rel: &str) -> Option<&'a str> {
+	let mut rest = value;
+	while let Some(start) = rest.find('<') {
+		let end = start + rest[start..].find('>')?;
+		let href = &rest[start + 1..end];
+		rest = &rest[end + 1..];
+		// This link's parameters run until the next comma outside of quotes
+		let mut quoted = false;
+		let params_end = rest
+			.char_indices()
+			.find(|&(_, c)| {
+				if c == '"' {
+					quoted = !quoted;
+				}
+				c == ',' && !quoted
+			})
+			.map_or(rest.len(), |(i, _)| i);
+		let params = &rest[..params_end];
+		rest = &rest[params_end..];
+		let matches = params
+			.split(';')
+			.filter_map(|p| p.split_once('='))
+			.filter(|(k, _)| k.trim().eq_ignore_ascii_case("rel"))
+			.flat_map(|(_, v)| v.trim().trim_matches('"').split_ascii_whitespace())
+			.any(|r| r.eq_ignore_ascii_case(rel));
+		if matches {
+			return Some(href);
+		}
+	}
+	None
+}
+
+/// Who a post has sent webmentions to, and how that went. Only for the
+/// admin, since it shows what's still queued.
+#[get("/sent/<path..>")]
+pub async fn sent(
+	_admin: Admin,
+	path: PathBuf,
+	db: &State<Pool<Postgres>>,
+	cfg: &State<Arc<Config>>,
+) -> Result<Json<Vec<OutgoingMention>>, (Status, String)> {
+	let path = db::trim_path(cfg, &path);
+	db::outgoing_mentions(db, &path)
+		.await
+		.map(Json)
+		.map_err(|e| (Status::InternalServerError, e.to_string()))
+}
+
+#[derive(FromForm)]
+pub struct WebMention {
+	pub source: String,
+	pub target: String,
+}
+
+#[derive(Responder)]
+#[response(status = 202)]
+pub struct Queued {
+	body: String,
+	location: Header<'static>,
+}
+
+pub fn parse_http_url(url: &str) -> Option<Url> {
+	Url::parse(url)
+		.ok()
+		.filter(|u| matches!(u.scheme(), "http" | "https") && u.has_host())
+}
+
+#[post("/", data = "<wm>")]
+pub async fn receive(
+	wm: Form<WebMention>,
+	cfg: &State<Arc<Config>>,
+	db: &State<Pool<Postgres>>,
+) -> Result<Queued, (Status, String)> {
+	let WebMention { source, target } = wm.into_inner();
+	let Some(source) = parse_http_url(&source) else {
+		return Err((
+			Status::BadRequest,
+			"Source should be an absolute http(s) URL".to_string(),
+		));
+	};
+	let Some(target) = parse_http_url(&target) else {
+		return Err((
+			Status::BadRequest,
+			"Target should be an absolute http(s) URL".to_string(),
+		));
+	};
+	if source == target {
+		return Err((
+			Status::BadRequest,
+			"Source and target should be different".to_string(),
+		));
+	}
+	let Some(path) = db::post_path(cfg, &target) else {
+		return Err((Status::BadRequest, "You can only mention posts".to_string()));
+	};
+	// Drafts can't be mentioned, since nobody should know they exist yet
+	if !db::read_post_meta(db, &path)
+		.await
+		.is_some_and(|m| m.ready || cfg.develop)
+	{
+		return Err((Status::BadRequest, "Target post doesn't exist".to_string()));
+	}
+	if is_banned(cfg, db, &source)
+		.await
+		.map_err(|_| (Status::InternalServerError, "Database error".to_string()))?
+	{
+		return Err((
+			Status::Forbidden,
+			"Webmentions from your domain aren't accepted".to_string(),
+		));
+	}
+	let id = db::queue_mention_request(db, &source, &target, &path)
+		.await
+		.map_err(|_| (Status::InternalServerError, "Database error".to_string()))?;
+	let status_url = format!("{}webmention/status/{id}", cfg.origin);
+	Ok(Queued {
+		body: format!("Queued for verification; check {status_url} to see how it went."),
+		location: Header::new("Location", status_url),
+	})
+}
+
+#[get("/status/<id>")]
+pub async fn status(
+	id: &str,
+	db: &State<Pool<Postgres>>,
+) -> Result<Json<MentionRequest>, (Status, String)> {
+	db::mention_request(db, id)
+		.await
+		.map_err(|e| (Status::InternalServerError, e.to_string()))?
+		.map(Json)
+		.ok_or((Status::NotFound, "No such webmention".to_string()))
+}
+
+#[derive(strum::Display, Clone, Copy, PartialEq, Eq)]
+#[strum(serialize_all = "snake_case")]
+pub enum RequestStatus {
+	Queued,
+	Accepted,
+	Rejected,
+	Deleted,
+	Failed,
+}
+
+/// What verifying a webmention's source told us.
+enum Verdict {
+	Accepted,
+	Rejected(String),
+	/// The source is gone, so the mention should be too.
+	Deleted,
+	/// We couldn't tell, so try again later.
+	Retry(String),
+}
+
+/// Give up on verifying a source after this many failed attempts.
+const MAX_VERIFY_ATTEMPTS: i32 = 4;
+/// Content types we know how to look for links in.
+const HTML_TYPES: &[&str] = &["text/html", "application/xhtml+xml"];
+const TEXT_TYPES: &[&str] = &["text/plain", "application/json"];
+
+/// Verify queued webmentions in the background.
+pub fn spawn_verifier(db: Pool<Postgres>, cfg: Arc<Config>) {
+	// Anyone can make us fetch a URL, so don't let them point us at our own network.
+	// Local addresses are fine while developing.
+	let outside = Outside::new(cfg.develop);
+	tokio::spawn(async move {
+		let mut interval = tokio::time::interval(SEND_INTERVAL);
+		loop {
+			interval.tick().await;
+			let requests = match db::claim_mention_requests(&db, SEND_BATCH, SEND_LEASE).await {
+				Ok(requests) => requests,
+				Err(e) => {
+					eprintln!("Failed to read the webmention verification queue: {e}");
+					continue;
+				}
+			};
+			requests
+				.into_iter()
+				.map(|request| {
+					let db = db.clone();
+					let cfg = cfg.clone();
+					async move { process(&db, &cfg, outside, request).await }
+				})
+				.collect::<JoinSet<_>>()
+				.join_all()
+				.await;
+		}
+	});
+}
+
+async fn process(db: &Pool<Postgres>, cfg: &Config, outside: Outside, request: MentionRequest) {
+	let verdict = match (Url::parse(&request.source), Url::parse(&request.target)) {
+		// The ban might be newer than the request
+		(Ok(source), Ok(target)) => match is_banned(cfg, db, &source).await {
+			Ok(true) => Verdict::Rejected("Source is banned".to_string()),
+			Ok(false) => verify(outside, &source, &target).await,
+			Err(_) => Verdict::Retry("Database error".to_string()),
+		},
+		_ => Verdict::Rejected("Bad URL".to_string()),
+	};
+	let now = Utc::now();
+	let (status, detail, next_attempt) = match verdict {
+		Verdict::Accepted => (RequestStatus::Accepted, None, now),
+		Verdict::Rejected(why) => (RequestStatus::Rejected, Some(why), now),
+		Verdict::Deleted => (RequestStatus::Deleted, None, now),
+		Verdict::Retry(why) if request.attempts + 1 >= MAX_VERIFY_ATTEMPTS => {
+			(RequestStatus::Failed, Some(why), now)
+		}
+		Verdict::Retry(why) => (
+			RequestStatus::Queued,
+			Some(why),
+			now + chrono::Duration::minutes(5 << request.attempts.clamp(0, 8)),
+		),
+	};
+	let source = Url::parse(&request.source);
+	let applied = match (status, &source) {
+		(RequestStatus::Accepted, Ok(source)) => {
+			let approved = host_listed(&cfg.webmention_allowlist, source);
+			db::get_webmention(db, source, &request.to_path, approved).await
+		}
+		(RequestStatus::Rejected | RequestStatus::Deleted, Ok(source)) => {
+			db::rm_webmention(db, source, &request.to_path).await
+		}
+		_ => Ok(()),
+	};
+	if let Err(e) = applied.and(
+		db::record_mention_request(
+			db,
+			&request.id,
+			&status.to_string(),
+			detail.as_deref(),
+			next_attempt,
+		)
+		.await,
+	) {
+		eprintln!(
+			"Failed to record webmention from {} to {}: {e}",
+			request.source, request.to_path
+		);
+	}
+}
+
+async fn verify(outside: Outside, source: &Url, target: &Url) -> Verdict {
+	let request = match outside.get(source) {
+		Ok(request) => request,
+		Err(e) => return Verdict::Rejected(e.to_string()),
+	};
+	let response = match request
+		.header(
+			ACCEPT,
+			"text/html, application/xhtml+xml;q=0.9, text/plain;q=0.5, application/json;q=0.5",
+		)
+		.send()
+		.await
+	{
+		Ok(response) => response,
+		Err(e) if fetch::refused(&e) => {
+			return Verdict::Rejected("Source isn't on the public internet".to_string());
+		}
+		Err(e) if e.is_timeout() || e.is_connect() => return Verdict::Retry(e.to_string()),
+		Err(e) => return Verdict::Rejected(e.to_string()),
+	};
+	let status = response.status();
+	if status == StatusCode::NOT_FOUND || status == StatusCode::GONE {
+		return Verdict::Deleted;
+	}
+	if status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS {
+		return Verdict::Retry(format!("Source responded with {status}"));
+	}
+	if !status.is_success() {
+		return Verdict::Rejected(format!("Source responded with {status}"));
+	}
+	let content_type = response
+		.headers()
+		.get(CONTENT_TYPE)
+		.and_then(|c| c.to_str().ok())
+		.and_then(|c| c.split(';').next())
+		.map(|c| c.trim().to_ascii_lowercase())
+		.unwrap_or_default();
+	let is_html = HTML_TYPES.contains(&content_type.as_str());
+	if !is_html && !TEXT_TYPES.contains(&content_type.as_str()) {
+		return Verdict::Rejected(format!("Can't look for links in {content_type:?}"));
+	}
+	if response
+		.content_length()
+		.is_some_and(|l| l > MAX_BODY as u64)
+	{
+		return Verdict::Rejected("Source is too large".to_string());
+	}
+	let base = response.url().clone();
+	let body = match read_limited(response, MAX_BODY).await {
+		Ok(body) => body,
+		Err(e) => return Verdict::Retry(e.to_string()),
+	};
+	let links = if is_html {
+		links_to(&body, &base, target)
+	} else {
+		body.contains(target.as_str())
+	};
+	if links {
+		Verdict::Accepted
+	} else {
+		Verdict::Rejected("Source doesn't link to target".to_string())
+	}
+}
+
+/// Whether an HTML document links to, or embeds, the target.
+fn links_to(html: &str, base: &Url, target: &Url) -> bool {
+	fn without_fragment(url: &Url) -> Url {
+		let mut url = url.clone();
+		url.set_fragment(None);
+		url
+	}
+	let target = without_fragment(target);
+	let doc = Document::from(html);
+	let hrefs = doc
+		.select("a[href], link[href], area[href]")
+		.iter()
+		.filter_map(|e| e.attr("href"))
+		.collect::<Vec<_>>();
+	let srcs = doc
+		.select("img[src], video[src], audio[src], source[src]")
+		.iter()
+		.filter_map(|e| e.attr("src"))
+		.collect::<Vec<_>>();
+	hrefs
+		.iter()
+		.chain(srcs.iter())
+		.filter_map(|link| base.join(link.trim()).ok())
+		.any(|link| without_fragment(&link) == target)
+}
+
+/// Whether a host matches a ban or allowlist entry. `example.com` only
+/// matches itself, while `*.example.com` also matches all of its subdomains.
+pub fn host_matches(pattern: &str, host: &str) -> bool {
+	let pattern = pattern.trim().trim_end_matches('.').to_ascii_lowercase();
+	let host = host.trim_end_matches('.').to_ascii_lowercase();
+	match pattern.strip_prefix("*.") {
+		Some(domain) => {
+			host == domain
+				|| host
+					.strip_suffix(domain)
+					.is_some_and(|sub| sub.ends_with('.'))
+		}
+		None => host == pattern,
+	}
+}
+
+fn host_listed(patterns: &[String], url: &Url) -> bool {
+	url.host_str()
+		.is_some_and(|host| patterns.iter().any(|p| host_matches(p, host)))
+}
+
+/// Whether a URL is banned, either in the config file or at runtime.
+async fn is_banned(cfg: &Config, db: &Pool<Postgres>, url: &Url) -> Result<bool, sqlx::Error> {
+	if host_listed(&cfg.webmention_bans, url) {
+		return Ok(true);
+	}
+	let bans = db::webmention_bans(db).await?;
+	Ok(url
+		.host_str()
+		.is_some_and(|host| bans.iter().any(|b| host_matches(&b.pattern, host))))
+}
+
+#[get("/")]
+pub async fn moderate(
+	admin: Admin,
+	db: &State<Pool<Postgres>>,
+	tera: &State<Arc<RwLock<Tera>>>,
+	cfg: &State<Arc<Config>>,
+	prefs: Preferences,
+) -> Result<RawHtml<String>, AppError> {
+	let mentions = db::moderation_queue(db, 50).await?;
+	let bans = db::webmention_bans(db).await?;
+	let content = crate::templates::render(
+		tera,
+		cfg,
+		"admin-webmentions.html.tera",
+		&crate::context!({
+			"prefs": prefs,
+			"admin": admin.identity,
+			"admin_provider": admin.provider,
+			"mentions": mentions,
+			"bans": bans,
+			"config_bans": cfg.webmention_bans,
+			"allowlist": cfg.webmention_allowlist,
+		}),
+	)
+	.await?;
+	Ok(RawHtml(content))
+}
+
+#[derive(FromForm)]
+pub struct ModerationForm {
+	pub to_path: String,
+	pub from_url: String,
+	pub approve: bool,
+}
+
+#[post("/moderate", data = "<form>")]
+pub async fn set_status(
+	_admin: Admin,
+	form: Form<ModerationForm>,
+	db: &State<Pool<Postgres>>,
+) -> Result<Redirect, (Status, String)> {
+	let status = if form.approve { "approved" } else { "rejected" };
+	db::set_mention_status(db, &form.to_path, &form.from_url, status)
+		.await
+		.map_err(|e| (Status::InternalServerError, e.to_string()))?;
+	Ok(Redirect::to("/admin/webmentions"))
+}
+
+#[derive(FromForm)]
+pub struct BanForm {
+	pub pattern: String,
+	pub reason: Option<String>,
+}
+
+#[post("/ban", data = "<form>")]
+pub async fn ban(
+	_admin: Admin,
+	form: Form<BanForm>,
+	db: &State<Pool<Postgres>>,
+) -> Result<Redirect, (Status, String)> {
+	let pattern = form.pattern.trim().to_ascii_lowercase();
+	if pattern.is_empty() {
+		return Err((Status::BadRequest, "Ban pattern is empty".to_string()));
+	}
+	let reason = form.reason.as_deref().filter(|r| !r.trim().is_empty());
+	db::add_webmention_ban(db, &pattern, reason)
+		.await
+		.map_err(|e| (Status::InternalServerError, e.to_string()))?;
+	// Anything we already have from the banned domain shouldn't be shown any more
+	let mentions = db::unrejected_mentions(db)
+		.await
+		.map_err(|e| (Status::InternalServerError, e.to_string()))?;
+	for (to_path, from_url) in mentions {
+		let banned = Url::parse(&from_url)
+			.ok()
+			.and_then(|u| u.host_str().map(|h| host_matches(&pattern, h)))
+			.unwrap_or(false);
+		if banned {
+			db::set_mention_status(db, &to_path, &from_url, "rejected")
+				.await
+				.map_err(|e| (Status::InternalServerError, e.to_string()))?;
+		}
+	}
+	Ok(Redirect::to("/admin/webmentions"))
+}
+
+#[derive(FromForm)]
+pub struct UnbanForm {
+	pub pattern: String,
+}
+
+#[post("/unban", data = "<form>")]
+pub async fn unban(
+	_admin: Admin,
+	form: Form<UnbanForm>,
+	db: &State<Pool<Postgres>>,
+) -> Result<Redirect, (Status, String)> {
+	db::remove_webmention_ban(db, &form.pattern)
+		.await
+		.map_err(|e| (Status::InternalServerError, e.to_string()))?;
+	Ok(Redirect::to("/admin/webmentions"))
+}
+
+#[cfg(test)]
+mod tests {
+	use tokio::{
+		io::{AsyncReadExt, AsyncWriteExt},
+		net::TcpListener,
+		sync::mpsc,
+	};
+
+	use super::*;
+
+	fn reply(status: &str, headers: &str, body: &str) -> String {
+		format!(
+			"HTTP/1.1 {status}\r\n{headers}Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+			body.len()
+		)
+	}
+
+	/// What the receiving site answers at each path.
+	fn respond(path: &str) -> String {
+		let html = "Content-Type: text/html; charset=utf-8\r\n";
+		match path {
+			"/linked" => reply(
+				"200 OK",
+				&format!("Link: </accept>; rel=\"webmention\"\r\n{html}"),
+				r#"<link rel="webmention" href="/elsewhere">"#,
+			),
+			"/posts/marked-up" => reply(
+				"200 OK",
+				html,
+				r#"<a href="/">Home</a><a rel="nofollow webmention" href="../accept">"#,
+			),
+			"/plain" => reply(
+				"200 OK",
+				"Content-Type: text/plain\r\n",
+				r#"<link rel="webmention" href="/accept">"#,
+			),
+			"/busy" => reply("200 OK", "Link: </busy-endpoint>; rel=webmention\r\n", ""),
+			"/refusing" => reply("200 OK", "Link: </refuse>; rel=webmention\r\n", ""),
+			"/accept" => reply("202 Accepted", "", ""),
+			"/busy-endpoint" => reply("503 Service Unavailable", "", ""),
+			"/refuse" => reply("400 Bad Request", "", ""),
+			_ => reply("404 Not Found", "", ""),
+		}
+	}
+
+	/// Stand up a site on a local port, passing on the path and body of every
+	/// request it gets.
+	async fn receiver() -> (Url, mpsc::UnboundedReceiver<(String, String)>) {
+		let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+		let base = Url::parse(&format!("http://{}/", listener.local_addr().unwrap())).unwrap();
+		let (tx, rx) = mpsc::unbounded_channel();
+		tokio::spawn(async move {
+			while let Ok((mut stream, _)) = listener.accept().await {
+				let mut request = Vec::new();
+				let mut buf = [0; 4096];
+				let head_end = loop {
+					let n = stream.read(&mut buf).await.unwrap();
+					request.extend_from_slice(&buf[..n]);
+					if let Some(i) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+						break i + 4;
+					}
+				};
+				let head = String::from_utf8_lossy(&request[..head_end]).to_string();
+				let length = head
+					.lines()
+					.find_map(|l| {
+						l.to_ascii_lowercase()
+							.strip_prefix("content-length:")?
+							.trim()
+							.parse()
+							.ok()
+					})
+					.unwrap_or(0);
+				while request.len() < head_end + length {
+					let n = stream.read(&mut buf).await.unwrap();
+					request.extend_from_slice(&buf[..n]);
+				}
+				let path = head.split(' ').nth(1).unwrap().to_string();
+				let body = String::from_utf8_lossy(&request[head_end..]).to_string();
+				stream.write_all(respond(&path).as_bytes()).await.unwrap();
+				tx.send((path, body)).ok();
+			}
+		});
+		(base, rx)
+	}
+
+	fn job(to_url: &Url, attempts: i32) -> OutgoingMention {
+		OutgoingMention {
+			from_path: "hello".to_string(),
+			to_url: to_url.to_string(),
+			status: DeliveryStatus::Pending.to_string(),
+			attempts,
+			next_attempt: Utc::now(),
+			last_attempt: None,
+			endpoint: None,
+			response_code: None,
+			last_error: None,
+		}
+	}
+
+	#[tokio::test]
+	async fn endpoints_are_discovered() {
+		let (base, _) = receiver().await;
+		let outside = Outside::new(true);
+		let discover = |path: &str| {
+			let url = base.join(path).unwrap();
+			async move { discover_endpoint(outside, &url).await.unwrap() }
+		};
+		// Link headers win over the page
+		assert_eq!(discover("linked").await, Some(base.join("accept").unwrap()));
+		// Links in the page are relative to the page
+		assert_eq!(
+			discover("posts/marked-up").await,
+			Some(base.join("accept").unwrap())
+		);
+		// Only HTML is searched for links
+		assert_eq!(discover("plain").await, None);
+	}
+
+	#[tokio::test]
+	async fn deliveries_are_recorded_by_outcome() {
+		let (base, mut requests) = receiver().await;
+		let outside = Outside::new(true);
+		let source = "https://example.com/post/hello";
+
+		let target = base.join("linked").unwrap();
+		let delivery = attempt(outside, source, &job(&target, 0)).await;
+		assert_eq!(delivery.status, DeliveryStatus::Sent);
+		assert_eq!(
+			delivery.endpoint,
+			Some(base.join("accept").unwrap().to_string())
+		);
+		assert_eq!(delivery.response_code, Some(202));
+		requests.recv().await.unwrap();
+		let (path, body) = requests.recv().await.unwrap();
+		assert_eq!(path, "/accept");
+		let form: HashMap<String, String> = url::form_urlencoded::parse(body.as_bytes())
+			.into_owned()
+			.collect();
+		assert_eq!(form["source"], source);
+		assert_eq!(form["target"], target.as_str());
+
+		let target = base.join("plain").unwrap();
+		let delivery = attempt(outside, source, &job(&target, 0)).await;
+		assert_eq!(delivery.status, DeliveryStatus::NoEndpoint);
+
+		// Servers that are struggling are tried again later, but not forever
+		let target = base.join("busy").unwrap();
+		let delivery = attempt(outside, source, &job(&target, 2)).await;
+		assert_eq!(delivery.status, DeliveryStatus::Pending);
+		assert_eq!(delivery.response_code, Some(503));
+		assert!(delivery.next_attempt > Utc::now() + chrono::Duration::minutes(3));
+		let delivery = attempt(outside, source, &job(&target, MAX_ATTEMPTS - 1)).await;
+		assert_eq!(delivery.status, DeliveryStatus::Failed);
+
+		let target = base.join("refusing").unwrap();
+		let delivery = attempt(outside, source, &job(&target, 0)).await;
+		assert_eq!(delivery.status, DeliveryStatus::Failed);
+		assert_eq!(delivery.response_code, Some(400));
+	}
+
+	#[test]
+	fn bans_match_hosts_and_wildcard_subdomains() {
+		assert!(host_matches("example.com", "example.com"));
+		assert!(host_matches(" Example.COM. ", "example.com"));
+		assert!(host_matches("example.com", "EXAMPLE.com."));
+		assert!(!host_matches("example.com", "www.example.com"));
+		assert!(host_matches("*.example.com", "example.com"));
+		assert!(host_matches("*.example.com", "a.b.example.com"));
+		assert!(!host_matches("*.example.com", "badexample.com"));
+		assert!(!host_matches("*.example.com", "example.com.evil.net"));
+
+		let bans = ["*.spam.example".to_string()];
+		assert!(host_listed(
+			&bans,
+			&Url::parse("https://x.spam.example/post").unwrap()
+		));
+		assert!(!host_listed(
+			&bans,
+			&Url::parse("https://ham.example/").unwrap()
+		));
+		assert!(!host_listed(
+			&bans,
+			&Url::parse("mailto:me@x.spam.example").unwrap()
+		));
+	}
+}